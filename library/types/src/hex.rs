@@ -0,0 +1,383 @@
+//! Coordinates and math for hex grids.
+//!
+//! Axial and cube coordinates share the same `q` and `r` axes, where `q` goes
+//! east and `r` goes north, or north east for hexes with a pointy top. Offset
+//! coordinates are plain points in one of the [`HexLayout`]s.
+
+use crate::{lib::*, point::Point2};
+
+/// The layout of offset coordinates of a hex grid.
+///
+/// Each layout matches the hex grid topology of a tilemap with the same name.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum HexLayout {
+    /// Hexes with a pointy top where each row is offset by half a hex more
+    /// than the row below, the offset coordinates are axial coordinates.
+    Rows,
+    /// Hexes with a flat top where each column is offset by half a hex more
+    /// than the column to the west, the offset coordinates are axial
+    /// coordinates.
+    Cols,
+    /// Hexes with a pointy top where the even rows are offset by half a hex
+    /// to the east of the odd rows.
+    EvenRows,
+    /// Hexes with a pointy top where the odd rows are offset by half a hex to
+    /// the east of the even rows.
+    OddRows,
+    /// Hexes with a flat top where the even columns are offset by half a hex
+    /// to the north of the odd columns.
+    EvenCols,
+    /// Hexes with a flat top where the odd columns are offset by half a hex
+    /// to the north of the even columns.
+    OddCols,
+}
+
+impl HexLayout {
+    /// Returns `true` if the hexes have a pointy top.
+    pub fn is_pointy(self) -> bool {
+        matches!(
+            self,
+            HexLayout::Rows | HexLayout::EvenRows | HexLayout::OddRows
+        )
+    }
+}
+
+/// A point in axial coordinates of a hex grid.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize,
+)]
+pub struct AxialPoint {
+    /// Q value of a point, going east.
+    pub q: i32,
+    /// R value of a point, going north or north east.
+    pub r: i32,
+}
+
+impl AxialPoint {
+    /// The directions to the neighbors of a hex in clockwise order, starting
+    /// at north east for hexes with a pointy top and at north for hexes with
+    /// a flat top.
+    pub const DIRECTIONS: [AxialPoint; 6] = [
+        AxialPoint { q: 0, r: 1 },
+        AxialPoint { q: 1, r: 0 },
+        AxialPoint { q: 1, r: -1 },
+        AxialPoint { q: 0, r: -1 },
+        AxialPoint { q: -1, r: 0 },
+        AxialPoint { q: -1, r: 1 },
+    ];
+
+    /// Constructs a new point with a Q,R coordinate.
+    pub const fn new(q: i32, r: i32) -> AxialPoint {
+        AxialPoint { q, r }
+    }
+
+    /// Returns the neighbor in a direction of [`DIRECTIONS`], wrapping around
+    /// after the sixth.
+    ///
+    /// [`DIRECTIONS`]: AxialPoint::DIRECTIONS
+    pub fn neighbor(self, direction: usize) -> AxialPoint {
+        let index = direction % Self::DIRECTIONS.len();
+        Self::DIRECTIONS
+            .get(index)
+            .map_or(self, |offset| self + *offset)
+    }
+
+    /// Returns all neighbors in the order of [`DIRECTIONS`].
+    ///
+    /// [`DIRECTIONS`]: AxialPoint::DIRECTIONS
+    pub fn neighbors(self) -> [AxialPoint; 6] {
+        Self::DIRECTIONS.map(|offset| self + offset)
+    }
+
+    /// Returns the amount of steps between two hexes.
+    pub fn distance(self, other: AxialPoint) -> u32 {
+        CubePoint::from(self).distance(CubePoint::from(other))
+    }
+
+    /// Returns the hexes at exactly a distance in clockwise order, starting
+    /// in the first of the [`DIRECTIONS`].
+    ///
+    /// [`DIRECTIONS`]: AxialPoint::DIRECTIONS
+    pub fn ring(self, radius: u32) -> Vec<AxialPoint> {
+        if radius == 0 {
+            return vec![self];
+        }
+        let radius = radius as i32;
+        let mut ring = Vec::with_capacity(6 * radius as usize);
+        let mut hex = self + (self.neighbor(0) - self) * radius;
+        for side in 0..6 {
+            for _ in 0..radius {
+                ring.push(hex);
+                hex = hex.neighbor(side + 2);
+            }
+        }
+        ring
+    }
+
+    /// Returns the hexes up to a distance, ring by ring starting with this
+    /// hex.
+    pub fn spiral(self, radius: u32) -> Vec<AxialPoint> {
+        (0..=radius).flat_map(|ring| self.ring(ring)).collect()
+    }
+
+    /// Returns the hexes on a straight line to another hex, including both.
+    pub fn line_to(self, other: AxialPoint) -> Vec<AxialPoint> {
+        let distance = self.distance(other);
+        if distance == 0 {
+            return vec![self];
+        }
+        // Nudged so that lines along edges between hexes round the same way.
+        let (q0, r0) = (self.q as f32 + 1e-6, self.r as f32 + 2e-6);
+        let (q1, r1) = (other.q as f32 + 1e-6, other.r as f32 + 2e-6);
+        (0..=distance)
+            .map(|step| {
+                let t = step as f32 / distance as f32;
+                AxialPoint::round(q0 + (q1 - q0) * t, r0 + (r1 - r0) * t)
+            })
+            .collect()
+    }
+
+    /// Returns the hex that contains a fractional axial coordinate.
+    pub fn round(q: f32, r: f32) -> AxialPoint {
+        CubePoint::round(q, r, -q - r).into()
+    }
+
+    /// Converts offset coordinates of a layout into axial coordinates.
+    pub fn from_offset(point: Point2, layout: HexLayout) -> AxialPoint {
+        let Point2 { x, y } = point;
+        match layout {
+            HexLayout::Rows | HexLayout::Cols => AxialPoint::new(x, y),
+            HexLayout::EvenRows => AxialPoint::new(x - (y + 1).div_euclid(2), y),
+            HexLayout::OddRows => AxialPoint::new(x - y.div_euclid(2), y),
+            HexLayout::EvenCols => AxialPoint::new(x, y - (x + 1).div_euclid(2)),
+            HexLayout::OddCols => AxialPoint::new(x, y - x.div_euclid(2)),
+        }
+    }
+
+    /// Converts axial coordinates into offset coordinates of a layout.
+    pub fn to_offset(self, layout: HexLayout) -> Point2 {
+        let AxialPoint { q, r } = self;
+        match layout {
+            HexLayout::Rows | HexLayout::Cols => Point2::new(q, r),
+            HexLayout::EvenRows => Point2::new(q + (r + 1).div_euclid(2), r),
+            HexLayout::OddRows => Point2::new(q + r.div_euclid(2), r),
+            HexLayout::EvenCols => Point2::new(q, r + (q + 1).div_euclid(2)),
+            HexLayout::OddCols => Point2::new(q, r + q.div_euclid(2)),
+        }
+    }
+}
+
+impl Display for AxialPoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "({}, {})", self.q, self.r)
+    }
+}
+
+impl From<(i32, i32)> for AxialPoint {
+    fn from((q, r): (i32, i32)) -> AxialPoint {
+        AxialPoint { q, r }
+    }
+}
+
+impl From<CubePoint> for AxialPoint {
+    fn from(cube: CubePoint) -> AxialPoint {
+        AxialPoint {
+            q: cube.q,
+            r: cube.r,
+        }
+    }
+}
+
+impl Add for AxialPoint {
+    type Output = AxialPoint;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        AxialPoint {
+            q: self.q + rhs.q,
+            r: self.r + rhs.r,
+        }
+    }
+}
+
+impl Sub for AxialPoint {
+    type Output = AxialPoint;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        AxialPoint {
+            q: self.q - rhs.q,
+            r: self.r - rhs.r,
+        }
+    }
+}
+
+impl Mul<i32> for AxialPoint {
+    type Output = AxialPoint;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        AxialPoint {
+            q: self.q * rhs,
+            r: self.r * rhs,
+        }
+    }
+}
+
+impl Neg for AxialPoint {
+    type Output = AxialPoint;
+
+    fn neg(self) -> Self::Output {
+        AxialPoint {
+            q: -self.q,
+            r: -self.r,
+        }
+    }
+}
+
+/// A point in cube coordinates of a hex grid, where `q + r + s` is always
+/// zero.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize,
+)]
+pub struct CubePoint {
+    /// Q value of a point, going east.
+    pub q: i32,
+    /// R value of a point, going north or north east.
+    pub r: i32,
+    /// S value of a point, the negative sum of Q and R.
+    pub s: i32,
+}
+
+impl CubePoint {
+    /// Constructs a new point with a Q,R coordinate, calculating S.
+    pub const fn new(q: i32, r: i32) -> CubePoint {
+        CubePoint { q, r, s: -q - r }
+    }
+
+    /// Returns the amount of steps between two hexes.
+    pub fn distance(self, other: CubePoint) -> u32 {
+        let q = (self.q - other.q).unsigned_abs();
+        let r = (self.r - other.r).unsigned_abs();
+        let s = (self.s - other.s).unsigned_abs();
+        (q + r + s) / 2
+    }
+
+    /// Returns the hex that contains a fractional cube coordinate.
+    pub fn round(q: f32, r: f32, s: f32) -> CubePoint {
+        let (round_q, round_r, round_s) = (q.round(), r.round(), s.round());
+        let diff_q = (round_q - q).abs();
+        let diff_r = (round_r - r).abs();
+        let diff_s = (round_s - s).abs();
+
+        // The component that was rounded the most is the least accurate.
+        let (q, r) = (round_q as i32, round_r as i32);
+        let s = round_s as i32;
+        if diff_q > diff_r && diff_q > diff_s {
+            CubePoint { q: -r - s, r, s }
+        } else if diff_r > diff_s {
+            CubePoint { q, r: -q - s, s }
+        } else {
+            CubePoint { q, r, s: -q - r }
+        }
+    }
+}
+
+impl Display for CubePoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "({}, {}, {})", self.q, self.r, self.s)
+    }
+}
+
+impl From<AxialPoint> for CubePoint {
+    fn from(axial: AxialPoint) -> CubePoint {
+        CubePoint::new(axial.q, axial.r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every layout of offset coordinates.
+    const LAYOUTS: [HexLayout; 6] = [
+        HexLayout::Rows,
+        HexLayout::Cols,
+        HexLayout::EvenRows,
+        HexLayout::OddRows,
+        HexLayout::EvenCols,
+        HexLayout::OddCols,
+    ];
+
+    #[test]
+    fn ring_is_at_distance_in_order() {
+        let center = AxialPoint::new(2, -3);
+        assert_eq!(center.ring(0), vec![center]);
+        for radius in 1..5 {
+            let ring = center.ring(radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert_eq!(
+                ring.first(),
+                Some(&(center + AxialPoint::new(0, radius as i32)))
+            );
+            for (hex, next) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                assert_eq!(center.distance(*hex), radius);
+                assert_eq!(hex.distance(*next), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn spiral_covers_every_hex_once() {
+        let center = AxialPoint::new(-1, 4);
+        let spiral = center.spiral(3);
+        assert_eq!(spiral.len(), 1 + 6 + 12 + 18);
+        assert_eq!(spiral.first(), Some(&center));
+        let mut unique = spiral.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), spiral.len());
+        assert!(spiral.iter().all(|hex| center.distance(*hex) <= 3));
+    }
+
+    #[test]
+    fn line_to_steps_between_neighbors() {
+        let start = AxialPoint::new(0, 0);
+        assert_eq!(start.line_to(start), vec![start]);
+        for end in start.spiral(4) {
+            let line = start.line_to(end);
+            assert_eq!(line.len(), start.distance(end) as usize + 1);
+            assert_eq!(line.first(), Some(&start));
+            assert_eq!(line.last(), Some(&end));
+            for (hex, next) in line.iter().zip(line.iter().skip(1)) {
+                assert_eq!(hex.distance(*next), 1);
+            }
+        }
+        // A line along the edges between hexes always rounds the same way.
+        assert_eq!(
+            start.line_to(AxialPoint::new(1, 1)),
+            vec![start, AxialPoint::new(0, 1), AxialPoint::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn round_picks_the_closest_hex() {
+        assert_eq!(AxialPoint::round(0.0, 0.0), AxialPoint::new(0, 0));
+        assert_eq!(AxialPoint::round(0.9, -0.1), AxialPoint::new(1, 0));
+        assert_eq!(AxialPoint::round(-1.2, 2.1), AxialPoint::new(-1, 2));
+        // Rounding each axis on its own would give (1, 1), which is not the
+        // closest hex.
+        assert_eq!(AxialPoint::round(0.6, 0.6), AxialPoint::new(1, 0));
+    }
+
+    #[test]
+    fn offset_round_trip() {
+        for layout in LAYOUTS {
+            for y in -5..5 {
+                for x in -5..5 {
+                    let point = Point2::new(x, y);
+                    let hex = AxialPoint::from_offset(point, layout);
+                    assert_eq!(hex.to_offset(layout), point, "{:?}", layout);
+                    assert_eq!(AxialPoint::from_offset(hex.to_offset(layout), layout), hex);
+                }
+            }
+        }
+    }
+}
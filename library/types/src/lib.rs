@@ -24,6 +24,7 @@
 )]
 
 pub mod dimension;
+pub mod hex;
 pub mod point;
 pub mod prelude;
 
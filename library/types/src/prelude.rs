@@ -7,14 +7,20 @@
 //!
 //! * [`bevy_tilemap_types::dimension`]::{[`Dimension2`], [`Dimension3`]}
 //! common methods and helpers for dealing with dimensions.
+//! * [`bevy_tilemap_types::hex`]::{[`AxialPoint`], [`CubePoint`], [`HexLayout`]}
+//! coordinates and math for hex grids.
 //! * [`bevy_tilemap_types::point`]::{[`Point2`], [`Point3`]} common
 //! methods and helpers for dealing with points of the 2nd and 3rd dimension.
 //!
 //! [`bevy_tilemap_types::prelude::v0`]: crate::prelude::v0
 //! [`bevy_tilemap_types::dimension`]: crate::dimension
+//! [`bevy_tilemap_types::hex`]: crate::hex
 //! [`bevy_tilemap_types::point`]: crate::point
 //! [`Dimension2`]: crate::dimension::Dimension2
 //! [`Dimension3`]: crate::dimension::Dimension3
+//! [`AxialPoint`]: crate::hex::AxialPoint
+//! [`CubePoint`]: crate::hex::CubePoint
+//! [`HexLayout`]: crate::hex::HexLayout
 //! [`Point2`]: crate::point::Point2
 //! [`Point3`]: crate::point::Point3
 
@@ -22,6 +28,7 @@
 pub mod v0 {
     pub use crate::{
         dimension::{Dimension2, Dimension3},
+        hex::{AxialPoint, CubePoint, HexLayout},
         point::{Point2, Point3},
     };
 }
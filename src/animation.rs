@@ -0,0 +1,417 @@
+//! Animated tiles.
+//!
+//! An animation is registered on a tilemap for a sprite index. Every tile
+//! with that sprite index is then drawn with the sprite of the current frame
+//! of the animation instead. All tiles with the same sprite index share the
+//! same clock, so water and torches stay in sync across chunks.
+//!
+//! Advancing an animation only updates the sprite indices of the chunk meshes
+//! that have animated tiles, the tiles themselves are never modified.
+//!
+//! # Animating water
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::prelude::*;
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! // Sprite 4 cycles through the sprites 4, 5 and 6, each shown for 0.2s.
+//! let water = TileAnimation::from_indices(vec![4, 5, 6], 0.2, AnimationMode::Loop);
+//!
+//! let tilemap = TilemapBuilder::new()
+//!     .texture_atlas(texture_atlas_handle)
+//!     .texture_dimensions(32, 32)
+//!     .add_animation(4, water)
+//!     .finish()
+//!     .unwrap();
+//!
+//! assert!(tilemap.animation(4).is_some());
+//! ```
+
+use crate::lib::*;
+
+/// How an animation plays once it reaches its last frame.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum AnimationMode {
+    /// Starts over from the first frame.
+    Loop,
+    /// Stops at the last frame.
+    Once,
+    /// Plays the frames backwards to the first frame, then starts over.
+    PingPong,
+}
+
+impl Default for AnimationMode {
+    fn default() -> AnimationMode {
+        AnimationMode::Loop
+    }
+}
+
+/// A single frame of an animation.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AnimationFrame {
+    /// The sprite index in the texture atlas to draw for this frame.
+    pub sprite_index: usize,
+    /// How long the frame is drawn in seconds.
+    pub duration: f32,
+}
+
+/// An animation of a tile with a sequence of frames.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct TileAnimation {
+    /// The frames of the animation, in order.
+    pub frames: Vec<AnimationFrame>,
+    /// How the animation plays once it reaches its last frame.
+    pub mode: AnimationMode,
+}
+
+impl TileAnimation {
+    /// Constructs a new animation from frames with their own durations.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let torch = TileAnimation::new(
+    ///     vec![
+    ///         AnimationFrame { sprite_index: 10, duration: 0.5 },
+    ///         AnimationFrame { sprite_index: 11, duration: 0.1 },
+    ///     ],
+    ///     AnimationMode::Loop,
+    /// );
+    ///
+    /// assert_eq!(torch.duration(), 0.6);
+    /// ```
+    pub fn new(frames: Vec<AnimationFrame>, mode: AnimationMode) -> TileAnimation {
+        TileAnimation { frames, mode }
+    }
+
+    /// Constructs a new animation from sprite indices which are all drawn for
+    /// the same duration in seconds.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let belt = TileAnimation::from_indices(vec![20, 21, 22, 23], 0.1, AnimationMode::Loop);
+    ///
+    /// assert_eq!(belt.frames.len(), 4);
+    /// ```
+    pub fn from_indices<I: IntoIterator<Item = usize>>(
+        indices: I,
+        duration: f32,
+        mode: AnimationMode,
+    ) -> TileAnimation {
+        let frames = indices
+            .into_iter()
+            .map(|sprite_index| AnimationFrame {
+                sprite_index,
+                duration,
+            })
+            .collect();
+        TileAnimation { frames, mode }
+    }
+
+    /// Returns the frames in the order they are played in one cycle.
+    fn cycle_frames(&self) -> impl Iterator<Item = &AnimationFrame> + '_ {
+        // A ping pong does not repeat the first and last frames.
+        let backwards = match self.mode {
+            AnimationMode::PingPong => self.frames.len().saturating_sub(2),
+            AnimationMode::Loop | AnimationMode::Once => 0,
+        };
+        self.frames
+            .iter()
+            .chain(self.frames.iter().rev().skip(1).take(backwards))
+    }
+
+    /// Returns the duration of one cycle of the animation in seconds.
+    ///
+    /// A cycle of a ping pong animation includes playing the frames
+    /// backwards.
+    pub fn duration(&self) -> f32 {
+        self.cycle_frames().map(|frame| frame.duration).sum()
+    }
+
+    /// Returns the sprite index of the frame which is drawn at a time in
+    /// seconds since the animation started, or `None` if it has no frames.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let frames = vec![1, 2, 3];
+    /// let looped = TileAnimation::from_indices(frames.clone(), 1.0, AnimationMode::Loop);
+    /// let once = TileAnimation::from_indices(frames.clone(), 1.0, AnimationMode::Once);
+    /// let ping_pong = TileAnimation::from_indices(frames, 1.0, AnimationMode::PingPong);
+    ///
+    /// assert_eq!(looped.frame_at(1.5), Some(2));
+    /// assert_eq!(looped.frame_at(3.5), Some(1));
+    /// assert_eq!(once.frame_at(3.5), Some(3));
+    /// assert_eq!(ping_pong.frame_at(3.5), Some(2));
+    /// assert_eq!(ping_pong.frame_at(4.5), Some(1));
+    /// ```
+    pub fn frame_at(&self, time: f64) -> Option<usize> {
+        let first = self.frames.first()?;
+        let duration = f64::from(self.duration());
+        if duration <= 0.0 {
+            return Some(first.sprite_index);
+        }
+
+        let mut time = match self.mode {
+            AnimationMode::Once if time >= duration => {
+                return self.frames.last().map(|frame| frame.sprite_index);
+            }
+            AnimationMode::Once => time.max(0.0),
+            AnimationMode::Loop | AnimationMode::PingPong => time.rem_euclid(duration),
+        };
+        let mut last = first;
+        for frame in self.cycle_frames() {
+            let frame_duration = f64::from(frame.duration);
+            if time < frame_duration {
+                return Some(frame.sprite_index);
+            }
+            time -= frame_duration;
+            last = frame;
+        }
+
+        // Only reached through rounding at the very end of a cycle.
+        Some(last.sprite_index)
+    }
+}
+
+/// The animations of a tilemap and their clock.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct TileAnimations {
+    /// The animations by the sprite index they animate.
+    animations: HashMap<usize, TileAnimation>,
+    /// The seconds since the animations started.
+    #[serde(skip)]
+    elapsed: f64,
+    /// The sprite index currently drawn for every animated sprite index.
+    #[serde(skip)]
+    frames: HashMap<usize, usize>,
+}
+
+impl TileAnimations {
+    /// Adds an animation for a sprite index, replacing any previous one.
+    pub(crate) fn insert(&mut self, sprite_index: usize, animation: TileAnimation) {
+        self.animations.insert(sprite_index, animation);
+    }
+
+    /// Removes the animation of a sprite index and returns it.
+    pub(crate) fn remove(&mut self, sprite_index: usize) -> Option<TileAnimation> {
+        self.animations.remove(&sprite_index)
+    }
+
+    /// Returns the animation of a sprite index.
+    pub(crate) fn get(&self, sprite_index: usize) -> Option<&TileAnimation> {
+        self.animations.get(&sprite_index)
+    }
+
+    /// Returns `true` if there are neither animations nor frames to undo.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.animations.is_empty() && self.frames.is_empty()
+    }
+
+    /// Advances the clock of the animations by seconds.
+    ///
+    /// Returns the animated sprite indices which are drawn with a different
+    /// sprite than before, including those of animations added or removed.
+    pub(crate) fn advance(&mut self, seconds: f64) -> HashSet<usize> {
+        self.elapsed += seconds;
+        let elapsed = self.elapsed;
+        let frames = self
+            .animations
+            .iter()
+            .filter_map(|(sprite_index, animation)| {
+                Some((*sprite_index, animation.frame_at(elapsed)?))
+            })
+            .collect::<HashMap<usize, usize>>();
+
+        let mut changed = HashSet::default();
+        for (sprite_index, frame) in frames.iter() {
+            if self.frames.get(sprite_index) != Some(frame) {
+                changed.insert(*sprite_index);
+            }
+        }
+        for sprite_index in self.frames.keys() {
+            if !frames.contains_key(sprite_index) {
+                changed.insert(*sprite_index);
+            }
+        }
+        self.frames = frames;
+        changed
+    }
+
+    /// Replaces the animated sprite indices with the sprite index of their
+    /// current frame.
+    pub(crate) fn apply(&self, indices: &mut [f32]) {
+        if self.frames.is_empty() {
+            return;
+        }
+        for index in indices.iter_mut() {
+            if let Some(frame) = self.frames.get(&(*index as usize)) {
+                *index = *frame as f32;
+            }
+        }
+    }
+}
+
+/// The sprite indices of the mesh of a chunk before they are animated.
+#[derive(Clone, PartialEq, Debug)]
+struct AnimatedChunk {
+    /// The mesh the sprite indices are of.
+    mesh: Handle<Mesh>,
+    /// The edits of the chunk when the sprite indices were taken.
+    edits: usize,
+    /// The animated sprite indices in the chunk.
+    animated: HashSet<usize>,
+    /// The sprite indices of the mesh, empty if none of them are animated.
+    indexes: Vec<f32>,
+}
+
+/// The sprite indices of the chunk meshes before they are animated, so they
+/// are only taken from the tiles again after a chunk was edited.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub(crate) struct AnimatedChunks {
+    /// The sprite indices of each chunk by its point.
+    chunks: HashMap<Point2, AnimatedChunk>,
+}
+
+impl AnimatedChunks {
+    /// Forgets the sprite indices of every chunk.
+    pub(crate) fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// Forgets the sprite indices of the chunks for which the function
+    /// returns `false`.
+    pub(crate) fn retain<F: FnMut(Point2) -> bool>(&mut self, mut f: F) {
+        self.chunks.retain(|point, _| f(*point));
+    }
+
+    /// Returns the sprite indices of the mesh of a chunk with the current
+    /// frames of the animations, or `None` if none of the changed sprite
+    /// indices are in it.
+    ///
+    /// The sprite indices before they are animated are only taken again with
+    /// the function if the chunk was edited or has another mesh since.
+    pub(crate) fn frames<F>(
+        &mut self,
+        point: Point2,
+        mesh: &Handle<Mesh>,
+        edits: usize,
+        animations: &TileAnimations,
+        changed: &HashSet<usize>,
+        indexes: F,
+    ) -> Option<Vec<f32>>
+    where
+        F: FnOnce() -> Vec<f32>,
+    {
+        let stale = self
+            .chunks
+            .get(&point)
+            .map_or(true, |chunk| chunk.mesh != *mesh || chunk.edits != edits);
+        let mut frames = if stale {
+            let indexes = indexes();
+            let animated = indexes
+                .iter()
+                .map(|index| *index as usize)
+                .filter(|index| animations.get(*index).is_some())
+                .collect::<HashSet<usize>>();
+            // Sprite indices of removed animations are drawn as they are once.
+            let affected = indexes
+                .iter()
+                .any(|index| changed.contains(&(*index as usize)));
+            let chunk = AnimatedChunk {
+                mesh: mesh.clone(),
+                edits,
+                indexes: if animated.is_empty() {
+                    Vec::new()
+                } else {
+                    indexes.clone()
+                },
+                animated,
+            };
+            self.chunks.insert(point, chunk);
+            if !affected {
+                return None;
+            }
+            indexes
+        } else {
+            let chunk = self.chunks.get(&point)?;
+            if !chunk.animated.iter().any(|index| changed.contains(index)) {
+                return None;
+            }
+            chunk.indexes.clone()
+        };
+        animations.apply(&mut frames);
+        Some(frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_and_apply() {
+        let mut animations = TileAnimations::default();
+        animations.insert(
+            4,
+            TileAnimation::from_indices(vec![4, 5], 1.0, AnimationMode::Loop),
+        );
+
+        let changed = animations.advance(0.5);
+        assert!(changed.contains(&4));
+        assert!(animations.advance(0.25).is_empty());
+
+        let mut indices = vec![4.0, 1.0, 4.0];
+        animations.apply(&mut indices);
+        assert_eq!(indices, vec![4.0, 1.0, 4.0]);
+
+        assert!(animations.advance(0.5).contains(&4));
+        animations.apply(&mut indices);
+        assert_eq!(indices, vec![5.0, 1.0, 5.0]);
+
+        animations.remove(4);
+        assert!(!animations.is_empty());
+        assert!(animations.advance(0.5).contains(&4));
+        assert!(animations.is_empty());
+    }
+
+    #[test]
+    fn animated_chunks_keep_indexes_until_edited() {
+        let mut animations = TileAnimations::default();
+        animations.insert(
+            4,
+            TileAnimation::from_indices(vec![4, 5], 1.0, AnimationMode::Loop),
+        );
+        let mesh = Handle::weak(HandleId::random::<Mesh>());
+        let mut chunks = AnimatedChunks::default();
+        let point = Point2::new(0, 0);
+
+        let changed = animations.advance(1.5);
+        let frames = chunks.frames(point, &mesh, 0, &animations, &changed, || vec![4.0, 1.0]);
+        assert_eq!(frames, Some(vec![5.0, 1.0]));
+
+        // The sprite indices are kept while the chunk is not edited.
+        let changed = animations.advance(1.0);
+        let frames = chunks.frames(point, &mesh, 0, &animations, &changed, || {
+            unreachable!("the sprite indices are kept")
+        });
+        assert_eq!(frames, Some(vec![4.0, 1.0]));
+
+        // Chunks without the changed sprite indices are left alone.
+        let changed = animations.advance(1.0);
+        let frames = chunks.frames(Point2::new(1, 0), &mesh, 0, &animations, &changed, || {
+            vec![1.0, 2.0]
+        });
+        assert_eq!(frames, None);
+
+        let frames = chunks.frames(point, &mesh, 1, &animations, &changed, || vec![1.0, 4.0]);
+        assert_eq!(frames, Some(vec![1.0, 5.0]));
+    }
+}
@@ -0,0 +1,316 @@
+//! Autotiling with terrains.
+//!
+//! Instead of choosing sprites by hand, a terrain is painted onto a tilemap
+//! with [`Tilemap::insert_terrain`]. The sprite of every painted tile is then
+//! chosen by the [`AutotileRules`] of its terrain by looking at which of its
+//! neighbors are painted with the same terrain. Whenever a tile is painted or
+//! cleared, the tiles around it are chosen again, also across chunks.
+//!
+//! The kinds of rules are:
+//!
+//! * [`AutotileKind::Bitmask`], 16 sprites chosen by the 4 edges of a square
+//! tile.
+//! * [`AutotileKind::Blob`], 47 sprites chosen by the 4 edges and the 4
+//! corners of a square tile.
+//! * [`AutotileKind::WangCorners`], 16 sprites of a 2-corner Wang set chosen by
+//! which corners of a square tile are surrounded by the terrain.
+//! * [`AutotileKind::Hex`], 64 sprites chosen by the 6 edges of a hex tile.
+//!
+//! # Painting grass
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::prelude::*;
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! // The 16 grass sprites start at sprite index 32 of the atlas.
+//! const GRASS: u32 = 0;
+//! let grass = AutotileRules::sequential(AutotileKind::Bitmask, 32);
+//!
+//! let mut tilemap = TilemapBuilder::new()
+//!     .texture_atlas(texture_atlas_handle)
+//!     .texture_dimensions(32, 32)
+//!     .auto_chunk()
+//!     .add_terrain(GRASS, grass)
+//!     .finish()
+//!     .unwrap();
+//!
+//! tilemap.insert_terrain((0, 0), 0, GRASS).unwrap();
+//! tilemap.insert_terrain((1, 0), 0, GRASS).unwrap();
+//!
+//! // The east edge of the first tile and the west edge of the second match.
+//! assert_eq!(tilemap.get_tile((0, 0), 0).unwrap().index, 32 + 0b0010);
+//! assert_eq!(tilemap.get_tile((1, 0), 0).unwrap().index, 32 + 0b1000);
+//! ```
+//!
+//! [`Tilemap::insert_terrain`]: crate::Tilemap::insert_terrain
+
+use crate::lib::*;
+
+/// The kinds of rules to choose a sprite of a terrain by its neighbors.
+///
+/// The sprites of a kind are ordered by a bit mask where each bit is set if
+/// the neighbor in that direction has the same terrain.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum AutotileKind {
+    /// 16 sprites for square topologies, by the edges with the bits north
+    /// `1`, east `2`, south `4` and west `8`.
+    Bitmask,
+    /// 47 sprites for square topologies, by the edges and corners with the
+    /// bits north `1`, north east `2`, east `4`, south east `8`, south `16`,
+    /// south west `32`, west `64` and north west `128`.
+    ///
+    /// A corner is only counted if both edges next to it are too, which
+    /// leaves 47 masks. The sprites are in the ascending order of those masks.
+    Blob,
+    /// 16 sprites of a 2-corner Wang set for square topologies, by the corners
+    /// with the bits north east `1`, south east `2`, south west `4` and north
+    /// west `8`.
+    ///
+    /// A corner is counted if all 3 tiles that share it with the tile have
+    /// the same terrain.
+    WangCorners,
+    /// 64 sprites for hex topologies, by the edges in clockwise order starting
+    /// at the first edge at or after north, with the bits `1` to `32`.
+    ///
+    /// For hexes with a pointy top the first edge is north east, for hexes
+    /// with a flat top it is north.
+    Hex,
+}
+
+impl AutotileKind {
+    /// Returns the amount of sprites the rules of this kind need.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// assert_eq!(AutotileKind::Bitmask.sprite_count(), 16);
+    /// assert_eq!(AutotileKind::Blob.sprite_count(), 47);
+    /// assert_eq!(AutotileKind::WangCorners.sprite_count(), 16);
+    /// assert_eq!(AutotileKind::Hex.sprite_count(), 64);
+    /// ```
+    pub fn sprite_count(self) -> usize {
+        match self {
+            AutotileKind::Bitmask | AutotileKind::WangCorners => 16,
+            AutotileKind::Blob => 47,
+            AutotileKind::Hex => 64,
+        }
+    }
+
+    /// Returns `true` if this kind of rules is meant for hex topologies.
+    pub fn is_hex(self) -> bool {
+        self == AutotileKind::Hex
+    }
+
+    /// Takes which neighbors have the same terrain, in clockwise order
+    /// starting at north, and returns the position of the sprite to use.
+    pub(crate) fn sprite_position(self, same: &[bool]) -> usize {
+        let is_same = |direction: usize| same.get(direction).copied().unwrap_or(false);
+        let mask = |directions: &[usize]| {
+            directions
+                .iter()
+                .enumerate()
+                .filter(|(_, direction)| is_same(**direction))
+                .fold(0, |mask, (bit, _)| mask | 1 << bit)
+        };
+        match self {
+            AutotileKind::Bitmask => mask(&[0, 2, 4, 6]),
+            AutotileKind::Blob => {
+                let mask = blob_mask(mask(&[0, 1, 2, 3, 4, 5, 6, 7]));
+                (0..mask).filter(|m| blob_mask(*m) == *m).count()
+            }
+            AutotileKind::WangCorners => {
+                let corner = |direction: usize| {
+                    is_same(direction) && is_same(direction + 1) && is_same((direction + 2) % 8)
+                };
+                [0, 2, 4, 6]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, direction)| corner(**direction))
+                    .fold(0, |mask, (bit, _)| mask | 1 << bit)
+            }
+            AutotileKind::Hex => mask(&[0, 1, 2, 3, 4, 5]),
+        }
+    }
+}
+
+/// Clears the corners of a blob mask that do not have both edges next to
+/// them set.
+fn blob_mask(mask: usize) -> usize {
+    let is_set = |bit: usize| mask & (1 << (bit % 8)) != 0;
+    (0..8).fold(0, |blob, bit| {
+        let corner = bit % 2 == 1;
+        if is_set(bit) && (!corner || (is_set(bit + 7) && is_set(bit + 1))) {
+            blob | 1 << bit
+        } else {
+            blob
+        }
+    })
+}
+
+/// The rules that choose the sprites of a terrain.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct AutotileRules {
+    /// The kind of rules.
+    pub kind: AutotileKind,
+    /// The sprite indices in the texture atlas in the order of the kind.
+    pub sprites: Vec<usize>,
+}
+
+impl AutotileRules {
+    /// Constructs new rules from a kind and the sprite indices in the order of
+    /// the kind.
+    ///
+    /// The amount of sprites must be the [`sprite_count`] of the kind, this is
+    /// checked when the terrain is added to a tilemap.
+    ///
+    /// [`sprite_count`]: AutotileKind::sprite_count
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let sprites = (0..16).map(|n| 15 - n).collect();
+    /// let rules = AutotileRules::new(AutotileKind::WangCorners, sprites);
+    ///
+    /// assert_eq!(rules.sprites[0], 15);
+    /// ```
+    pub fn new(kind: AutotileKind, sprites: Vec<usize>) -> AutotileRules {
+        AutotileRules { kind, sprites }
+    }
+
+    /// Constructs new rules where the sprites of the kind are in order in the
+    /// texture atlas, starting at the first sprite index.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let rules = AutotileRules::sequential(AutotileKind::Blob, 100);
+    ///
+    /// assert_eq!(rules.sprites.len(), 47);
+    /// assert_eq!(rules.sprites[46], 146);
+    /// ```
+    pub fn sequential(kind: AutotileKind, first_sprite: usize) -> AutotileRules {
+        let sprites = (first_sprite..first_sprite + kind.sprite_count()).collect();
+        AutotileRules { kind, sprites }
+    }
+
+    /// Returns `true` if the rules have the amount of sprites their kind
+    /// needs and fit the topology.
+    pub(crate) fn is_valid(&self, hex: bool) -> bool {
+        self.sprites.len() == self.kind.sprite_count() && self.kind.is_hex() == hex
+    }
+
+    /// Takes which neighbors have the same terrain, in clockwise order
+    /// starting at north, and returns the sprite index to use.
+    pub(crate) fn sprite_index(&self, same: &[bool]) -> Option<usize> {
+        self.sprites.get(self.kind.sprite_position(same)).copied()
+    }
+}
+
+/// The terrains of a tilemap and where they are painted.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Autotiles {
+    /// The rules of every terrain.
+    terrains: HashMap<u32, AutotileRules>,
+    /// The terrain painted at a global tile point and sprite order.
+    painted: HashMap<(Point3, usize), u32>,
+}
+
+impl Autotiles {
+    /// Adds the rules of a terrain, replacing any previous ones.
+    pub(crate) fn insert(&mut self, terrain: u32, rules: AutotileRules) {
+        self.terrains.insert(terrain, rules);
+    }
+
+    /// Removes the rules of a terrain and every tile painted with it.
+    pub(crate) fn remove(&mut self, terrain: u32) -> Option<AutotileRules> {
+        self.painted.retain(|_, painted| *painted != terrain);
+        self.terrains.remove(&terrain)
+    }
+
+    /// Returns the rules of a terrain.
+    pub(crate) fn rules(&self, terrain: u32) -> Option<&AutotileRules> {
+        self.terrains.get(&terrain)
+    }
+
+    /// Returns an iterator of all terrains with their rules.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&u32, &AutotileRules)> {
+        self.terrains.iter()
+    }
+
+    /// Returns `true` if no tile is painted with a terrain.
+    pub(crate) fn is_unpainted(&self) -> bool {
+        self.painted.is_empty()
+    }
+
+    /// Paints a terrain at a point and sprite order.
+    pub(crate) fn paint(&mut self, point: Point3, sprite_order: usize, terrain: u32) {
+        self.painted.insert((point, sprite_order), terrain);
+    }
+
+    /// Removes the terrain painted at a point and sprite order and returns it.
+    pub(crate) fn erase(&mut self, point: Point3, sprite_order: usize) -> Option<u32> {
+        self.painted.remove(&(point, sprite_order))
+    }
+
+    /// Returns the points and sprite orders painted with a terrain.
+    pub(crate) fn painted_with(&self, terrain: u32) -> Vec<(Point3, usize)> {
+        self.painted
+            .iter()
+            .filter(|(_, painted)| **painted == terrain)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    /// Returns the terrain painted at a point and sprite order.
+    pub(crate) fn terrain(&self, point: Point3, sprite_order: usize) -> Option<u32> {
+        self.painted.get(&(point, sprite_order)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blob_positions() {
+        let masks = (0..256).filter(|m| blob_mask(*m) == *m).count();
+        assert_eq!(masks, 47);
+
+        let none = [false; 8];
+        assert_eq!(AutotileKind::Blob.sprite_position(&none), 0);
+        let all = [true; 8];
+        assert_eq!(AutotileKind::Blob.sprite_position(&all), 46);
+
+        // A corner without its edges is ignored.
+        let corner = [false, true, false, false, false, false, false, false];
+        assert_eq!(AutotileKind::Blob.sprite_position(&corner), 0);
+
+        // North, north east and east are the mask 7, after 0, 1, 4 and 5.
+        let north_east = [true, true, true, false, false, false, false, false];
+        assert_eq!(AutotileKind::Blob.sprite_position(&north_east), 4);
+    }
+
+    #[test]
+    fn wang_corners() {
+        let north_east = [true, true, true, false, false, false, false, false];
+        assert_eq!(
+            AutotileKind::WangCorners.sprite_position(&north_east),
+            0b0001
+        );
+
+        let north_west = [true, false, false, false, false, false, true, true];
+        assert_eq!(
+            AutotileKind::WangCorners.sprite_position(&north_west),
+            0b1000
+        );
+
+        let all = [true; 8];
+        assert_eq!(AutotileKind::WangCorners.sprite_position(&all), 0b1111);
+    }
+}
@@ -21,7 +21,7 @@ pub(super) trait Layer: 'static {
     fn clear(&mut self);
 
     /// Takes all the tiles in the layer and returns attributes for the renderer.
-    fn tiles_to_attributes(&self, dimension: Dimension3) -> (Vec<f32>, Vec<[f32; 4]>);
+    fn tiles_to_attributes(&self, dimension: Dimension3) -> (Vec<f32>, Vec<[f32; 4]>, Vec<f32>);
 }
 
 /// A layer with dense sprite tiles.
@@ -94,7 +94,7 @@ impl Layer for DenseLayer {
         self.tiles.clear();
     }
 
-    fn tiles_to_attributes(&self, _dimension: Dimension3) -> (Vec<f32>, Vec<[f32; 4]>) {
+    fn tiles_to_attributes(&self, _dimension: Dimension3) -> (Vec<f32>, Vec<[f32; 4]>, Vec<f32>) {
         crate::chunk::raw_tile::dense_tiles_to_attributes(&self.tiles)
     }
 }
@@ -148,7 +148,7 @@ impl Layer for SparseLayer {
         self.tiles.clear();
     }
 
-    fn tiles_to_attributes(&self, dimension: Dimension3) -> (Vec<f32>, Vec<[f32; 4]>) {
+    fn tiles_to_attributes(&self, dimension: Dimension3) -> (Vec<f32>, Vec<[f32; 4]>, Vec<f32>) {
         crate::chunk::raw_tile::sparse_tiles_to_attributes(dimension, &self.tiles)
     }
 }
@@ -224,3 +224,83 @@ impl Default for SpriteLayer {
         }
     }
 }
+
+/// A layer with a data value for tiles, which is never drawn.
+///
+/// Like sprite layers it is either dense or sparse.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(super) enum DataLayer {
+    /// A value for every tile, which is 0 until it is set.
+    Dense(Vec<i32>),
+    /// A map of the values of the tiles that were set.
+    Sparse(HashMap<usize, i32>),
+}
+
+impl DataLayer {
+    /// Constructs a new data layer of a kind with the area of the chunk.
+    pub fn new(kind: LayerKind, area: usize) -> DataLayer {
+        match kind {
+            LayerKind::Dense => DataLayer::Dense(vec![0; area]),
+            LayerKind::Sparse => DataLayer::Sparse(HashMap::default()),
+        }
+    }
+
+    /// Gets the value of a tile by an index.
+    pub fn get(&self, index: usize) -> Option<i32> {
+        match self {
+            DataLayer::Dense(values) => values.get(index).copied(),
+            DataLayer::Sparse(values) => values.get(&index).copied(),
+        }
+    }
+
+    /// Sets the value of a tile at an index.
+    pub fn set(&mut self, index: usize, value: i32) {
+        match self {
+            DataLayer::Dense(values) => {
+                if let Some(inner_value) = values.get_mut(index) {
+                    *inner_value = value;
+                } else {
+                    warn!(
+                        "data is out of bounds at index {} and can not be set",
+                        index
+                    );
+                }
+            }
+            DataLayer::Sparse(values) => {
+                values.insert(index, value);
+            }
+        }
+    }
+
+    /// Returns the indices and values of the tiles, leaving out the ones that
+    /// are 0 in dense layers.
+    pub fn values(&self) -> Vec<(usize, i32)> {
+        match self {
+            DataLayer::Dense(values) => values
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| **value != 0)
+                .map(|(index, value)| (index, *value))
+                .collect(),
+            DataLayer::Sparse(values) => values
+                .iter()
+                .map(|(index, value)| (*index, *value))
+                .collect(),
+        }
+    }
+
+    /// Removes the value of a tile at an index, which is 0 again for dense
+    /// layers.
+    pub fn remove(&mut self, index: usize) {
+        match self {
+            DataLayer::Dense(values) => {
+                if let Some(value) = values.get_mut(index) {
+                    *value = 0;
+                }
+            }
+            DataLayer::Sparse(values) => {
+                values.remove(&index);
+            }
+        }
+    }
+}
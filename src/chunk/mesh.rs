@@ -15,6 +15,10 @@ impl ChunkMesh {
     pub(crate) const ATTRIBUTE_TILE_INDEX: &'static str = "Vertex_Tile_Index";
     /// Vertex attribute of the tile's color.
     pub(crate) const ATTRIBUTE_TILE_COLOR: &'static str = "Vertex_Tile_Color";
+    /// Vertex attribute of the tile's flip and rotation.
+    pub(crate) const ATTRIBUTE_TILE_FLAGS: &'static str = "Vertex_Tile_Flags";
+    /// Vertex attribute of the tile's brightness from the fog of war.
+    pub(crate) const ATTRIBUTE_TILE_FOG: &'static str = "Vertex_Tile_Fog";
 
     /// Constructs a new chunk mesh.
     pub(crate) fn new(dimensions: Dimension3, layers: u32, z_offset: Vec2) -> ChunkMesh {
@@ -67,11 +67,21 @@ pub mod raw_tile;
 pub(crate) mod render;
 /// Systems for chunks.
 pub(crate) mod system;
+/// Building chunks on the task pools.
+pub(crate) mod task;
+/// Typed values attached to tiles.
+mod tile_data;
 
-use crate::{lib::*, tile::Tile};
+use crate::{
+    fog::{FogOfWar, FogState},
+    lib::*,
+    tile::{Tile, TileData},
+};
 pub use layer::LayerKind;
-use layer::{DenseLayer, LayerKindInner, SparseLayer, SpriteLayer};
+use layer::{DataLayer, DenseLayer, LayerKindInner, SparseLayer, SpriteLayer};
 pub use raw_tile::RawTile;
+pub(crate) use tile_data::TileDataTypes;
+use tile_data::{TileDataMap, TypedValues};
 
 /// A type for sprite layers.
 type SpriteLayers = Vec<Option<SpriteLayer>>;
@@ -85,11 +95,28 @@ pub(crate) struct Chunk {
     z_layers: Vec<SpriteLayers>,
     /// Ephemeral user data that can be used for flags or other purposes.
     user_data: u128,
+    /// The typed metadata values of the chunk.
+    #[serde(default)]
+    metadata: TypedValues,
+    /// The fog states of the tiles, empty until one is set.
+    #[serde(default)]
+    fog: Vec<FogState>,
+    /// The data layers of the chunk, which are added when a value is first
+    /// set.
+    #[serde(default)]
+    data_layers: HashMap<usize, DataLayer>,
+    /// The typed values attached to the tiles of the chunk.
+    #[serde(default)]
+    tile_data: TileDataMap,
     /// A chunks mesh used for rendering.
     #[serde(skip)]
     mesh: Option<Handle<Mesh>>,
     /// An entity which is tied to this chunk.
     entity: Option<Entity>,
+    /// How many times the chunk was modified, to tell if a mesh that was
+    /// built from a copy of it is still up to date.
+    #[serde(skip)]
+    edits: usize,
 }
 
 impl Chunk {
@@ -103,8 +130,13 @@ impl Chunk {
             point,
             z_layers: vec![vec![None; sprite_layers.len()]; dimensions.depth as usize],
             user_data: 0,
+            metadata: TypedValues::default(),
+            fog: Vec::new(),
+            data_layers: HashMap::default(),
+            tile_data: TileDataMap::default(),
             mesh: None,
             entity: None,
+            edits: 0,
         };
 
         for (sprite_order, kind) in sprite_layers.iter().enumerate() {
@@ -116,6 +148,23 @@ impl Chunk {
         chunk
     }
 
+    /// Returns a copy of the chunk with only what is needed to build its
+    /// mesh.
+    pub(crate) fn render_copy(&self) -> Chunk {
+        Chunk {
+            point: self.point,
+            z_layers: self.z_layers.clone(),
+            user_data: 0,
+            metadata: TypedValues::default(),
+            fog: self.fog.clone(),
+            data_layers: HashMap::default(),
+            tile_data: TileDataMap::default(),
+            mesh: None,
+            entity: None,
+            edits: self.edits,
+        }
+    }
+
     /// Adds a layer from a layer kind, the z layer, and dimensions of the
     /// chunk.
     pub(crate) fn add_sprite_layer(
@@ -130,7 +179,8 @@ impl Chunk {
                     let tiles = vec![
                         RawTile {
                             index: 0,
-                            color: Color::rgba(0.0, 0.0, 0.0, 0.0)
+                            color: Color::rgba(0.0, 0.0, 0.0, 0.0),
+                            ..Default::default()
                         };
                         (dimensions.width * dimensions.height) as usize
                     ];
@@ -174,6 +224,33 @@ impl Chunk {
         self.point
     }
 
+    /// Returns the user data of the chunk.
+    pub(crate) fn user_data(&self) -> u128 {
+        self.user_data
+    }
+
+    /// Sets the user data of the chunk.
+    pub(crate) fn set_user_data(&mut self, user_data: u128) {
+        self.user_data = user_data;
+    }
+
+    /// Returns a reference to the typed metadata values of the chunk.
+    pub(crate) fn metadata(&self) -> &TypedValues {
+        &self.metadata
+    }
+
+    /// Returns a mutable reference to the typed metadata values of the chunk.
+    pub(crate) fn metadata_mut(&mut self) -> &mut TypedValues {
+        &mut self.metadata
+    }
+
+    /// Gives the deserialized metadata and tile data values of the registered
+    /// types their type back.
+    pub(crate) fn type_data(&mut self, types: &TileDataTypes) {
+        self.metadata.type_values(types);
+        self.tile_data.type_values(types);
+    }
+
     /// Moves a layer from a z layer to another.
     pub(crate) fn move_sprite_layer(&mut self, from_layer_z: usize, to_layer_z: usize) {
         for sprite_layers in &mut self.z_layers {
@@ -185,6 +262,23 @@ impl Chunk {
             }
             sprite_layers.swap(from_layer_z, to_layer_z);
         }
+        self.tile_data.swap_sprite_orders(from_layer_z, to_layer_z);
+    }
+
+    /// Inserts a layer at a sprite order, moving the sprite orders of the
+    /// layers after it up by one. This reverses removing a layer.
+    pub(crate) fn insert_sprite_layer(
+        &mut self,
+        kind: &LayerKind,
+        sprite_order: usize,
+        dimensions: Dimension3,
+    ) {
+        for z_layer in &mut self.z_layers {
+            if sprite_order <= z_layer.len() {
+                z_layer.insert(sprite_order, None);
+            }
+        }
+        self.add_sprite_layer(kind, sprite_order, dimensions);
     }
 
     /// Removes a layer from the specified layer.
@@ -216,6 +310,8 @@ impl Chunk {
                 let raw_tile = RawTile {
                     index: tile.sprite_index,
                     color: tile.tint,
+                    flip: tile.flip,
+                    rotation: tile.rotation,
                 };
                 if let Some(layer) = layer {
                     layer.inner.as_mut().set_tile(index, raw_tile);
@@ -265,6 +361,16 @@ impl Chunk {
         self.entity
     }
 
+    /// Counts a modification of the chunk.
+    pub(crate) fn add_edit(&mut self) {
+        self.edits = self.edits.wrapping_add(1);
+    }
+
+    /// Returns how many times the chunk was modified.
+    pub(crate) fn edits(&self) -> usize {
+        self.edits
+    }
+
     /// Gets the layers entity, if any. Useful for despawning.
     pub(crate) fn take_entity(&mut self) -> Option<Entity> {
         self.entity.take()
@@ -302,10 +408,139 @@ impl Chunk {
         })
     }
 
-    /// Clears a given layer of all sprites.
-    pub(crate) fn clear_layer(&mut self, layer: usize) {
-        if let Some(sprite_layer) = self.z_layers.get_mut(layer) {
-            for layer in sprite_layer.iter_mut().flatten() {
+    /// Gets the indices of all the tiles that exist with their sprite order
+    /// and z depth.
+    pub(crate) fn tile_indices(&self) -> Vec<(usize, usize, usize)> {
+        let mut indices = Vec::new();
+        for (z_depth, sprite_layers) in self.z_layers.iter().enumerate() {
+            for (sprite_order, layer) in sprite_layers.iter().enumerate() {
+                if let Some(layer) = layer {
+                    for index in layer.inner.as_ref().get_tile_indices() {
+                        indices.push((index, sprite_order, z_depth));
+                    }
+                }
+            }
+        }
+        indices
+    }
+
+    /// Gets the fog state of a tile from its index in the chunk, ignoring the
+    /// z depth.
+    pub(crate) fn fog_state(&self, index: usize) -> FogState {
+        self.fog.get(index).copied().unwrap_or_default()
+    }
+
+    /// Sets the fog state of a tile from its index in the chunk, ignoring the
+    /// z depth, with the area of the chunk.
+    pub(crate) fn set_fog_state(&mut self, index: usize, state: FogState, area: usize) {
+        if self.fog.is_empty() {
+            self.fog = vec![FogState::Hidden; area];
+        }
+        if let Some(fog) = self.fog.get_mut(index) {
+            *fog = state;
+        }
+    }
+
+    /// Gets the data value of a tile from an index and data layer.
+    pub(crate) fn get_data(&self, index: usize, data_layer: usize) -> Option<i32> {
+        self.data_layers
+            .get(&data_layer)
+            .and_then(|layer| layer.get(index))
+    }
+
+    /// Sets the data value of a tile at an index, adding the data layer with
+    /// its kind and the area of the chunk if needed.
+    pub(crate) fn set_data(
+        &mut self,
+        index: usize,
+        data_layer: usize,
+        kind: LayerKind,
+        value: i32,
+        area: usize,
+    ) {
+        self.data_layers
+            .entry(data_layer)
+            .or_insert_with(|| DataLayer::new(kind, area))
+            .set(index, value);
+    }
+
+    /// Removes the data value of a tile at an index and data layer.
+    pub(crate) fn remove_data(&mut self, index: usize, data_layer: usize) {
+        if let Some(layer) = self.data_layers.get_mut(&data_layer) {
+            layer.remove(index);
+        }
+    }
+
+    /// Returns the indices and values of a data layer, leaving out the ones
+    /// that are 0 in dense layers.
+    pub(crate) fn data_values(&self, data_layer: usize) -> Vec<(usize, i32)> {
+        self.data_layers
+            .get(&data_layer)
+            .map(|layer| layer.values())
+            .unwrap_or_default()
+    }
+
+    /// Removes a data layer with all its values.
+    pub(crate) fn remove_data_layer(&mut self, data_layer: usize) {
+        self.data_layers.remove(&data_layer);
+    }
+
+    /// Attaches a typed value to a tile at an index and sprite order,
+    /// replacing the value of the same type.
+    pub(crate) fn insert_tile_data<T: TileData>(
+        &mut self,
+        index: usize,
+        sprite_order: usize,
+        value: T,
+    ) {
+        self.tile_data.insert(index, sprite_order, value);
+    }
+
+    /// Gets a reference to the typed value attached to a tile at an index and
+    /// sprite order.
+    pub(crate) fn get_tile_data<T: TileData>(
+        &self,
+        index: usize,
+        sprite_order: usize,
+    ) -> Option<&T> {
+        self.tile_data.get(index, sprite_order)
+    }
+
+    /// Gets a mutable reference to the typed value attached to a tile at an
+    /// index and sprite order.
+    pub(crate) fn get_tile_data_mut<T: TileData>(
+        &mut self,
+        index: usize,
+        sprite_order: usize,
+    ) -> Option<&mut T> {
+        self.tile_data.get_mut(index, sprite_order)
+    }
+
+    /// Removes the typed value attached to a tile at an index and sprite
+    /// order and returns it.
+    pub(crate) fn remove_tile_data<T: TileData>(
+        &mut self,
+        index: usize,
+        sprite_order: usize,
+    ) -> Option<T> {
+        self.tile_data.remove(index, sprite_order)
+    }
+
+    /// Removes all typed values attached to a tile at an index and sprite
+    /// order.
+    pub(crate) fn clear_tile_data(&mut self, index: usize, sprite_order: usize) {
+        self.tile_data.clear(index, sprite_order);
+    }
+
+    /// Removes all typed values attached to the tiles of a sprite order.
+    pub(crate) fn clear_layer_tile_data(&mut self, sprite_order: usize) {
+        self.tile_data.clear_sprite_order(sprite_order);
+    }
+
+    /// Clears a given sprite layer of all sprites at every z depth.
+    pub(crate) fn clear_layer(&mut self, sprite_order: usize) {
+        for sprite_layers in &mut self.z_layers {
+            if let Some(Some(layer)) = sprite_layers.get_mut(sprite_order) {
                 layer.inner.as_mut().clear();
             }
         }
@@ -318,18 +553,43 @@ impl Chunk {
     pub(crate) fn tiles_to_renderer_parts(
         &self,
         dimensions: Dimension3,
-    ) -> (Vec<f32>, Vec<[f32; 4]>) {
+    ) -> (Vec<f32>, Vec<[f32; 4]>, Vec<f32>) {
         let mut tile_indices = Vec::new();
         let mut tile_colors = Vec::new();
+        let mut tile_flags = Vec::new();
         for depth in &self.z_layers {
             for layer in depth.iter().flatten() {
-                let (mut indices, mut colors) =
+                let (mut indices, mut colors, mut flags) =
                     layer.inner.as_ref().tiles_to_attributes(dimensions);
                 tile_indices.append(&mut indices);
                 tile_colors.append(&mut colors);
+                tile_flags.append(&mut flags);
             }
         }
-        (tile_indices, tile_colors)
+        (tile_indices, tile_colors, tile_flags)
+    }
+
+    /// Returns the brightness of every vertex of the chunk's mesh from the fog
+    /// of war, in the same order as the renderer parts of the tiles.
+    ///
+    /// Without fog of war everything is fully bright.
+    pub(crate) fn fog_to_renderer_parts(
+        &self,
+        dimensions: Dimension3,
+        fog_of_war: Option<FogOfWar>,
+    ) -> Vec<f32> {
+        let area = (dimensions.width * dimensions.height) as usize;
+        let mut brightness = Vec::with_capacity(area * 4);
+        for index in 0..area {
+            let value = fog_of_war.map_or(1.0, |fog| fog.brightness(self.fog_state(index)));
+            brightness.extend([value; 4].iter());
+        }
+        let layers = self
+            .z_layers
+            .iter()
+            .map(|depth| depth.iter().flatten().count())
+            .sum::<usize>();
+        brightness.repeat(layers)
     }
 }
 
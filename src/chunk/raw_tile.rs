@@ -1,13 +1,18 @@
-use crate::lib::*;
+use crate::{lib::*, tile::TileFlip};
 
 #[derive(Component, Copy, Clone, PartialEq, Debug, Serialize, Deserialize, Reflect)]
 #[reflect(Component, Serialize, Deserialize, PartialEq)]
-/// A raw tile composed of simply an index and a color.
+/// A raw tile composed of simply an index, a color and its orientation.
 pub struct RawTile {
     /// The index of the tile in the sprite sheet.
     pub index: usize,
     /// The color, or tint, of the tile.
     pub color: Color,
+    /// The axes the sprite is flipped on, before it is rotated.
+    pub flip: TileFlip,
+    /// The counter-clockwise rotation of the sprite in steps, which are 90°
+    /// for square topologies and 60° for hex topologies.
+    pub rotation: u8,
 }
 
 impl Default for RawTile {
@@ -15,33 +20,49 @@ impl Default for RawTile {
         RawTile {
             index: 0,
             color: Color::WHITE,
+            flip: TileFlip::NONE,
+            rotation: 0,
         }
     }
 }
 
-/// A utility function that takes an array of `Tile`s and splits the indexes and
-/// colors and returns them as separate vectors for use in the renderer.
-pub(crate) fn dense_tiles_to_attributes(tiles: &[RawTile]) -> (Vec<f32>, Vec<[f32; 4]>) {
+impl RawTile {
+    /// Packs the flip and rotation of the tile for the renderer.
+    ///
+    /// The first two bits are the flip and the rest are the rotation.
+    fn flags(&self) -> f32 {
+        f32::from(self.flip.bits()) + f32::from(self.rotation) * 4.0
+    }
+}
+
+/// A utility function that takes an array of `Tile`s and splits the indexes,
+/// colors and flags and returns them as separate vectors for use in the
+/// renderer.
+pub(crate) fn dense_tiles_to_attributes(tiles: &[RawTile]) -> (Vec<f32>, Vec<[f32; 4]>, Vec<f32>) {
     let capacity = tiles.len() * 4;
     let mut tile_indexes: Vec<f32> = Vec::with_capacity(capacity);
     let mut tile_colors: Vec<[f32; 4]> = Vec::with_capacity(capacity);
+    let mut tile_flags: Vec<f32> = Vec::with_capacity(capacity);
     for tile in tiles.iter() {
         tile_indexes.extend([tile.index as f32; 4].iter());
         tile_colors.extend([tile.color.into(); 4].iter());
+        tile_flags.extend([tile.flags(); 4].iter());
     }
-    (tile_indexes, tile_colors)
+    (tile_indexes, tile_colors, tile_flags)
 }
 
-/// A utility function that takes a sparse map of `Tile`s and splits the indexes
-/// and colors and returns them as separate vectors for use in the renderer.
+/// A utility function that takes a sparse map of `Tile`s and splits the
+/// indexes, colors and flags and returns them as separate vectors for use in
+/// the renderer.
 pub(crate) fn sparse_tiles_to_attributes(
     dimension: Dimension3,
     tiles: &HashMap<usize, RawTile>,
-) -> (Vec<f32>, Vec<[f32; 4]>) {
+) -> (Vec<f32>, Vec<[f32; 4]>, Vec<f32>) {
     let area = (dimension.width * dimension.height) as usize;
     let mut tile_indexes = vec![0.; area * 4];
     // If tiles are set with an alpha of 0, they are discarded.
     let mut tile_colors = vec![[0.0, 0.0, 0.0, 0.0]; area * 4];
+    let mut tile_flags = vec![0.; area * 4];
     for (index, tile) in tiles.iter() {
         for i in 0..4 {
             if let Some(index) = tile_indexes.get_mut(index * 4 + i) {
@@ -50,7 +71,33 @@ pub(crate) fn sparse_tiles_to_attributes(
             if let Some(index) = tile_colors.get_mut(index * 4 + i) {
                 *index = tile.color.into();
             }
+            if let Some(index) = tile_flags.get_mut(index * 4 + i) {
+                *index = tile.flags();
+            }
         }
     }
-    (tile_indexes, tile_colors)
+    (tile_indexes, tile_colors, tile_flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_pack_flip_and_rotation() {
+        let tile = |flip, rotation| RawTile {
+            flip,
+            rotation,
+            ..Default::default()
+        };
+        assert_eq!(tile(TileFlip::NONE, 0).flags(), 0.0);
+        assert_eq!(tile(TileFlip::HORIZONTAL, 0).flags(), 1.0);
+        assert_eq!(tile(TileFlip::VERTICAL, 0).flags(), 2.0);
+        assert_eq!(tile(TileFlip::all(), 0).flags(), 3.0);
+        assert_eq!(tile(TileFlip::NONE, 1).flags(), 4.0);
+        // The shaders read the flip from the first two bits and the rotation
+        // from the rest.
+        assert_eq!(tile(TileFlip::VERTICAL, 5).flags(), 22.0);
+        assert_eq!(tile(TileFlip::all(), u8::MAX).flags(), 1023.0);
+    }
 }
@@ -1,5 +1,15 @@
 use crate::lib::*;
 
+/// Returns the source of a vertex shader with the functions shared by all
+/// vertex shaders added before its main function.
+fn vertex_shader(source: &str) -> String {
+    source.replacen(
+        "void main()",
+        concat!(include_str!("tilemap-orientation.glsl"), "void main()"),
+        1,
+    )
+}
+
 /// Builds the chunk pipeline.
 macro_rules! build_chunk_pipeline {
     ($handle: ident, $id: expr, $name: ident, $file: expr) => {
@@ -43,8 +53,10 @@ macro_rules! build_chunk_pipeline {
                     },
                 }),
                 ..PipelineDescriptor::new(ShaderStages {
-                    vertex: shaders
-                        .add(Shader::from_glsl(ShaderStage::Vertex, include_str!($file))),
+                    vertex: shaders.add(Shader::from_glsl(
+                        ShaderStage::Vertex,
+                        &vertex_shader(include_str!($file)),
+                    )),
                     fragment: Some(shaders.add(Shader::from_glsl(
                         ShaderStage::Fragment,
                         include_str!("tilemap.frag"),
@@ -132,6 +144,41 @@ impl GridTopology {
             HexOddCols => CHUNK_HEXCOLS_ODD_PIPELINE,
         }
     }
+
+    /// Returns the layout of the tile points of a hex topology as offset
+    /// coordinates, or `None` for a square topology.
+    ///
+    /// The layout matches where the tiles are drawn because the chunks of the
+    /// staggered topologies have an even amount of rows or columns, which
+    /// the [`TilemapBuilder`] makes sure of.
+    ///
+    /// [`TilemapBuilder`]: crate::tilemap::TilemapBuilder
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::{hex::{AxialPoint, HexLayout}, point::Point2, prelude::*};
+    ///
+    /// let layout = GridTopology::HexOddRows.hex_layout().unwrap();
+    /// assert_eq!(layout, HexLayout::OddRows);
+    ///
+    /// // The tile north east of (0, 0) is (0, 1) in odd rows.
+    /// let north_east = AxialPoint::from_offset(Point2::new(0, 0), layout).neighbor(0);
+    /// assert_eq!(north_east.to_offset(layout), Point2::new(0, 1));
+    ///
+    /// assert_eq!(GridTopology::Square.hex_layout(), None);
+    /// ```
+    pub fn hex_layout(self) -> Option<HexLayout> {
+        use GridTopology::*;
+        match self {
+            Square => None,
+            HexY => Some(HexLayout::Rows),
+            HexX => Some(HexLayout::Cols),
+            HexEvenRows => Some(HexLayout::EvenRows),
+            HexOddRows => Some(HexLayout::OddRows),
+            HexEvenCols => Some(HexLayout::EvenCols),
+            HexOddCols => Some(HexLayout::OddCols),
+        }
+    }
 }
 
 /// Adds the tilemap graph to the pipeline and shaders.
@@ -3,9 +3,14 @@
 layout(location = 0) in vec3 Vertex_Position;
 layout(location = 1) in float Vertex_Tile_Index;
 layout(location = 2) in vec4 Vertex_Tile_Color;
+layout(location = 3) in float Vertex_Tile_Flags;
+layout(location = 4) in float Vertex_Tile_Fog;
 
 layout(location = 0) out vec2 v_Uv;
 layout(location = 1) out vec4 v_Color;
+layout(location = 2) out vec2 v_Local;
+layout(location = 3) flat out vec4 v_Rect;
+layout(location = 4) flat out vec4 v_Orientation;
 
 layout(set = 0, binding = 0) uniform CameraViewProj {
     mat4 ViewProj;
@@ -66,6 +71,12 @@ void main() {
         sprite_rect.end
     );
     v_Uv = floor(atlas_positions[local_index]) / AtlasSize;
-    v_Color = Vertex_Tile_Color;
+    // darken the tiles that are explored and hide those that are not
+    v_Color = Vertex_Tile_Fog == 0.0
+        ? vec4(0.0)
+        : vec4(Vertex_Tile_Color.rgb * Vertex_Tile_Fog, Vertex_Tile_Color.a);
+
+    // flip and rotate the sprite, a rotation step is a sixth of a turn for hexes
+    orient_sprite(local_index, sprite_rect, Vertex_Tile_Flags, 60.0);
     gl_Position = ViewProj * ChunkTransform * vec4(ceil(vertex_position), 1.0);
 }
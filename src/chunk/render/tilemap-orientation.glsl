@@ -0,0 +1,27 @@
+// Added before the main function of every vertex shader of the tilemap when
+// the pipelines are built.
+
+// The corners of a sprite from its bottom left, by the vertex index.
+const vec2 LOCAL_POSITIONS[4] = vec2[](
+    vec2(0.0, 1.0),
+    vec2(0.0, 0.0),
+    vec2(1.0, 0.0),
+    vec2(1.0, 1.0)
+);
+
+// Passes the fragment shader what it needs to flip and rotate the sprite
+// around its center, from the flags of the tile. A rotation step turns the
+// sprite by the angle in degrees.
+void orient_sprite(int local_index, Rect sprite_rect, float tile_flags, float step) {
+    int flags = int(tile_flags);
+    vec2 sprite_dimensions = sprite_rect.end - sprite_rect.begin;
+    v_Local = LOCAL_POSITIONS[local_index];
+    v_Rect = vec4(floor(sprite_rect.begin), floor(sprite_rect.end)) / AtlasSize.xyxy;
+    v_Orientation = vec4(
+        (flags & 1) == 1 ? -1.0 : 1.0,
+        (flags & 2) == 2 ? -1.0 : 1.0,
+        float(flags >> 2) * radians(step),
+        sprite_dimensions.x / sprite_dimensions.y
+    );
+}
+
@@ -2,6 +2,9 @@
 
 layout(location = 0) in vec2 v_Uv;
 layout(location = 1) in vec4 v_Color;
+layout(location = 2) in vec2 v_Local;
+layout(location = 3) flat in vec4 v_Rect;
+layout(location = 4) flat in vec4 v_Orientation;
 
 layout(location = 0) out vec4 o_Target;
 
@@ -12,8 +15,28 @@ void main() {
     if (v_Color.a == 0.0) {
         discard;
     }
+
+    vec2 uv = v_Uv;
+    if (v_Orientation.xyz != vec3(1.0, 1.0, 0.0)) {
+        // move to the center of the sprite with an aspect ratio of 1 and y up
+        vec2 aspect = vec2(v_Orientation.w, -1.0);
+        vec2 point = (v_Local - 0.5) * aspect;
+
+        // sample where the point was before it was rotated
+        float c = cos(v_Orientation.z);
+        float s = sin(v_Orientation.z);
+        point = vec2(c * point.x + s * point.y, c * point.y - s * point.x);
+
+        // then before it was flipped
+        vec2 local = point / aspect * v_Orientation.xy + 0.5;
+        if (any(lessThan(local, vec2(0.0))) || any(greaterThan(local, vec2(1.0)))) {
+            discard;
+        }
+        uv = mix(v_Rect.xy, v_Rect.zw, local);
+    }
+
     o_Target = v_Color * texture(
         sampler2D(TextureAtlas_texture, TextureAtlas_texture_sampler),
-        v_Uv
+        uv
     );
 }
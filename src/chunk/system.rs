@@ -9,9 +9,14 @@ use crate::{
 pub(crate) fn chunk_update(
     mut meshes: ResMut<Assets<Mesh>>,
     map_query: Query<&Tilemap>,
-    mut chunk_query: Query<(&Parent, &Point2, &Handle<Mesh>), Changed<Modified>>,
+    mut chunk_query: Query<(&Parent, &Point2, &Handle<Mesh>, &Modified), Changed<Modified>>,
 ) {
-    for (parent, point, mesh_handle) in chunk_query.iter_mut() {
+    for (parent, point, mesh_handle, modified) in chunk_query.iter_mut() {
+        // Chunks are spawned with an up to date mesh, unless they were
+        // modified while it was built.
+        if modified.0 == 0 {
+            continue;
+        }
         let tilemap = if let Ok(tilemap) = map_query.get(**parent) {
             tilemap
         } else {
@@ -30,9 +35,16 @@ pub(crate) fn chunk_update(
             error!("`Mesh` is missing, can not update chunk");
             return;
         };
-        let (indexes, colors) = chunk.tiles_to_renderer_parts(tilemap.chunk_dimensions());
+        let (mut indexes, colors, flags) =
+            chunk.tiles_to_renderer_parts(tilemap.chunk_dimensions());
+        tilemap.animations().apply(&mut indexes);
         mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_INDEX, indexes);
         mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_COLOR, colors);
+        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_FLAGS, flags);
+        mesh.set_attribute(
+            ChunkMesh::ATTRIBUTE_TILE_FOG,
+            chunk.fog_to_renderer_parts(tilemap.chunk_dimensions(), tilemap.fog_of_war()),
+        );
     }
 }
 
@@ -43,10 +55,10 @@ fn auto_spawn(
     tilemap: &mut Tilemap,
     spawn_dimensions: Dimension2,
 ) {
-    let translation = camera_transform.translation - tilemap_transform.translation;
-    let point_x = translation.x / tilemap.tile_width() as f32;
-    let point_y = translation.y / tilemap.tile_height() as f32;
-    let (chunk_x, chunk_y) = tilemap.point_to_chunk_point((point_x as i32, point_y as i32));
+    let Point2 {
+        x: chunk_x,
+        y: chunk_y,
+    } = tilemap.world_to_chunk(tilemap_transform, camera_transform.translation);
     let mut new_spawned: Vec<Point2> = Vec::new();
     let spawn_width = spawn_dimensions.width as i32;
     let spawn_height = spawn_dimensions.height as i32;
@@ -74,7 +86,14 @@ fn auto_spawn(
         }
     }
 
-    let spawned_list = tilemap.spawned_chunks_mut().clone();
+    let mut spawned_list = tilemap.spawned_chunks_mut().clone();
+    spawned_list.extend(
+        tilemap
+            .pending_chunks()
+            .points()
+            .into_iter()
+            .map(|point| (point.x, point.y)),
+    );
     for point in spawned_list.iter() {
         if !new_spawned.contains(&point.into()) {
             if let Err(e) = tilemap.despawn_chunk(point) {
@@ -198,6 +217,7 @@ mod tests {
                         sprite_order: 0,
                         sprite_index: 1,
                         tint: Color::BLUE,
+                        ..Default::default()
                     })
                     .unwrap();
                 tilemap.spawn_chunk(Point2::new(0, 0)).unwrap();
@@ -0,0 +1,172 @@
+//! Building chunks and their meshes on the async compute task pool.
+//!
+//! A tilemap with async chunks starts a task for every chunk that is spawned,
+//! building from a copy of the chunk or generating it if it does not exist
+//! yet. The tasks are kept in the tilemap's [`PendingChunks`] by chunk point
+//! until the tilemap events take the finished ones and spawn them, and
+//! despawning a chunk that is still being built drops and so cancels its
+//! task. The edits of a chunk are counted, so a chunk that was changed while
+//! it was built is flagged to have its mesh updated again.
+
+use crate::{
+    animation::TileAnimations,
+    chunk::{mesh::ChunkMesh, Chunk, LayerKind},
+    fog::FogOfWar,
+    generator::{self, ChunkGenerator},
+    lib::*,
+};
+use futures_lite::future;
+
+/// Where the tiles of a chunk that is built come from.
+pub(crate) enum ChunkSource {
+    /// A copy of a chunk that exists.
+    Existing(Box<Chunk>),
+    /// A chunk that does not exist yet and is generated.
+    Generated {
+        /// The generator of the chunk.
+        generator: Arc<dyn ChunkGenerator>,
+        /// The kinds of the sprite layers of the chunk.
+        layer_kinds: Vec<Option<LayerKind>>,
+    },
+}
+
+/// What the mesh of a chunk is built from, besides its tiles.
+#[derive(Clone)]
+pub(crate) struct MeshParts {
+    /// The mesh every chunk starts from.
+    pub(crate) chunk_mesh: ChunkMesh,
+    /// The dimensions of a chunk.
+    pub(crate) dimensions: Dimension3,
+    /// The animations of the sprite indices.
+    pub(crate) animations: TileAnimations,
+    /// The fog of war of the tilemap, if there is any.
+    pub(crate) fog_of_war: Option<FogOfWar>,
+}
+
+impl MeshParts {
+    /// Builds the mesh of a chunk with all of its attributes.
+    pub(crate) fn build(&self, chunk: &Chunk) -> Mesh {
+        let mut mesh = Mesh::from(&self.chunk_mesh);
+        let (mut indexes, colors, flags) = chunk.tiles_to_renderer_parts(self.dimensions);
+        self.animations.apply(&mut indexes);
+        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_INDEX, indexes);
+        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_COLOR, colors);
+        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_FLAGS, flags);
+        mesh.set_attribute(
+            ChunkMesh::ATTRIBUTE_TILE_FOG,
+            chunk.fog_to_renderer_parts(self.dimensions, self.fog_of_war),
+        );
+        mesh
+    }
+}
+
+/// A chunk that was built on a task pool.
+pub(crate) struct BuiltChunk {
+    /// The chunk, if it was generated.
+    pub(crate) generated: Option<Chunk>,
+    /// The edits of the chunk the mesh was built from.
+    pub(crate) edits: usize,
+    /// The mesh of the chunk.
+    pub(crate) mesh: Mesh,
+}
+
+/// Builds a chunk and its mesh.
+pub(crate) fn build_chunk(point: Point2, source: ChunkSource, parts: MeshParts) -> BuiltChunk {
+    match source {
+        ChunkSource::Existing(chunk) => BuiltChunk {
+            mesh: parts.build(&chunk),
+            generated: None,
+            edits: chunk.edits(),
+        },
+        ChunkSource::Generated {
+            generator,
+            layer_kinds,
+        } => {
+            let chunk = generator::generate_chunk(
+                generator.as_ref(),
+                point,
+                &layer_kinds,
+                parts.dimensions,
+            );
+            BuiltChunk {
+                mesh: parts.build(&chunk),
+                edits: chunk.edits(),
+                generated: Some(chunk),
+            }
+        }
+    }
+}
+
+/// The chunks that are being built on the async compute task pool.
+#[derive(Default)]
+pub(crate) struct PendingChunks {
+    /// The task building each chunk.
+    tasks: HashMap<Point2, Task<BuiltChunk>>,
+    /// The chunks that were waited for until they were built.
+    built: HashMap<Point2, BuiltChunk>,
+}
+
+impl PendingChunks {
+    /// Returns `true` if a chunk is being built.
+    pub(crate) fn contains(&self, point: Point2) -> bool {
+        self.tasks.contains_key(&point) || self.built.contains_key(&point)
+    }
+
+    /// Returns `true` if no chunks are being built.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tasks.is_empty() && self.built.is_empty()
+    }
+
+    /// Returns the points of the chunks that are being built.
+    pub(crate) fn points(&self) -> Vec<Point2> {
+        self.tasks
+            .keys()
+            .chain(self.built.keys())
+            .copied()
+            .collect()
+    }
+
+    /// Adds the task building a chunk.
+    pub(crate) fn insert(&mut self, point: Point2, task: Task<BuiltChunk>) {
+        self.tasks.insert(point, task);
+    }
+
+    /// Cancels building a chunk and returns `true` if it was being built.
+    pub(crate) fn cancel(&mut self, point: Point2) -> bool {
+        // Dropping a task cancels it.
+        self.tasks.remove(&point).is_some() || self.built.remove(&point).is_some()
+    }
+
+    /// Blocks until all chunks are built, so that they are all taken by the
+    /// next [`take_finished`]. Intended for testing purposes only.
+    ///
+    /// [`take_finished`]: PendingChunks::take_finished
+    #[cfg(test)]
+    pub(crate) fn wait(&mut self) {
+        for (point, task) in self.tasks.drain() {
+            self.built.insert(point, future::block_on(task));
+        }
+    }
+
+    /// Removes and returns the chunks that are done.
+    pub(crate) fn take_finished(&mut self) -> Vec<(Point2, BuiltChunk)> {
+        let mut finished = self.built.drain().collect::<Vec<(Point2, BuiltChunk)>>();
+        for (point, task) in self.tasks.iter_mut() {
+            if let Some(built) = future::block_on(future::poll_once(task)) {
+                finished.push((*point, built));
+            }
+        }
+        for (point, _) in finished.iter() {
+            self.tasks.remove(point);
+        }
+        finished
+    }
+}
+
+impl Debug for PendingChunks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("PendingChunks")
+            .field("points", &self.points())
+            .finish()
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{lib::*, tile::TileData};
+use serde_json::Value;
+
+/// Serializes a value of a tile data type.
+type ToValue = fn(&dyn Reflect) -> Option<Value>;
+
+/// A value attached to a tile.
+pub(super) enum TileValue {
+    /// A value with the function that serializes its type.
+    Typed {
+        /// The reflected value.
+        value: Box<dyn Reflect>,
+        /// Serializes the value.
+        to_value: ToValue,
+    },
+    /// A deserialized value that gets its type back when it is first used.
+    Untyped(Value),
+}
+
+/// Turns a deserialized value back into a value of a tile data type.
+type FromValue = fn(&Value) -> Option<TileValue>;
+
+impl TileValue {
+    /// Constructs a new tile value from a typed value.
+    fn new<T: TileData>(value: T) -> TileValue {
+        TileValue::Typed {
+            value: Box::new(value),
+            to_value: |value| {
+                let value = value.downcast_ref::<T>()?;
+                serde_json::to_value(value).ok()
+            },
+        }
+    }
+
+    /// Deserializes a value of a type into a typed value.
+    fn from_value<T: TileData>(value: &Value) -> Option<TileValue> {
+        match serde_json::from_value::<T>(value.clone()) {
+            Ok(value) => Some(TileValue::new(value)),
+            Err(e) => {
+                error!("failed to deserialize tile data: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Gives a deserialized value its type back.
+    fn type_with(&mut self, from_value: FromValue) {
+        if let TileValue::Untyped(value) = self {
+            if let Some(typed) = from_value(value) {
+                *self = typed;
+            }
+        }
+    }
+
+    /// Returns a reference to the value if it is of a type.
+    fn downcast_ref<T: TileData>(&self) -> Option<&T> {
+        match self {
+            TileValue::Typed { value, .. } => value.downcast_ref::<T>(),
+            TileValue::Untyped(_) => None,
+        }
+    }
+
+    /// Returns a mutable reference to the value if it is of a type.
+    fn downcast_mut<T: TileData>(&mut self) -> Option<&mut T> {
+        match self {
+            TileValue::Typed { value, .. } => value.downcast_mut::<T>(),
+            TileValue::Untyped(_) => None,
+        }
+    }
+}
+
+impl Debug for TileValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            TileValue::Typed { value, .. } => f.debug_tuple("Typed").field(value).finish(),
+            TileValue::Untyped(value) => f.debug_tuple("Untyped").field(value).finish(),
+        }
+    }
+}
+
+impl Serialize for TileValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            TileValue::Typed { value, to_value } => match to_value(value.as_ref()) {
+                Some(value) => value.serialize(serializer),
+                None => Err(serde::ser::Error::custom(format!(
+                    "failed to serialize tile data of type {}",
+                    value.type_name()
+                ))),
+            },
+            TileValue::Untyped(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TileValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<TileValue, D::Error> {
+        Value::deserialize(deserializer).map(TileValue::Untyped)
+    }
+}
+
+/// The tile data types that deserialized values are given back, by the UUID
+/// of the type.
+#[derive(Clone, Default)]
+pub(crate) struct TileDataTypes {
+    /// Deserializes the values of each type.
+    types: HashMap<Uuid, FromValue>,
+}
+
+impl TileDataTypes {
+    /// Registers a type and returns `true` if it was not registered before.
+    pub(crate) fn register<T: TileData>(&mut self) -> bool {
+        self.types
+            .insert(T::TYPE_UUID, TileValue::from_value::<T>)
+            .is_none()
+    }
+}
+
+impl Debug for TileDataTypes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_set().entries(self.types.keys()).finish()
+    }
+}
+
+/// Typed values by the UUID of their type, at most one of each type.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct TypedValues {
+    /// The values by the UUID of their type.
+    values: HashMap<Uuid, TileValue>,
+}
+
+impl TypedValues {
+    /// Inserts a value, replacing the value of the same type.
+    pub(crate) fn insert<T: TileData>(&mut self, value: T) {
+        self.values.insert(T::TYPE_UUID, TileValue::new(value));
+    }
+
+    /// Returns a reference to the value of a type, if it was inserted or
+    /// given its type back.
+    pub(crate) fn get<T: TileData>(&self) -> Option<&T> {
+        self.values.get(&T::TYPE_UUID)?.downcast_ref::<T>()
+    }
+
+    /// Returns a mutable reference to the value of a type.
+    pub(crate) fn get_mut<T: TileData>(&mut self) -> Option<&mut T> {
+        let value = self.values.get_mut(&T::TYPE_UUID)?;
+        value.type_with(TileValue::from_value::<T>);
+        value.downcast_mut::<T>()
+    }
+
+    /// Removes the value of a type and returns it.
+    pub(crate) fn remove<T: TileData>(&mut self) -> Option<T> {
+        let mut value = self.values.remove(&T::TYPE_UUID)?;
+        value.type_with(TileValue::from_value::<T>);
+        match value {
+            TileValue::Typed { value, .. } => value.downcast::<T>().ok().map(|value| *value),
+            TileValue::Untyped(_) => None,
+        }
+    }
+
+    /// Gives the deserialized values of the registered types their type back.
+    pub(crate) fn type_values(&mut self, types: &TileDataTypes) {
+        for (uuid, value) in self.values.iter_mut() {
+            if let Some(from_value) = types.types.get(uuid) {
+                value.type_with(*from_value);
+            }
+        }
+    }
+
+    /// Returns `true` if there are no values.
+    fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// The typed values attached to the tiles of a chunk, by the sprite order and
+/// index of the tile.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(super) struct TileDataMap {
+    /// The values of each tile by its index, for each sprite order.
+    values: HashMap<usize, HashMap<usize, TypedValues>>,
+}
+
+impl TileDataMap {
+    /// Attaches a value to a tile, replacing the value of the same type.
+    pub(super) fn insert<T: TileData>(&mut self, index: usize, sprite_order: usize, value: T) {
+        self.values
+            .entry(sprite_order)
+            .or_default()
+            .entry(index)
+            .or_default()
+            .insert(value);
+    }
+
+    /// Returns a reference to the value of a type attached to a tile.
+    pub(super) fn get<T: TileData>(&self, index: usize, sprite_order: usize) -> Option<&T> {
+        self.values.get(&sprite_order)?.get(&index)?.get()
+    }
+
+    /// Returns a mutable reference to the value of a type attached to a tile.
+    pub(super) fn get_mut<T: TileData>(
+        &mut self,
+        index: usize,
+        sprite_order: usize,
+    ) -> Option<&mut T> {
+        self.values
+            .get_mut(&sprite_order)?
+            .get_mut(&index)?
+            .get_mut()
+    }
+
+    /// Removes the value of a type attached to a tile and returns it.
+    pub(super) fn remove<T: TileData>(&mut self, index: usize, sprite_order: usize) -> Option<T> {
+        let tiles = self.values.get_mut(&sprite_order)?;
+        let values = tiles.get_mut(&index)?;
+        let value = values.remove();
+        if values.is_empty() {
+            tiles.remove(&index);
+        }
+        if tiles.is_empty() {
+            self.values.remove(&sprite_order);
+        }
+        value
+    }
+
+    /// Removes all values attached to a tile.
+    pub(super) fn clear(&mut self, index: usize, sprite_order: usize) {
+        if let Some(tiles) = self.values.get_mut(&sprite_order) {
+            tiles.remove(&index);
+            if tiles.is_empty() {
+                self.values.remove(&sprite_order);
+            }
+        }
+    }
+
+    /// Removes all values attached to the tiles of a sprite order.
+    pub(super) fn clear_sprite_order(&mut self, sprite_order: usize) {
+        self.values.remove(&sprite_order);
+    }
+
+    /// Swaps the values attached to the tiles of two sprite orders.
+    pub(super) fn swap_sprite_orders(&mut self, sprite_order: usize, other: usize) {
+        let values = self.values.remove(&sprite_order);
+        if let Some(other_values) = self.values.remove(&other) {
+            self.values.insert(sprite_order, other_values);
+        }
+        if let Some(values) = values {
+            self.values.insert(other, values);
+        }
+    }
+
+    /// Gives the deserialized values of the registered types their type back.
+    pub(super) fn type_values(&mut self, types: &TileDataTypes) {
+        for values in self.values.values_mut().flat_map(HashMap::values_mut) {
+            values.type_values(types);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    #[uuid = "3b4b5a0e-5d1e-4f0e-9a43-9e2f0f5c6a71"]
+    struct Door {
+        locked: bool,
+    }
+
+    #[test]
+    fn tile_data_round_trip() {
+        let mut map = TileDataMap::default();
+        map.insert(3, 0, Door { locked: true });
+        map.insert(7, 2, Door { locked: false });
+
+        let json = serde_json::to_string(&map).unwrap();
+        let mut map: TileDataMap = serde_json::from_str(&json).unwrap();
+        // Deserialized values are found once their type is given back.
+        assert_eq!(map.get::<Door>(3, 0), None);
+
+        let mut types = TileDataTypes::default();
+        assert!(types.register::<Door>());
+        map.type_values(&types);
+        assert_eq!(map.get(3, 0), Some(&Door { locked: true }));
+        assert_eq!(map.get(7, 2), Some(&Door { locked: false }));
+        assert_eq!(map.get::<Door>(3, 2), None);
+
+        // Values that are borrowed mutably get their type back on their own.
+        let mut map: TileDataMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(map.get_mut(7, 2), Some(&mut Door { locked: false }));
+    }
+
+    #[test]
+    fn swap_sprite_orders_moves_values() {
+        let mut map = TileDataMap::default();
+        map.insert(3, 0, Door { locked: true });
+
+        map.swap_sprite_orders(0, 1);
+        assert_eq!(map.get::<Door>(3, 0), None);
+        assert_eq!(map.get(3, 1), Some(&Door { locked: true }));
+    }
+}
@@ -0,0 +1,393 @@
+//! An in-game editor to paint tilemaps with the mouse.
+//!
+//! This module is only available with the `editor` feature.
+//!
+//! ```toml
+//! [dependencies]
+//! bevy_tilemap = { version = "0.4", features = ["editor"] }
+//! ```
+//!
+//! The [`TilemapEditorPlugin`] edits every tilemap entity with an
+//! [`EditorTarget`] while the [`TilemapEditor`] resource is enabled. Every
+//! stroke of the mouse is a transaction of the tilemap, so it can be undone.
+//!
+//! | Input | Action |
+//! |-------|--------|
+//! | Left mouse button | Uses the tool under the cursor |
+//! | `1`, `2`, `3`, `4` | Picks the paint, erase, fill or rectangle tool |
+//! | `Q`, `E` | Picks the previous or next sprite of the texture atlas |
+//! | `PageDown`, `PageUp` | Switches to the layer below or above |
+//! | `Ctrl` + `Z`, `Ctrl` + `Y` | Undoes or redoes the latest stroke |
+//! | `Ctrl` + `S`, `Ctrl` + `O` | Saves or loads the tiles as JSON |
+//!
+//! # Editing a tilemap
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_tilemap::{
+//!     editor::{EditorTarget, TilemapEditor, TilemapEditorPlugin},
+//!     prelude::*,
+//! };
+//!
+//! fn spawn(mut commands: Commands, mut editor: ResMut<TilemapEditor>) {
+//!     editor.enabled = true;
+//!     editor.save_path = "assets/maps/level.json".into();
+//!     commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+//!     // Then spawn a tilemap with an `EditorTarget::default()` component.
+//! }
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugins(TilemapDefaultPlugins)
+//!     .add_plugin(TilemapEditorPlugin)
+//!     .add_startup_system(spawn.system())
+//!     .run()
+//! ```
+
+use crate::{chunk::RawTile, lib::*, pathfinding::Connectivity, tile::Tile, Tilemap};
+
+/// The tools of the editor.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum EditorTool {
+    /// Sets the tiles under the cursor to the sprite.
+    Paint,
+    /// Clears the tiles under the cursor.
+    Erase,
+    /// Sets the connected tiles with the same sprite as the clicked one to the
+    /// sprite.
+    Fill,
+    /// Sets the tiles in a rectangle from where the mouse was pressed to where
+    /// it was released to the sprite.
+    Rectangle,
+}
+
+impl Default for EditorTool {
+    fn default() -> EditorTool {
+        EditorTool::Paint
+    }
+}
+
+impl EditorTool {
+    /// Returns the name of the transaction of a stroke with the tool.
+    fn name(&self) -> &'static str {
+        match self {
+            EditorTool::Paint => "paint",
+            EditorTool::Erase => "erase",
+            EditorTool::Fill => "fill",
+            EditorTool::Rectangle => "rectangle",
+        }
+    }
+}
+
+/// The resource with the state of the editor.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TilemapEditor {
+    /// If the editor reacts to the mouse and keyboard.
+    pub enabled: bool,
+    /// The tool that is used with the mouse.
+    pub tool: EditorTool,
+    /// The index of the sprite in the texture atlas that is painted.
+    pub sprite_index: usize,
+    /// The sprite order of the tiles that are edited.
+    pub sprite_order: usize,
+    /// The file the tiles are saved to and loaded from.
+    pub save_path: PathBuf,
+}
+
+impl Default for TilemapEditor {
+    fn default() -> TilemapEditor {
+        TilemapEditor {
+            enabled: false,
+            tool: EditorTool::default(),
+            sprite_index: 0,
+            sprite_order: 0,
+            save_path: PathBuf::from("tilemap.json"),
+        }
+    }
+}
+
+/// A component for tilemap entities that can be edited, with the stroke of
+/// the mouse on the tilemap.
+#[derive(Component, Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct EditorTarget {
+    /// The point where the mouse was pressed, while it is held.
+    stroke_start: Option<Point3>,
+    /// The point that was last edited in the stroke.
+    stroke_last: Option<Point3>,
+}
+
+/// The tiles of a tilemap as they are saved by the editor.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct SavedTiles {
+    /// Every tile with its global point and sprite order.
+    pub tiles: Vec<(Point3, usize, RawTile)>,
+}
+
+impl SavedTiles {
+    /// Takes the tiles of every chunk of a tilemap.
+    pub fn from_tilemap(tilemap: &Tilemap) -> SavedTiles {
+        let mut tiles = tilemap
+            .tile_points()
+            .into_iter()
+            .filter_map(|(point, sprite_order)| {
+                let tile = tilemap.raw_tile(point, sprite_order)?;
+                Some((point, sprite_order, *tile))
+            })
+            .collect::<Vec<(Point3, usize, RawTile)>>();
+        // The chunks are in no order, this keeps saves of the same tiles equal.
+        tiles.sort_by_key(|(point, sprite_order, _)| (*sprite_order, point.z, point.y, point.x));
+        SavedTiles { tiles }
+    }
+
+    /// Returns the tiles to insert into a tilemap.
+    pub fn to_tiles(&self) -> Vec<Tile<Point3>> {
+        self.tiles
+            .iter()
+            .map(|(point, sprite_order, tile)| Tile {
+                point: *point,
+                sprite_order: *sprite_order,
+                sprite_index: tile.index,
+                tint: tile.color,
+                flip: tile.flip,
+                rotation: tile.rotation,
+            })
+            .collect()
+    }
+}
+
+/// The plugin of the tilemap editor, which is not part of the default plugins.
+#[derive(Default)]
+pub struct TilemapEditorPlugin;
+
+impl Plugin for TilemapEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TilemapEditor>()
+            .add_system(tilemap_editor.system());
+    }
+}
+
+/// Returns the point in the world under the cursor, seen from the first 2D
+/// camera.
+fn cursor_translation(
+    windows: &Windows,
+    camera_query: &Query<(&GlobalTransform, &OrthographicProjection), With<Camera>>,
+) -> Option<Vec3> {
+    let window = windows.get_primary()?;
+    let cursor = window.cursor_position()?;
+    let (transform, projection) = camera_query.iter().next()?;
+    let size = Vec2::new(window.width(), window.height());
+    let local = (cursor - size / 2.0) * projection.scale;
+    Some(transform.mul_vec3(local.extend(0.0)))
+}
+
+/// Returns the next sprite order with a layer above or below a sprite order,
+/// or the same if there is none.
+fn next_sprite_order(tilemap: &Tilemap, sprite_order: usize, up: bool) -> usize {
+    let layers = tilemap.layers();
+    let exists = |order: &usize| layers.get(*order).map_or(false, Option::is_some);
+    if up {
+        (sprite_order + 1..layers.len())
+            .find(exists)
+            .unwrap_or(sprite_order)
+    } else {
+        (0..sprite_order).rev().find(exists).unwrap_or(sprite_order)
+    }
+}
+
+/// Returns the connected points from a point with the same sprite as it,
+/// only searching the chunks that exist.
+fn fill_points(tilemap: &Tilemap, point: Point3, sprite_order: usize) -> Vec<Point3> {
+    let index = tilemap.raw_tile(point, sprite_order).map(|tile| tile.index);
+    tilemap
+        .flood_region(point, sprite_order, Connectivity::Edges, |_, tile| {
+            tile.map(|tile| tile.index) == index
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Sets the tiles at points to the sprite of the editor, or clears them if
+/// erasing.
+fn edit_tiles(tilemap: &mut Tilemap, editor: &TilemapEditor, points: Vec<Point3>) {
+    // Editing outside of the chunks does nothing.
+    let points = points.into_iter().filter(|point| {
+        let chunk_point: Point2 = tilemap.point_to_chunk_point(*point).into();
+        tilemap.chunks().contains_key(&chunk_point)
+    });
+    let points = points.collect::<Vec<Point3>>();
+    let result = if editor.tool == EditorTool::Erase {
+        let points = points
+            .into_iter()
+            .filter(|point| tilemap.raw_tile(*point, editor.sprite_order).is_some())
+            .map(|point| (point, editor.sprite_order))
+            .collect::<Vec<(Point3, usize)>>();
+        tilemap.clear_tiles(points)
+    } else {
+        let tiles = points.into_iter().map(|point| Tile {
+            point,
+            sprite_order: editor.sprite_order,
+            sprite_index: editor.sprite_index,
+            ..Default::default()
+        });
+        tilemap.insert_tiles(tiles)
+    };
+    if let Err(e) = result {
+        error!("tilemap editor: {}", e);
+    }
+}
+
+/// Saves the tiles of a tilemap to the save path of the editor.
+fn save(tilemap: &Tilemap, editor: &TilemapEditor) {
+    let saved = SavedTiles::from_tilemap(tilemap);
+    let result = serde_json::to_string(&saved)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(&editor.save_path, json).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => info!("saved tilemap to {}", editor.save_path.display()),
+        Err(e) => error!("failed to save tilemap: {}", e),
+    }
+}
+
+/// Loads the tiles saved at the save path of the editor into a tilemap, as
+/// a transaction that can be undone.
+fn load(tilemap: &mut Tilemap, editor: &TilemapEditor) {
+    let result = std::fs::read_to_string(&editor.save_path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str::<SavedTiles>(&json).map_err(|e| e.to_string()));
+    let saved = match result {
+        Ok(saved) => saved,
+        Err(e) => {
+            error!("failed to load tilemap: {}", e);
+            return;
+        }
+    };
+    if tilemap.begin_transaction("load").is_err() {
+        return;
+    }
+    let points = tilemap.tile_points();
+    let result = tilemap
+        .clear_tiles(points)
+        .and_then(|_| tilemap.insert_tiles(saved.to_tiles()));
+    if let Err(e) = result {
+        error!("failed to load tilemap: {}", e);
+    }
+    if tilemap.commit_transaction().is_ok() {
+        info!("loaded tilemap from {}", editor.save_path.display());
+    }
+}
+
+/// Reacts to the keyboard and mouse to edit the tilemaps with an
+/// [`EditorTarget`].
+pub(crate) fn tilemap_editor(
+    mut editor: ResMut<TilemapEditor>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    atlases: Res<Assets<TextureAtlas>>,
+    camera_query: Query<(&GlobalTransform, &OrthographicProjection), With<Camera>>,
+    mut tilemap_query: Query<(&mut Tilemap, &GlobalTransform, &mut EditorTarget)>,
+) {
+    if !editor.enabled {
+        return;
+    }
+    let control = keys.pressed(KeyCode::LControl) || keys.pressed(KeyCode::RControl);
+    let stroking = tilemap_query
+        .iter_mut()
+        .any(|(_, _, target)| target.stroke_start.is_some());
+
+    for (key, tool) in [
+        (KeyCode::Key1, EditorTool::Paint),
+        (KeyCode::Key2, EditorTool::Erase),
+        (KeyCode::Key3, EditorTool::Fill),
+        (KeyCode::Key4, EditorTool::Rectangle),
+    ] {
+        if keys.just_pressed(key) && !stroking {
+            editor.tool = tool;
+        }
+    }
+
+    // The sprites and layers are picked from the first tilemap.
+    if let Some((tilemap, ..)) = tilemap_query.iter_mut().next() {
+        if keys.just_pressed(KeyCode::Q) || keys.just_pressed(KeyCode::E) {
+            if let Some(atlas) = atlases.get(tilemap.texture_atlas()) {
+                let len = atlas.len().max(1);
+                editor.sprite_index = if keys.just_pressed(KeyCode::E) {
+                    (editor.sprite_index + 1) % len
+                } else {
+                    (editor.sprite_index + len - 1) % len
+                };
+            }
+        }
+        if keys.just_pressed(KeyCode::PageUp) || keys.just_pressed(KeyCode::PageDown) {
+            let up = keys.just_pressed(KeyCode::PageUp);
+            editor.sprite_order = next_sprite_order(&tilemap, editor.sprite_order, up);
+        }
+    }
+
+    let cursor = cursor_translation(&windows, &camera_query);
+    for (mut tilemap, global_transform, mut target) in tilemap_query.iter_mut() {
+        if control && !stroking {
+            let result = if keys.just_pressed(KeyCode::Z) {
+                tilemap.undo()
+            } else if keys.just_pressed(KeyCode::Y) {
+                tilemap.redo()
+            } else {
+                Ok(None)
+            };
+            if let Err(e) = result {
+                error!("tilemap editor: {}", e);
+            }
+            if keys.just_pressed(KeyCode::S) {
+                save(&tilemap, &editor);
+            }
+            if keys.just_pressed(KeyCode::O) {
+                load(&mut tilemap, &editor);
+            }
+        }
+
+        let transform = Transform::from(*global_transform);
+        let point = cursor.map(|cursor| tilemap.world_to_tile(&transform, cursor, 0));
+        if let Some(point) = point {
+            if buttons.just_pressed(MouseButton::Left) {
+                match tilemap.begin_transaction(editor.tool.name()) {
+                    Ok(()) => {
+                        target.stroke_start = Some(point);
+                        if editor.tool == EditorTool::Fill {
+                            let points = fill_points(&tilemap, point, editor.sprite_order);
+                            edit_tiles(&mut tilemap, &editor, points);
+                        }
+                    }
+                    Err(e) => error!("tilemap editor: {}", e),
+                }
+            }
+            if target.stroke_start.is_some() && buttons.pressed(MouseButton::Left) {
+                if target.stroke_last != Some(point)
+                    && matches!(editor.tool, EditorTool::Paint | EditorTool::Erase)
+                {
+                    edit_tiles(&mut tilemap, &editor, vec![point]);
+                }
+                target.stroke_last = Some(point);
+            }
+        }
+
+        // The stroke ends even if the cursor left the window, the rectangle
+        // then ends where the cursor was last seen.
+        if buttons.just_released(MouseButton::Left) {
+            if let Some(start) = target.stroke_start.take() {
+                let end = point.or(target.stroke_last).unwrap_or(start);
+                if editor.tool == EditorTool::Rectangle {
+                    let mut points = Vec::new();
+                    for y in start.y.min(end.y)..=start.y.max(end.y) {
+                        for x in start.x.min(end.x)..=start.x.max(end.x) {
+                            points.push(Point3::new(x, y, 0));
+                        }
+                    }
+                    edit_tiles(&mut tilemap, &editor, points);
+                }
+                if let Err(e) = tilemap.commit_transaction() {
+                    error!("tilemap editor: {}", e);
+                }
+            }
+            target.stroke_last = None;
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! The tilemap events.
 
-use crate::{chunk::LayerKind, lib::*};
+use crate::{
+    chunk::{LayerKind, RawTile},
+    lib::*,
+};
 
 #[derive(Debug)]
 /// Events that can happen to chunks.
@@ -33,3 +36,43 @@ pub enum TilemapChunkEvent {
         sprite_layer: usize,
     },
 }
+
+#[derive(Debug, Clone, PartialEq)]
+/// Events that can happen to the data of a tilemap and its chunks.
+pub enum TilemapEvent {
+    /// An event when the user data of a chunk has changed.
+    ChunkUserDataChanged {
+        /// The point of the chunk.
+        point: Point2,
+    },
+    /// An event when a metadata value of a chunk was inserted or removed.
+    ChunkMetadataChanged {
+        /// The point of the chunk.
+        point: Point2,
+    },
+    /// An event when a tile has changed, which is only sent if the tilemap
+    /// has tile events.
+    ///
+    /// Changes through a mutable reference of a raw tile are sent when the
+    /// events update in the tilemap stage, or before the tilemap is edited
+    /// again. A tile changed through [`get_tile_mut`] in a system that runs
+    /// after the tilemap stage is therefore only seen by readers one frame
+    /// late, at the next flush.
+    ///
+    /// [`get_tile_mut`]: crate::Tilemap::get_tile_mut
+    TileChanged {
+        /// The global point of the tile.
+        point: Point3,
+        /// The sprite order of the tile.
+        sprite_order: usize,
+        /// The raw tile before the change, if there was one.
+        old: Option<RawTile>,
+        /// The raw tile after the change, if there is one.
+        new: Option<RawTile>,
+    },
+    /// An event when the custom flags at an index have changed.
+    CustomFlagsChanged {
+        /// The index of the custom flags.
+        index: usize,
+    },
+}
@@ -0,0 +1,186 @@
+//! Fog of war that hides what observers have not seen yet.
+//!
+//! A tilemap with fog of war keeps a fog state for every tile next to the
+//! tiles of its chunks. Tiles start hidden, become visible while they are in
+//! the field of view of an entity with a [`FogObserver`], and stay explored
+//! after. The renderer hides tiles that are hidden and darkens explored ones
+//! without touching their colors, only the fog of changed chunks is sent to
+//! their meshes.
+//!
+//! # Enabling fog of war
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::{fog::{FogOfWar, FogState}, prelude::*};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! let mut tilemap = TilemapBuilder::new()
+//!     .texture_atlas(texture_atlas_handle)
+//!     .texture_dimensions(32, 32)
+//!     .fog_of_war(FogOfWar { explored_brightness: 0.4 })
+//!     .finish()
+//!     .unwrap();
+//!
+//! tilemap.insert_chunk((0, 0)).unwrap();
+//! assert_eq!(tilemap.fog_state((3, 3)), Some(FogState::Hidden));
+//!
+//! tilemap.set_fog_state((3, 3), FogState::Explored).unwrap();
+//! assert_eq!(tilemap.fog_state((3, 3)), Some(FogState::Explored));
+//! ```
+
+use crate::{chunk::mesh::ChunkMesh, chunk::RawTile, lib::*, Tilemap};
+
+/// The state of the fog of war over a tile.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum FogState {
+    /// The tile has never been seen and is not drawn.
+    Hidden,
+    /// The tile has been seen before and is drawn darker.
+    Explored,
+    /// The tile is seen by an observer and is drawn as is.
+    Visible,
+}
+
+impl Default for FogState {
+    fn default() -> FogState {
+        FogState::Hidden
+    }
+}
+
+/// The settings of the fog of war of a tilemap.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FogOfWar {
+    /// The brightness explored tiles are drawn with, between 0 and 1.
+    pub explored_brightness: f32,
+}
+
+impl Default for FogOfWar {
+    fn default() -> FogOfWar {
+        FogOfWar {
+            explored_brightness: 0.5,
+        }
+    }
+}
+
+impl FogOfWar {
+    /// Returns the brightness a tile with a fog state is drawn with, where 0
+    /// is not drawn at all.
+    pub(crate) fn brightness(&self, state: FogState) -> f32 {
+        match state {
+            FogState::Hidden => 0.0,
+            // Not 0 so that explored tiles are never hidden.
+            FogState::Explored => self.explored_brightness.max(f32::EPSILON),
+            FogState::Visible => 1.0,
+        }
+    }
+}
+
+/// A component for entities that lift the fog of war of tilemaps around them.
+///
+/// Every frame the point of the observer in each tilemap with fog of war is
+/// found from its global transform. The tiles in the field of view from there
+/// become visible.
+///
+/// # Examples
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_tilemap::fog::FogObserver;
+///
+/// const WALL: usize = 1;
+///
+/// fn spawn_player(mut commands: Commands) {
+///     let observer = FogObserver::new(8, 0, |_, tile| tile.map_or(false, |tile| tile.index == WALL));
+///     commands
+///         .spawn()
+///         .insert(observer)
+///         .insert(Transform::default())
+///         .insert(GlobalTransform::default());
+/// }
+/// ```
+#[derive(Component)]
+pub struct FogObserver {
+    /// The furthest distance that can be seen.
+    pub radius: u32,
+    /// The sprite order of the tiles that can block the view.
+    pub sprite_order: usize,
+    /// Returns `true` if a tile blocks the view.
+    opaque: Box<dyn Fn(Point3, Option<&RawTile>) -> bool + Send + Sync>,
+}
+
+impl FogObserver {
+    /// Constructs a new fog observer with a function that returns `true` if a
+    /// tile blocks the view.
+    pub fn new<F>(radius: u32, sprite_order: usize, opaque: F) -> FogObserver
+    where
+        F: Fn(Point3, Option<&RawTile>) -> bool + Send + Sync + 'static,
+    {
+        FogObserver {
+            radius,
+            sprite_order,
+            opaque: Box::new(opaque),
+        }
+    }
+}
+
+impl Debug for FogObserver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("FogObserver")
+            .field("radius", &self.radius)
+            .field("sprite_order", &self.sprite_order)
+            .finish()
+    }
+}
+
+/// Lifts the fog of war of tilemaps around the observers and sends the fog of
+/// the chunks that changed to their meshes, including those of which the fog
+/// was set by hand.
+pub(crate) fn tilemap_fog(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut tilemap_query: Query<(&mut Tilemap, &GlobalTransform)>,
+    observer_query: Query<(&FogObserver, &GlobalTransform)>,
+) {
+    for (mut tilemap, global_transform) in tilemap_query.iter_mut() {
+        let fog = match tilemap.fog_of_war() {
+            Some(fog) => fog,
+            None => continue,
+        };
+
+        // Only borrow mutably when needed to not trigger change detection.
+        let mut changed = if tilemap.fog_changed().is_empty() {
+            HashSet::default()
+        } else {
+            tilemap.take_fog_changed()
+        };
+
+        let tilemap_transform = Transform::from(*global_transform);
+        let mut visible = HashSet::default();
+        for (observer, transform) in observer_query.iter() {
+            let origin = tilemap.world_to_tile(&tilemap_transform, transform.translation, 0);
+            visible.extend(tilemap.field_of_view(
+                origin,
+                observer.radius,
+                observer.sprite_order,
+                &observer.opaque,
+            ));
+        }
+        if tilemap.fog_visible() != Some(&visible) {
+            changed.extend(tilemap.update_fog(visible));
+        }
+
+        let chunk_dimensions = tilemap.chunk_dimensions();
+        for point in changed {
+            let chunk = match tilemap.get_chunk(&point) {
+                Some(chunk) => chunk,
+                None => continue,
+            };
+            let mesh = match chunk.mesh().and_then(|handle| meshes.get_mut(handle)) {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let fog = chunk.fog_to_renderer_parts(chunk_dimensions, Some(fog));
+            mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_FOG, fog);
+        }
+    }
+}
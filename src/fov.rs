@@ -0,0 +1,530 @@
+//! Field of view and line of sight over the tiles of a tilemap.
+//!
+//! Whether a tile blocks the view is up to a user given function of its point
+//! and raw tile. Square topologies use symmetric shadowcasting, so that a tile
+//! sees another exactly when it is seen by it. Hex topologies see every hex of
+//! which the line from the origin is not blocked.
+//!
+//! * [`Tilemap::field_of_view`] returns the points that can be seen from an
+//! origin within a radius.
+//! * [`Tilemap::line_of_sight`] checks if the line between two points is not
+//! blocked.
+//! * [`ViewShade`] is a component for the entity of a tilemap that tints or
+//! hides the tiles outside a field of view, with the [`ViewShadePlugin`].
+//!
+//! # Seeing around a pillar
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::{chunk::RawTile, point::Point3, prelude::*};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! const PILLAR: usize = 1;
+//!
+//! let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+//! tilemap.insert_chunk((0, 0)).unwrap();
+//! tilemap.insert_tile(Tile { point: (2, 0), sprite_index: PILLAR, ..Default::default() }).unwrap();
+//!
+//! let opaque = |_, tile: Option<&RawTile>| tile.map_or(false, |tile| tile.index == PILLAR);
+//! let visible = tilemap.field_of_view((0, 0), 5, 0, opaque);
+//!
+//! // The pillar itself is seen, but not what is behind it.
+//! assert!(visible.contains(&Point3::new(2, 0, 0)));
+//! assert!(!visible.contains(&Point3::new(4, 0, 0)));
+//! assert!(visible.contains(&Point3::new(0, 4, 0)));
+//! ```
+//!
+//! [`Tilemap::field_of_view`]: crate::Tilemap::field_of_view
+//! [`Tilemap::line_of_sight`]: crate::Tilemap::line_of_sight
+
+use crate::{chunk::RawTile, lib::*, Tilemap, TilemapSystem};
+
+/// A slope of a shadowcasting scan as a fraction.
+#[derive(Copy, Clone, Debug)]
+struct Slope {
+    /// The numerator of the fraction.
+    rise: i32,
+    /// The denominator of the fraction, which is always positive.
+    run: i32,
+}
+
+impl Slope {
+    /// The slope from the origin to the near edge of a column at a depth.
+    fn edge(depth: i32, column: i32) -> Slope {
+        Slope {
+            rise: 2 * column - 1,
+            run: 2 * depth,
+        }
+    }
+
+    /// Returns the first column at or after the slope at a depth, rounding
+    /// half a column up.
+    fn min_column(self, depth: i32) -> i32 {
+        (2 * depth * self.rise + self.run).div_euclid(2 * self.run)
+    }
+
+    /// Returns the last column at or before the slope at a depth, rounding
+    /// half a column down.
+    fn max_column(self, depth: i32) -> i32 {
+        -(-2 * depth * self.rise + self.run).div_euclid(2 * self.run)
+    }
+
+    /// Returns `true` if the center of a column at a depth is at or after the
+    /// slope.
+    fn is_before(self, depth: i32, column: i32) -> bool {
+        column * self.run >= depth * self.rise
+    }
+
+    /// Returns `true` if the center of a column at a depth is at or before
+    /// the slope.
+    fn is_after(self, depth: i32, column: i32) -> bool {
+        column * self.run <= depth * self.rise
+    }
+}
+
+/// Scans the rows of a quadrant of a symmetric shadowcast.
+///
+/// The transform turns a depth and column of the quadrant into a point.
+fn scan<T, F>(
+    depth: i32,
+    mut start: Slope,
+    end: Slope,
+    radius: i32,
+    transform: &T,
+    opaque: &mut F,
+    visible: &mut HashSet<Point3>,
+) where
+    T: Fn(i32, i32) -> Point3,
+    F: FnMut(Point3) -> bool,
+{
+    if depth > radius {
+        return;
+    }
+    let mut previous = None;
+    for column in start.min_column(depth)..=end.max_column(depth) {
+        let point = transform(depth, column);
+        let wall = opaque(point);
+        let symmetric = start.is_before(depth, column) && end.is_after(depth, column);
+        if (wall || symmetric) && depth * depth + column * column <= radius * radius + radius {
+            visible.insert(point);
+        }
+        match previous {
+            Some(true) if !wall => start = Slope::edge(depth, column),
+            Some(false) if wall => scan(
+                depth + 1,
+                start,
+                Slope::edge(depth, column),
+                radius,
+                transform,
+                opaque,
+                visible,
+            ),
+            _ => {}
+        }
+        previous = Some(wall);
+    }
+    if previous == Some(false) {
+        scan(depth + 1, start, end, radius, transform, opaque, visible);
+    }
+}
+
+/// Returns the points that can be seen from an origin within a radius on a
+/// square grid with symmetric shadowcasting.
+pub(crate) fn shadowcast<F>(origin: Point3, radius: u32, mut opaque: F) -> HashSet<Point3>
+where
+    F: FnMut(Point3) -> bool,
+{
+    let Point3 { x, y, z } = origin;
+    let radius = radius as i32;
+    let mut visible = HashSet::default();
+    visible.insert(origin);
+
+    let start = Slope { rise: -1, run: 1 };
+    let end = Slope { rise: 1, run: 1 };
+    let north = |depth, column| Point3::new(x + column, y + depth, z);
+    let east = |depth, column| Point3::new(x + depth, y - column, z);
+    let south = |depth, column| Point3::new(x - column, y - depth, z);
+    let west = |depth, column| Point3::new(x - depth, y + column, z);
+    scan(1, start, end, radius, &north, &mut opaque, &mut visible);
+    scan(1, start, end, radius, &east, &mut opaque, &mut visible);
+    scan(1, start, end, radius, &south, &mut opaque, &mut visible);
+    scan(1, start, end, radius, &west, &mut opaque, &mut visible);
+
+    visible
+}
+
+/// Returns the points that can be seen from an origin within a radius on a
+/// hex grid, which are those with a line from the origin that is not blocked.
+pub(crate) fn hex_cast<F>(
+    origin: Point3,
+    radius: u32,
+    layout: HexLayout,
+    mut opaque: F,
+) -> HashSet<Point3>
+where
+    F: FnMut(Point3) -> bool,
+{
+    let center = AxialPoint::from_offset(origin.into(), layout);
+    center
+        .spiral(radius)
+        .into_iter()
+        .map(|hex| hex_point(hex, layout, origin.z))
+        .filter(|point| {
+            let line = hex_line(origin, *point, layout);
+            let between = line.len().saturating_sub(1);
+            line.iter()
+                .take(between)
+                .skip(1)
+                .all(|point| !opaque(*point))
+        })
+        .collect()
+}
+
+/// Returns the point of a hex in offset coordinates at a z depth.
+fn hex_point(hex: AxialPoint, layout: HexLayout, z: i32) -> Point3 {
+    let Point2 { x, y } = hex.to_offset(layout);
+    Point3::new(x, y, z)
+}
+
+/// Returns the points on a straight line between two points of a hex grid,
+/// including both.
+pub(crate) fn hex_line(from: Point3, to: Point3, layout: HexLayout) -> Vec<Point3> {
+    let start = AxialPoint::from_offset(from.into(), layout);
+    let end = AxialPoint::from_offset(to.into(), layout);
+    start
+        .line_to(end)
+        .into_iter()
+        .map(|hex| hex_point(hex, layout, from.z))
+        .collect()
+}
+
+/// Returns the points on a straight line between two points of a square grid
+/// with Bresenham's algorithm, including both.
+pub(crate) fn line(from: Point3, to: Point3) -> Vec<Point3> {
+    let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let step_x = if from.x < to.x { 1 } else { -1 };
+    let step_y = if from.y < to.y { 1 } else { -1 };
+    let (mut x, mut y) = (from.x, from.y);
+    let mut error = dx + dy;
+    let mut points = Vec::with_capacity(dx.max(-dy) as usize + 1);
+    loop {
+        points.push(Point3::new(x, y, from.z));
+        if x == to.x && y == to.y {
+            break;
+        }
+        let double = 2 * error;
+        if double >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if double <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+    points
+}
+
+/// A component that tints or hides the tiles of a tilemap outside a field of
+/// view.
+///
+/// Insert it on the same entity as the [`Tilemap`] and add the
+/// [`ViewShadePlugin`]. Whenever its origin, radius, sprite order or color
+/// change the field of view is found again and the color of every tile
+/// outside it is multiplied by the shade color. Tiles that come back into
+/// view get the color they had when they were shaded. A shade color without
+/// alpha hides the tiles.
+///
+/// Only the tiles around the view before and after it changed are shaded
+/// again, and the tiles of chunks that were not shaded yet. Tiles that are
+/// inserted into shaded chunks while the view stays the same are not shaded
+/// until the view reaches them. Tiles of which the color was changed while
+/// they were shaded keep the new color when they come back into view.
+///
+/// # Examples
+/// ```
+/// use bevy::prelude::*;
+/// use bevy_tilemap::{fov::ViewShade, point::Point3, prelude::*};
+///
+/// const WALL: usize = 1;
+///
+/// fn follow_player(mut query: Query<&mut ViewShade>) {
+///     for mut shade in query.iter_mut() {
+///         shade.origin = Point3::new(4, 2, 0);
+///     }
+/// }
+///
+/// let shade = ViewShade::new(Point3::new(0, 0, 0), 8, 0, Color::rgb(0.3, 0.3, 0.3), |_, tile| {
+///     tile.map_or(true, |tile| tile.index == WALL)
+/// });
+/// assert!(shade.visible().is_empty());
+/// ```
+#[derive(Component)]
+pub struct ViewShade {
+    /// The point the view is seen from.
+    pub origin: Point3,
+    /// The furthest distance that can be seen.
+    pub radius: u32,
+    /// The sprite order of the tiles that can block the view.
+    pub sprite_order: usize,
+    /// The color that the colors of tiles outside the view are multiplied by.
+    pub color: Color,
+    /// Returns `true` if a tile blocks the view.
+    opaque: Box<dyn Fn(Point3, Option<&RawTile>) -> bool + Send + Sync>,
+    /// The points that were seen when the view was last found.
+    visible: HashSet<Point3>,
+    /// The colors the shaded tiles had before they were shaded, with the
+    /// color they were shaded to.
+    shaded: HashMap<(Point3, usize), (Color, Color)>,
+    /// The chunks of which all tiles were shaded.
+    chunks: HashSet<Point2>,
+    /// The origin, radius, sprite order and color the view was last found
+    /// with.
+    found: Option<(Point3, u32, usize, Color)>,
+}
+
+impl ViewShade {
+    /// Constructs a new view shade with a function that returns `true` if a
+    /// tile blocks the view.
+    pub fn new<F>(
+        origin: Point3,
+        radius: u32,
+        sprite_order: usize,
+        color: Color,
+        opaque: F,
+    ) -> ViewShade
+    where
+        F: Fn(Point3, Option<&RawTile>) -> bool + Send + Sync + 'static,
+    {
+        ViewShade {
+            origin,
+            radius,
+            sprite_order,
+            color,
+            opaque: Box::new(opaque),
+            visible: HashSet::default(),
+            shaded: HashMap::default(),
+            chunks: HashSet::default(),
+            found: None,
+        }
+    }
+
+    /// Returns the points that were seen when the view was last found.
+    pub fn visible(&self) -> &HashSet<Point3> {
+        &self.visible
+    }
+
+    /// Returns the shaded color of a tile color.
+    fn shade(&self, color: Color) -> Color {
+        Color::rgba(
+            color.r() * self.color.r(),
+            color.g() * self.color.g(),
+            color.b() * self.color.b(),
+            // A tile without alpha would be cleared instead.
+            (color.a() * self.color.a()).max(f32::EPSILON),
+        )
+    }
+}
+
+impl Debug for ViewShade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ViewShade")
+            .field("origin", &self.origin)
+            .field("radius", &self.radius)
+            .field("sprite_order", &self.sprite_order)
+            .field("color", &self.color)
+            .field("visible", &self.visible)
+            .field("shaded", &self.shaded)
+            .field("chunks", &self.chunks)
+            .field("found", &self.found)
+            .finish()
+    }
+}
+
+/// The plugin that shades the tiles of tilemaps with a [`ViewShade`], which is
+/// not part of the default plugins.
+///
+/// It must be added after the [`TilemapPlugin`].
+///
+/// # Examples
+/// ```no_run
+/// use bevy::prelude::*;
+/// use bevy_tilemap::{fov::ViewShadePlugin, prelude::*};
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(TilemapDefaultPlugins)
+///     .add_plugin(ViewShadePlugin)
+///     .run()
+/// ```
+///
+/// [`TilemapPlugin`]: crate::TilemapPlugin
+#[derive(Default)]
+pub struct ViewShadePlugin;
+
+impl Plugin for ViewShadePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            crate::stage::TILEMAP,
+            tilemap_view_shade.system().before(TilemapSystem::Events),
+        );
+    }
+}
+
+/// Finds the field of view of view shades that changed and shades the tiles
+/// outside of it.
+fn tilemap_view_shade(mut tilemap_query: Query<(&mut Tilemap, &mut ViewShade)>) {
+    for (mut tilemap, mut shade) in tilemap_query.iter_mut() {
+        let view = (shade.origin, shade.radius, shade.sprite_order, shade.color);
+        if shade.found == Some(view) {
+            continue;
+        }
+        let recolor = shade.found.map_or(false, |found| found.3 != shade.color);
+
+        let visible = tilemap.field_of_view(
+            shade.origin,
+            shade.radius,
+            shade.sprite_order,
+            &shade.opaque,
+        );
+        let z = shade.origin.z;
+        let in_view = |point: Point3| visible.contains(&Point3::new(point.x, point.y, z));
+
+        // Only the tiles around the view before and after can be seen or
+        // stop being seen, besides those of chunks that were not shaded.
+        let mut points = tilemap.tile_points_around(shade.origin, shade.radius);
+        if let Some((origin, radius, ..)) = shade.found {
+            points.extend(tilemap.tile_points_around(origin, radius));
+        }
+        if recolor {
+            points.extend(shade.shaded.keys().copied());
+        }
+        shade
+            .chunks
+            .retain(|chunk_point| tilemap.chunks().contains_key(chunk_point));
+        let unshaded: Vec<Point2> = tilemap
+            .chunks()
+            .keys()
+            .filter(|chunk_point| !shade.chunks.contains(chunk_point))
+            .copied()
+            .collect();
+        for chunk_point in unshaded {
+            points.extend(tilemap.chunk_tile_points(chunk_point));
+            shade.chunks.insert(chunk_point);
+        }
+
+        let mut colors = Vec::new();
+        let mut seen = HashSet::default();
+        for key in points {
+            if !seen.insert(key) {
+                continue;
+            }
+            let (point, sprite_order) = key;
+            let current = match tilemap.raw_tile(point, sprite_order) {
+                Some(tile) => tile.color,
+                None => {
+                    shade.shaded.remove(&key);
+                    continue;
+                }
+            };
+            // A tile of which the color was changed since it was shaded keeps
+            // the new color.
+            let original = match shade.shaded.remove(&key) {
+                Some((original, shaded)) if shaded == current => original,
+                _ => current,
+            };
+            if in_view(point) {
+                if original != current {
+                    colors.push((point, sprite_order, original));
+                }
+            } else {
+                let shaded = shade.shade(original);
+                if shaded != current {
+                    colors.push((point, sprite_order, shaded));
+                }
+                shade.shaded.insert(key, (original, shaded));
+            }
+        }
+        if !colors.is_empty() {
+            tilemap.set_tile_colors(colors);
+        }
+
+        shade.visible = visible;
+        shade.found = Some(view);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile::Tile;
+
+    /// The sprite index of a tile that blocks the view.
+    const WALL: usize = 1;
+
+    /// Returns the color of the tile at a point.
+    fn tile_color(point: (i32, i32), app: &mut App) -> Color {
+        let mut tilemap_query = app.world.query::<&mut Tilemap>();
+        let mut tilemap = tilemap_query.iter_mut(&mut app.world).next().unwrap();
+        tilemap.get_tile(point, 0).unwrap().color
+    }
+
+    #[test]
+    fn view_shade_keeps_changed_colors() {
+        let mut app = App::new();
+        app.add_stage("update", SystemStage::parallel())
+            .add_system_to_stage("update", tilemap_view_shade.system());
+
+        let mut tilemap = Tilemap::new(Handle::weak(HandleId::random::<TextureAtlas>()), 32, 32);
+        tilemap.insert_chunk((0, 0)).unwrap();
+        let tiles = vec![
+            Tile {
+                point: (1, 0),
+                ..Default::default()
+            },
+            Tile {
+                point: (2, 0),
+                sprite_index: WALL,
+                ..Default::default()
+            },
+            Tile {
+                point: (3, 0),
+                ..Default::default()
+            },
+            Tile {
+                point: (10, 10),
+                ..Default::default()
+            },
+        ];
+        tilemap.insert_tiles(tiles).unwrap();
+        let shade = ViewShade::new(Point3::new(0, 0, 0), 3, 0, Color::BLACK, |_, tile| {
+            tile.map_or(false, |tile| tile.index == WALL)
+        });
+        app.world.spawn().insert(tilemap).insert(shade);
+
+        app.update();
+        let shaded = Color::rgba(0.0, 0.0, 0.0, 1.0);
+        assert_eq!(tile_color((1, 0), &mut app), Color::WHITE);
+        assert_eq!(tile_color((3, 0), &mut app), shaded);
+        assert_eq!(tile_color((10, 10), &mut app), shaded);
+
+        {
+            let mut tilemap_query = app.world.query::<&mut Tilemap>();
+            let mut tilemap = tilemap_query.iter_mut(&mut app.world).next().unwrap();
+            tilemap.get_tile_mut((10, 10), 0).unwrap().color = Color::RED;
+        }
+        {
+            let mut shade_query = app.world.query::<&mut ViewShade>();
+            let mut shade = shade_query.iter_mut(&mut app.world).next().unwrap();
+            shade.origin = Point3::new(10, 9, 0);
+        }
+
+        app.update();
+        // The tint that was set while the tile was shaded is kept.
+        assert_eq!(tile_color((10, 10), &mut app), Color::RED);
+        assert_eq!(tile_color((1, 0), &mut app), shaded);
+        assert_eq!(tile_color((3, 0), &mut app), shaded);
+    }
+}
@@ -0,0 +1,110 @@
+//! Procedural generation of chunks that do not exist yet.
+//!
+//! A tilemap with a [`ChunkGenerator`] generates every chunk that is spawned
+//! before it exists, such as when the camera reaches new chunks with
+//! [`auto_spawn`]. A generator is a function of the point of the chunk, so
+//! the same chunk is always generated the same way and endless worlds
+//! generate themselves.
+//!
+//! With [`async_chunks`] chunks are generated on the async compute task pool
+//! and spawned once they are done, so generating does not stall the frame.
+//!
+//! # Generating grass with some trees
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::{dimension::Dimension3, point::{Point2, Point3}, prelude::*};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! const GRASS: usize = 0;
+//! const TREE: usize = 1;
+//!
+//! let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+//! tilemap.set_chunk_generator(|chunk: Point2, dimensions: Dimension3| {
+//!     let mut tiles = Vec::new();
+//!     for y in 0..dimensions.height as i32 {
+//!         for x in 0..dimensions.width as i32 {
+//!             // The same chunk always has the same trees.
+//!             let sprite_index = if (x * 7 + y * 3 + chunk.x + chunk.y) % 11 == 0 {
+//!                 TREE
+//!             } else {
+//!                 GRASS
+//!             };
+//!             tiles.push(Tile { point: Point3::new(x, y, 0), sprite_index, ..Default::default() });
+//!         }
+//!     }
+//!     tiles
+//! });
+//!
+//! assert!(tilemap.spawn_chunk((0, 0)).is_ok());
+//! assert!(tilemap.contains_chunk((0, 0)));
+//! assert_eq!(tilemap.get_tile((-16, -16), 0).map(|tile| tile.index), Some(TREE));
+//! ```
+//!
+//! [`auto_spawn`]: crate::tilemap::TilemapBuilder::auto_spawn
+//! [`async_chunks`]: crate::tilemap::TilemapBuilder::async_chunks
+
+use crate::{
+    chunk::{Chunk, LayerKind},
+    lib::*,
+    tile::Tile,
+};
+
+/// Generates the tiles of chunks that do not exist yet.
+///
+/// Implemented for every function that takes the point of a chunk and the
+/// chunk dimensions and returns the tiles.
+pub trait ChunkGenerator: Send + Sync + 'static {
+    /// Returns the tiles of a new chunk at a point.
+    ///
+    /// The points of the tiles are in the chunk, from 0 at the bottom left
+    /// tile up to the chunk dimensions. Tiles outside of the chunk are
+    /// ignored.
+    fn generate(&self, point: Point2, dimensions: Dimension3) -> Vec<Tile<Point3>>;
+}
+
+impl<F> ChunkGenerator for F
+where
+    F: Fn(Point2, Dimension3) -> Vec<Tile<Point3>> + Send + Sync + 'static,
+{
+    fn generate(&self, point: Point2, dimensions: Dimension3) -> Vec<Tile<Point3>> {
+        self(point, dimensions)
+    }
+}
+
+impl Debug for dyn ChunkGenerator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("ChunkGenerator")
+    }
+}
+
+/// Returns a new chunk with the tiles of a generator.
+pub(crate) fn generate_chunk(
+    generator: &dyn ChunkGenerator,
+    point: Point2,
+    layer_kinds: &[Option<LayerKind>],
+    dimensions: Dimension3,
+) -> Chunk {
+    let mut chunk = Chunk::new(point, layer_kinds, dimensions);
+    for tile in generator.generate(point, dimensions) {
+        let Point3 { x, y, z } = tile.point;
+        if x < 0
+            || y < 0
+            || z < 0
+            || x >= dimensions.width as i32
+            || y >= dimensions.height as i32
+            || z >= dimensions.depth as i32
+        {
+            warn!(
+                "generated tile {} is outside of chunk {}",
+                tile.point, point
+            );
+            continue;
+        }
+        let index = dimensions.encode_point_unchecked(tile.point);
+        chunk.set_tile(index, tile);
+    }
+    chunk
+}
@@ -0,0 +1,343 @@
+//! Undo and redo of tilemap edits grouped in transactions.
+//!
+//! Edits are only recorded while a transaction is open. Every tile that is
+//! inserted, cleared or changed through a mutable reference is recorded with
+//! its raw tile before and after, as well as data values, moved layers and
+//! removed layers. Tile data is not recorded and can not be changed during a
+//! transaction. Once committed, the transaction can be undone and redone as a
+//! whole, marking every chunk it touches as modified.
+//!
+//! The history keeps a limited amount of transactions, the oldest are dropped
+//! first. It is saved with the tilemap and can also be serialized on its own.
+//!
+//! # Undoing a transaction
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::prelude::*;
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+//! tilemap.insert_chunk((0, 0)).unwrap();
+//!
+//! tilemap.begin_transaction("paint wall").unwrap();
+//! tilemap.insert_tile(Tile { point: (1, 1), sprite_index: 2, ..Default::default() }).unwrap();
+//! tilemap.insert_tile(Tile { point: (2, 1), sprite_index: 2, ..Default::default() }).unwrap();
+//! tilemap.commit_transaction().unwrap();
+//!
+//! assert_eq!(tilemap.undo().unwrap(), Some("paint wall".to_string()));
+//! assert!(tilemap.get_tile((1, 1), 0).is_none());
+//!
+//! assert_eq!(tilemap.redo().unwrap(), Some("paint wall".to_string()));
+//! assert_eq!(tilemap.get_tile((2, 1), 0).map(|tile| tile.index), Some(2));
+//! ```
+
+use crate::{
+    chunk::{LayerKind, RawTile},
+    lib::*,
+    tilemap::TilemapLayer,
+};
+
+/// The default amount of transactions that are kept.
+const DEFAULT_MAX_LEN: usize = 100;
+
+/// A single recorded edit of a tilemap.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) enum Edit {
+    /// A tile was changed.
+    Tile {
+        /// The global point of the tile.
+        point: Point3,
+        /// The sprite order of the tile.
+        sprite_order: usize,
+        /// The raw tile before the change, if there was one.
+        old: Option<RawTile>,
+        /// The raw tile after the change, if there is one.
+        new: Option<RawTile>,
+    },
+    /// A layer was moved to another sprite order.
+    MoveLayer {
+        /// The sprite order the layer was at.
+        from: usize,
+        /// The sprite order the layer is at.
+        to: usize,
+    },
+    /// A layer was removed, after the removal of its tiles was recorded.
+    RemoveLayer {
+        /// The sprite order of the layer.
+        sprite_order: usize,
+        /// The layer that was removed.
+        layer: TilemapLayer,
+    },
+    /// A data value was changed.
+    Data {
+        /// The point of the chunk of the value.
+        chunk_point: Point2,
+        /// The index of the value in the chunk.
+        index: usize,
+        /// The data layer of the value.
+        data_layer: usize,
+        /// The value before the change, if the chunk had one.
+        old: Option<i32>,
+        /// The value after the change, if the chunk has one.
+        new: Option<i32>,
+    },
+    /// A data layer was removed, after the removal of its values was
+    /// recorded.
+    RemoveDataLayer {
+        /// The data layer that was removed.
+        data_layer: usize,
+        /// The kind of the data layer.
+        kind: LayerKind,
+    },
+}
+
+/// Named edits of a tilemap that are undone and redone together.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Transaction {
+    /// The name of the transaction.
+    name: String,
+    /// The edits in the order they were made.
+    edits: Vec<Edit>,
+}
+
+impl Transaction {
+    /// Returns the name of the transaction.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the amount of edits in the transaction.
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Returns `true` if nothing was edited in the transaction.
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Returns the edits in the order they were made.
+    pub(crate) fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+}
+
+/// The transactions of a tilemap that can be undone and redone.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct History {
+    /// The most transactions that are kept to be undone.
+    max_len: usize,
+    /// The transactions that can be undone, the latest last.
+    undo: VecDeque<Transaction>,
+    /// The transactions that can be redone, the latest undone last.
+    redo: Vec<Transaction>,
+    /// The transaction that edits are recorded in, if one was begun.
+    open: Option<Transaction>,
+}
+
+impl Default for History {
+    fn default() -> History {
+        History::new(DEFAULT_MAX_LEN)
+    }
+}
+
+impl History {
+    /// Constructs a new empty history that keeps at most an amount of
+    /// transactions.
+    pub fn new(max_len: usize) -> History {
+        History {
+            max_len,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            open: None,
+        }
+    }
+
+    /// Returns the most transactions that are kept to be undone.
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Sets the most transactions that are kept to be undone, dropping the
+    /// oldest ones over it.
+    pub fn set_max_len(&mut self, max_len: usize) {
+        self.max_len = max_len;
+        self.truncate();
+    }
+
+    /// Returns the transactions that can be undone, from the oldest to the
+    /// latest.
+    pub fn undo_transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.undo.iter()
+    }
+
+    /// Returns the transactions that can be redone, from the latest undone to
+    /// the oldest.
+    pub fn redo_transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.redo.iter().rev()
+    }
+
+    /// Returns `true` if there is a transaction that can be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns `true` if there is a transaction that can be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Returns `true` if a transaction was begun and not yet committed.
+    pub fn is_recording(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Removes all transactions, also the one that is recorded.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open = None;
+    }
+
+    /// Begins recording a transaction, returning `false` if one is already
+    /// being recorded.
+    pub(crate) fn begin(&mut self, name: String) -> bool {
+        if self.open.is_some() {
+            return false;
+        }
+        self.open = Some(Transaction {
+            name,
+            edits: Vec::new(),
+        });
+        true
+    }
+
+    /// Records an edit in the open transaction, if there is one.
+    pub(crate) fn record(&mut self, edit: Edit) {
+        if let Some(transaction) = &mut self.open {
+            transaction.edits.push(edit);
+        }
+    }
+
+    /// Ends the open transaction so that it can be undone, returning `false`
+    /// if there was none. Transactions without edits are dropped.
+    ///
+    /// Nothing can be redone after a new transaction.
+    pub(crate) fn commit(&mut self) -> bool {
+        let transaction = match self.open.take() {
+            Some(transaction) => transaction,
+            None => return false,
+        };
+        if !transaction.is_empty() {
+            self.redo.clear();
+            self.undo.push_back(transaction);
+            self.truncate();
+        }
+        true
+    }
+
+    /// Returns the latest transaction to undo.
+    pub(crate) fn next_undo(&self) -> Option<&Transaction> {
+        self.undo.back()
+    }
+
+    /// Returns the latest undone transaction to redo.
+    pub(crate) fn next_redo(&self) -> Option<&Transaction> {
+        self.redo.last()
+    }
+
+    /// Moves the latest transaction to undo to the ones that can be redone,
+    /// once it was undone.
+    pub(crate) fn undone(&mut self) {
+        if let Some(transaction) = self.undo.pop_back() {
+            self.redo.push(transaction);
+        }
+    }
+
+    /// Moves the latest undone transaction to the ones that can be undone,
+    /// once it was redone.
+    pub(crate) fn redone(&mut self) {
+        if let Some(transaction) = self.redo.pop() {
+            self.undo.push_back(transaction);
+            self.truncate();
+        }
+    }
+
+    /// Drops the oldest transactions over the most that are kept.
+    fn truncate(&mut self) {
+        while self.undo.len() > self.max_len {
+            self.undo.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Commits a transaction with one tile edit.
+    fn commit_tile(history: &mut History, name: &str, index: usize) {
+        assert!(history.begin(name.to_string()));
+        history.record(Edit::Tile {
+            point: Point3::new(index as i32, 0, 0),
+            sprite_order: 0,
+            old: None,
+            new: Some(RawTile {
+                index,
+                ..Default::default()
+            }),
+        });
+        assert!(history.commit());
+    }
+
+    #[test]
+    fn keeps_at_most_max_len() {
+        let mut history = History::new(2);
+        for (index, name) in ["a", "b", "c"].iter().enumerate() {
+            commit_tile(&mut history, name, index);
+        }
+        let names = history
+            .undo_transactions()
+            .map(Transaction::name)
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["b", "c"]);
+
+        history.undone();
+        history.undone();
+        assert!(!history.can_undo());
+        history.set_max_len(1);
+        history.redone();
+        history.redone();
+        let names = history
+            .undo_transactions()
+            .map(Transaction::name)
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["c"]);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn serialization_round_trip() {
+        let mut history = History::new(5);
+        commit_tile(&mut history, "paint", 3);
+        assert!(history.begin("move".to_string()));
+        history.record(Edit::MoveLayer { from: 0, to: 2 });
+        history.record(Edit::Data {
+            chunk_point: Point2::new(-1, 2),
+            index: 7,
+            data_layer: 1,
+            old: None,
+            new: Some(4),
+        });
+        assert!(history.commit());
+        history.undone();
+
+        let json = serde_json::to_string(&history).unwrap();
+        let deserialized: History = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, history);
+        assert_eq!(deserialized.next_redo().map(Transaction::len), Some(2));
+    }
+}
@@ -0,0 +1,1143 @@
+//! Loading of [LDtk] projects as Bevy assets.
+//!
+//! This module is only available with the `ldtk` feature.
+//!
+//! ```toml
+//! [dependencies]
+//! bevy_tilemap = { version = "0.4", features = ["ldtk"] }
+//! ```
+//!
+//! With the feature enabled, the [`TilemapPlugin`] registers an asset loader
+//! for `.ldtk` files which produces an [`LdtkProject`]. Every level of the
+//! project, including levels saved in separate files, becomes an
+//! [`LdtkLevel`] with:
+//!
+//! * a ready to use [`Tilemap`] with the tiles of the Tiles, AutoLayer and
+//! IntGrid layers,
+//! * the values of every IntGrid layer as an [`IntGrid`] which can be queried
+//! with tile coordinates,
+//! * every entity instance as an [`LdtkEntity`] describing what to spawn and
+//! where,
+//! * an [`LdtkReport`] of everything which could not be carried over.
+//!
+//! LDtk lists its layers from the top down, the tilemap sprite layers are
+//! ordered from the bottom up. Where LDtk stacks several tiles in the same
+//! cell of a layer, which auto layers often do, the tiles are spread over as
+//! many sprite layers as needed.
+//!
+//! LDtk counts cells from the top left of a level while tilemaps count them
+//! from the bottom, so the level is flipped as it is loaded and centered on
+//! the tilemap's origin.
+//!
+//! # Loading a level
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_tilemap::{ldtk::LdtkProject, prelude::*};
+//!
+//! struct Project(Handle<LdtkProject>);
+//!
+//! fn load(mut commands: Commands, asset_server: Res<AssetServer>) {
+//!     commands.insert_resource(Project(asset_server.load("maps/world.ldtk")));
+//! }
+//!
+//! fn spawn(
+//!     mut commands: Commands,
+//!     project: Res<Project>,
+//!     mut projects: ResMut<Assets<LdtkProject>>,
+//! ) {
+//!     let level = match projects
+//!         .get_mut(&project.0)
+//!         .and_then(|project| project.remove_level("Level_0"))
+//!     {
+//!         Some(level) => level,
+//!         None => return,
+//!     };
+//!
+//!     let transform = Transform::default();
+//!     for entity in level.entities.iter() {
+//!         let translation = entity.translation(&level.tilemap, &transform);
+//!         info!("{} at {}", entity.identifier, translation);
+//!     }
+//!
+//!     commands.spawn().insert_bundle(TilemapBundle {
+//!         tilemap: level.tilemap,
+//!         visible: Visible {
+//!             is_visible: true,
+//!             is_transparent: true,
+//!         },
+//!         transform,
+//!         global_transform: Default::default(),
+//!     });
+//! }
+//! ```
+//!
+//! [LDtk]: https://ldtk.io/
+//! [`TilemapPlugin`]: crate::TilemapPlugin
+
+use crate::{
+    chunk::LayerKind,
+    lib::*,
+    tile::{Tile, TileFlip},
+    tilemap::{TilemapBuilder, TilemapError},
+    Tilemap, TilemapLayer,
+};
+use serde_json::Value;
+
+/// Flag set on a tile if it is flipped horizontally.
+const FLIPPED_X: u8 = 0b01;
+/// Flag set on a tile if it is flipped vertically.
+const FLIPPED_Y: u8 = 0b10;
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+/// The kinds of errors that can occur when loading an LDtk project.
+pub enum ErrorKind {
+    /// The JSON of the file could not be parsed.
+    Json(String),
+    /// A layer refers to a tileset which is not defined.
+    MissingTileset(i64),
+    /// A tileset has no image.
+    MissingTilesetImage(String),
+    /// An external level was referenced but never resolved.
+    UnresolvedLevel(String),
+    /// The tilemap could not be constructed.
+    Tilemap(TilemapError),
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        use ErrorKind::*;
+        match self {
+            Json(err) => write!(f, "could not parse the JSON: {}", err),
+            MissingTileset(uid) => write!(f, "the tileset with uid {} is not defined", uid),
+            MissingTilesetImage(identifier) => {
+                write!(f, "the tileset {} has no image", identifier)
+            }
+            UnresolvedLevel(path) => write!(
+                f,
+                "the external level {} was not resolved, try `resolve_level` first",
+                path
+            ),
+            Tilemap(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl Error for ErrorKind {}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+/// The error type for loading LDtk projects.
+pub struct LdtkError(pub Box<ErrorKind>);
+
+impl Display for LdtkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for LdtkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl From<ErrorKind> for LdtkError {
+    fn from(kind: ErrorKind) -> LdtkError {
+        LdtkError(Box::new(kind))
+    }
+}
+
+impl From<TilemapError> for LdtkError {
+    fn from(err: TilemapError) -> LdtkError {
+        LdtkError(Box::new(ErrorKind::Tilemap(err)))
+    }
+}
+
+impl From<serde_json::Error> for LdtkError {
+    fn from(err: serde_json::Error) -> LdtkError {
+        LdtkError(Box::new(ErrorKind::Json(err.to_string())))
+    }
+}
+
+/// An LDtk result.
+pub type LdtkResult<T> = Result<T, LdtkError>;
+
+#[derive(Clone, PartialEq, Debug)]
+/// A feature of an LDtk level which could not be carried over to the tilemap.
+pub enum Unsupported {
+    /// Only one tileset can be used per level, the tiles of this layer which
+    /// use another tileset were skipped.
+    Tileset {
+        /// The identifier of the layer.
+        layer: String,
+        /// How many tiles were skipped.
+        skipped: usize,
+    },
+    /// A layer with a different grid size than the tileset of the level,
+    /// which was skipped.
+    GridSize {
+        /// The identifier of the layer.
+        layer: String,
+        /// The grid size of the layer.
+        size: u32,
+    },
+    /// A hidden layer, which was skipped.
+    HiddenLayer(String),
+    /// A layer with an offset, which was placed without it.
+    LayerOffset(String),
+}
+
+impl Display for Unsupported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        use Unsupported::*;
+        match self {
+            Tileset { layer, skipped } => write!(
+                f,
+                "only one tileset is supported per level, skipped {} tiles of layer {}",
+                skipped, layer
+            ),
+            GridSize { layer, size } => write!(
+                f,
+                "layer {} with a grid size of {} does not match the tileset and was skipped",
+                layer, size
+            ),
+            HiddenLayer(layer) => write!(f, "hidden layer {} was skipped", layer),
+            LayerOffset(layer) => write!(f, "the offset of layer {} is not supported", layer),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+/// A report of everything in an LDtk level that could not be carried over.
+pub struct LdtkReport {
+    /// All the unsupported features found in the level.
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl LdtkReport {
+    /// Returns `true` if the whole level was carried over.
+    pub fn is_empty(&self) -> bool {
+        self.unsupported.is_empty()
+    }
+}
+
+/// The values of an IntGrid layer.
+///
+/// The values are queried with the tile coordinates of the tilemap of the
+/// level. If the grid size of the layer is not the same as the tileset, each
+/// cell of the layer still counts as one tile.
+#[derive(Clone, PartialEq, Debug)]
+pub struct IntGrid {
+    /// The width of the layer in cells.
+    width: u32,
+    /// The height of the layer in cells.
+    height: u32,
+    /// The values of the cells, from the top left row by row, 0 if empty.
+    values: Vec<i32>,
+    /// The identifiers of the values, if they have one.
+    identifiers: HashMap<i32, String>,
+}
+
+impl IntGrid {
+    /// Returns the value at a point, or `None` if the cell is empty or out of
+    /// bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::ldtk::LdtkFile;
+    ///
+    /// let ldtk = r#"{
+    ///     "defs": { "tilesets": [], "layers": [] },
+    ///     "levels": [{
+    ///         "identifier": "Level_0",
+    ///         "worldX": 0, "worldY": 0, "pxWid": 32, "pxHei": 32,
+    ///         "layerInstances": [{
+    ///             "__identifier": "Collisions", "__type": "IntGrid",
+    ///             "__cWid": 2, "__cHei": 2, "__gridSize": 16,
+    ///             "intGridCsv": [1, 0, 0, 2]
+    ///         }]
+    ///     }]
+    /// }"#;
+    ///
+    /// let project = LdtkFile::parse(ldtk).unwrap().into_project(&Default::default()).unwrap();
+    /// let int_grid = project.levels[0].int_grids.get("Collisions").unwrap();
+    ///
+    /// // The top left and bottom right cells.
+    /// assert_eq!(int_grid.get((-1, 0)), Some(1));
+    /// assert_eq!(int_grid.get((0, -1)), Some(2));
+    /// assert_eq!(int_grid.get((0, 0)), None);
+    /// assert_eq!(int_grid.get((5, 5)), None);
+    /// ```
+    pub fn get<P: Into<Point2>>(&self, point: P) -> Option<i32> {
+        let point: Point2 = point.into();
+        let column = point.x + (self.width / 2) as i32;
+        let row = (self.height / 2) as i32 - 1 - point.y;
+        if column < 0 || row < 0 || column >= self.width as i32 || row >= self.height as i32 {
+            return None;
+        }
+        let index = row as usize * self.width as usize + column as usize;
+        self.values.get(index).copied().filter(|value| *value != 0)
+    }
+
+    /// Returns the identifier of a value, if it has one.
+    pub fn identifier(&self, value: i32) -> Option<&str> {
+        self.identifiers.get(&value).map(String::as_str)
+    }
+
+    /// Returns an iterator over all the points which are not empty, with
+    /// their value.
+    pub fn iter(&self) -> impl Iterator<Item = (Point2, i32)> + '_ {
+        let width = self.width.max(1) as usize;
+        let (origin_x, origin_y) = ((self.width / 2) as i32, (self.height / 2) as i32);
+        self.values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != 0)
+            .map(move |(index, value)| {
+                let x = (index % width) as i32 - origin_x;
+                let y = origin_y - 1 - (index / width) as i32;
+                (Point2::new(x, y), *value)
+            })
+    }
+}
+
+/// An entity instance of a level, describing what to spawn.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LdtkEntity {
+    /// The identifier of the entity definition.
+    pub identifier: String,
+    /// The tile the entity is in.
+    pub point: Point2,
+    /// The width and height of the entity in pixels.
+    pub size: Vec2,
+    /// The pivot of the entity, from 0.0 to 1.0 from the top left.
+    pub pivot: Vec2,
+    /// The values of all the fields of the entity by their identifier.
+    pub fields: HashMap<String, Value>,
+}
+
+impl LdtkEntity {
+    /// Returns the world translation of the center of the tile the entity is
+    /// in.
+    pub fn translation(&self, tilemap: &Tilemap, transform: &Transform) -> Vec3 {
+        tilemap.tile_to_world(transform, self.point)
+    }
+}
+
+/// A level which was loaded into a tilemap.
+#[derive(Debug)]
+pub struct LdtkLevel {
+    /// The identifier of the level.
+    pub identifier: String,
+    /// The position of the top left of the level in the world, in LDtk
+    /// pixels.
+    pub world_position: Vec2,
+    /// The tilemap with all the tiles of the level.
+    pub tilemap: Tilemap,
+    /// The values of the IntGrid layers by their identifier.
+    pub int_grids: HashMap<String, IntGrid>,
+    /// All the entity instances of the level.
+    pub entities: Vec<LdtkEntity>,
+    /// What could not be carried over from the level.
+    pub report: LdtkReport,
+}
+
+/// An LDtk project with all of its levels.
+#[derive(Debug)]
+pub struct LdtkProject {
+    /// All the levels of the project, in order.
+    pub levels: Vec<LdtkLevel>,
+}
+
+impl TypeUuid for LdtkProject {
+    const TYPE_UUID: Uuid = Uuid::from_u128(215760133519718309584617744358337920351);
+}
+
+impl LdtkProject {
+    /// Returns a level by its identifier.
+    pub fn level(&self, identifier: &str) -> Option<&LdtkLevel> {
+        self.levels
+            .iter()
+            .find(|level| level.identifier == identifier)
+    }
+
+    /// Removes a level by its identifier and returns it.
+    pub fn remove_level(&mut self, identifier: &str) -> Option<LdtkLevel> {
+        let index = self
+            .levels
+            .iter()
+            .position(|level| level.identifier == identifier)?;
+        Some(self.levels.remove(index))
+    }
+}
+
+/// The asset loader for LDtk `.ldtk` files.
+#[derive(Default)]
+pub struct LdtkLoader;
+
+impl AssetLoader for LdtkLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let directory = load_context
+                .path()
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            let mut file = LdtkFile::parse(std::str::from_utf8(bytes)?)?;
+            for path in file.external_levels() {
+                let level = load_context.read_asset_bytes(directory.join(&path)).await?;
+                file.resolve_level(&path, std::str::from_utf8(&level)?)?;
+            }
+
+            let mut texture_atlases = HashMap::default();
+            for (uid, path) in file.tileset_images() {
+                let image_path = AssetPath::new(directory.join(path), None);
+                let texture = load_context.get_handle(image_path.clone());
+                let texture_atlas = load_context.set_labeled_asset(
+                    &format!("tileset_{}", uid),
+                    LoadedAsset::new(file.texture_atlas(uid, texture)?).with_dependency(image_path),
+                );
+                texture_atlases.insert(uid, texture_atlas);
+            }
+            let project = file.into_project(&texture_atlases)?;
+            load_context.set_default_asset(LoadedAsset::new(project));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+/// The root of an LDtk project file.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct RawProject {
+    /// The definitions of the project.
+    defs: RawDefinitions,
+    /// All the levels of the project.
+    levels: Vec<RawLevel>,
+}
+
+/// The definitions of a project.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct RawDefinitions {
+    /// All the tilesets.
+    #[serde(default)]
+    tilesets: Vec<RawTileset>,
+    /// All the layers.
+    #[serde(default)]
+    layers: Vec<RawLayerDefinition>,
+}
+
+/// The definition of a tileset.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTileset {
+    /// The unique ID of the tileset.
+    uid: i64,
+    /// The identifier of the tileset.
+    identifier: String,
+    /// The path to the image, relative to the project.
+    rel_path: Option<String>,
+    /// The size of a tile in pixels.
+    tile_grid_size: u32,
+    /// The spacing between tiles in pixels.
+    #[serde(default)]
+    spacing: u32,
+    /// The padding around the tiles in pixels.
+    #[serde(default)]
+    padding: u32,
+    /// The amount of columns of tiles.
+    #[serde(rename = "__cWid")]
+    columns: u32,
+    /// The amount of rows of tiles.
+    #[serde(rename = "__cHei")]
+    rows: u32,
+}
+
+/// The definition of a layer.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawLayerDefinition {
+    /// The unique ID of the layer.
+    uid: i64,
+    /// The values of an IntGrid layer.
+    #[serde(default)]
+    int_grid_values: Vec<RawIntGridValue>,
+}
+
+/// The definition of an IntGrid value.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct RawIntGridValue {
+    /// The value.
+    value: i32,
+    /// The identifier of the value.
+    identifier: Option<String>,
+}
+
+/// A level.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawLevel {
+    /// The identifier of the level.
+    identifier: String,
+    /// The X position of the level in the world in pixels.
+    #[serde(default)]
+    world_x: i32,
+    /// The Y position of the level in the world in pixels.
+    #[serde(default)]
+    world_y: i32,
+    /// The layers of the level from the top down, if it is not external.
+    layer_instances: Option<Vec<RawLayer>>,
+    /// The path to the level if it is saved in a separate file.
+    external_rel_path: Option<String>,
+}
+
+/// A layer of a level.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawLayer {
+    /// The identifier of the layer.
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    /// The type of the layer.
+    #[serde(rename = "__type")]
+    kind: String,
+    /// The width of the layer in cells.
+    #[serde(rename = "__cWid")]
+    width: u32,
+    /// The height of the layer in cells.
+    #[serde(rename = "__cHei")]
+    height: u32,
+    /// The size of a cell in pixels.
+    #[serde(rename = "__gridSize")]
+    grid_size: u32,
+    /// The opacity of the layer.
+    #[serde(rename = "__opacity", default = "default_opacity")]
+    opacity: f32,
+    /// The X offset of the layer in pixels.
+    #[serde(rename = "__pxTotalOffsetX", default)]
+    offset_x: i32,
+    /// The Y offset of the layer in pixels.
+    #[serde(rename = "__pxTotalOffsetY", default)]
+    offset_y: i32,
+    /// The tileset of the layer.
+    #[serde(rename = "__tilesetDefUid")]
+    tileset_uid: Option<i64>,
+    /// The tileset which overrides the tileset of the layer.
+    override_tileset_uid: Option<i64>,
+    /// The unique ID of the layer definition.
+    #[serde(default)]
+    layer_def_uid: i64,
+    /// If the layer is visible.
+    #[serde(default = "default_visible")]
+    visible: bool,
+    /// The values of an IntGrid layer, from the top left row by row.
+    #[serde(default)]
+    int_grid_csv: Vec<i32>,
+    /// The tiles of an auto layer, in the order they are drawn.
+    #[serde(default)]
+    auto_layer_tiles: Vec<RawTileInstance>,
+    /// The tiles of a tile layer, in the order they are drawn.
+    #[serde(default)]
+    grid_tiles: Vec<RawTileInstance>,
+    /// The entity instances of an entity layer.
+    #[serde(default)]
+    entity_instances: Vec<RawEntity>,
+}
+
+/// A tile of a layer.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct RawTileInstance {
+    /// The position of the tile in the layer in pixels.
+    px: [i32; 2],
+    /// The flip flags of the tile.
+    #[serde(default)]
+    f: u8,
+    /// The ID of the tile in the tileset.
+    t: u32,
+}
+
+/// An entity instance of a layer.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawEntity {
+    /// The identifier of the entity definition.
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    /// The cell of the entity.
+    #[serde(rename = "__grid")]
+    grid: [i32; 2],
+    /// The pivot of the entity.
+    #[serde(rename = "__pivot", default)]
+    pivot: [f32; 2],
+    /// The width of the entity in pixels.
+    #[serde(default)]
+    width: u32,
+    /// The height of the entity in pixels.
+    #[serde(default)]
+    height: u32,
+    /// The fields of the entity.
+    #[serde(default)]
+    field_instances: Vec<RawField>,
+}
+
+/// A field of an entity instance.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct RawField {
+    /// The identifier of the field.
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    /// The value of the field.
+    #[serde(rename = "__value")]
+    value: Value,
+}
+
+/// The default opacity of a layer.
+fn default_opacity() -> f32 {
+    1.0
+}
+
+/// The default visibility of a layer.
+fn default_visible() -> bool {
+    true
+}
+
+/// A parsed LDtk project, which can be made into an [`LdtkProject`].
+///
+/// This is what the asset loader uses internally, which is useful if the
+/// project is not loaded through the asset server.
+///
+/// # Examples
+/// ```
+/// use bevy::asset::{prelude::*, HandleId};
+/// use bevy::sprite::prelude::*;
+/// use bevy::utils::HashMap;
+/// use bevy_tilemap::{ldtk::LdtkFile, prelude::*};
+///
+/// let ldtk = r#"{
+///     "defs": {
+///         "tilesets": [{
+///             "uid": 1, "identifier": "Terrain", "relPath": "terrain.png",
+///             "tileGridSize": 16, "__cWid": 4, "__cHei": 4
+///         }],
+///         "layers": []
+///     },
+///     "levels": [{
+///         "identifier": "Level_0",
+///         "worldX": 0, "worldY": 0, "pxWid": 32, "pxHei": 32,
+///         "layerInstances": [{
+///             "__identifier": "Ground", "__type": "Tiles",
+///             "__cWid": 2, "__cHei": 2, "__gridSize": 16, "__tilesetDefUid": 1,
+///             "gridTiles": [{ "px": [0, 0], "src": [32, 0], "f": 0, "t": 2 }]
+///         }]
+///     }]
+/// }"#;
+///
+/// let file = LdtkFile::parse(ldtk).unwrap();
+/// let (uid, path) = file.tileset_images().pop().unwrap();
+/// assert_eq!(path.to_str(), Some("terrain.png"));
+///
+/// // In production use a strong handle from an actual source.
+/// let mut texture_atlases = HashMap::default();
+/// texture_atlases.insert(uid, Handle::weak(HandleId::random::<TextureAtlas>()));
+///
+/// let mut project = file.into_project(&texture_atlases).unwrap();
+/// let level = project.remove_level("Level_0").unwrap();
+/// assert!(level.report.is_empty());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct LdtkFile {
+    /// The raw project.
+    project: RawProject,
+}
+
+impl LdtkFile {
+    /// Parses a `.ldtk` project.
+    ///
+    /// Levels saved in separate files are not read, they must be resolved
+    /// with [`resolve_level`] before the project can be used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is not a valid LDtk project.
+    ///
+    /// [`resolve_level`]: LdtkFile::resolve_level
+    pub fn parse(json: &str) -> LdtkResult<LdtkFile> {
+        Ok(LdtkFile {
+            project: serde_json::from_str(json)?,
+        })
+    }
+
+    /// Returns the paths of all the levels saved in separate files which
+    /// still need to be resolved.
+    pub fn external_levels(&self) -> Vec<String> {
+        self.project
+            .levels
+            .iter()
+            .filter(|level| level.layer_instances.is_none())
+            .filter_map(|level| level.external_rel_path.clone())
+            .collect()
+    }
+
+    /// Resolves a level saved in a separate file with the contents of its
+    /// `.ldtkl` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON is not a valid LDtk level.
+    pub fn resolve_level(&mut self, path: &str, json: &str) -> LdtkResult<()> {
+        let resolved: RawLevel = serde_json::from_str(json)?;
+        for level in self.project.levels.iter_mut() {
+            if level.layer_instances.is_none() && level.external_rel_path.as_deref() == Some(path) {
+                level.layer_instances = resolved.layer_instances.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the unique ID and the image path, relative to the project, of
+    /// every tileset used by a level.
+    pub fn tileset_images(&self) -> Vec<(i64, PathBuf)> {
+        let mut uids = self
+            .project
+            .levels
+            .iter()
+            .filter_map(|level| level.layer_instances.as_ref())
+            .flatten()
+            .filter_map(RawLayer::tileset)
+            .collect::<Vec<i64>>();
+        uids.sort_unstable();
+        uids.dedup();
+
+        self.project
+            .defs
+            .tilesets
+            .iter()
+            .filter(|tileset| uids.contains(&tileset.uid))
+            .filter_map(|tileset| {
+                let path = tileset.rel_path.as_ref()?;
+                Some((tileset.uid, PathBuf::from(path)))
+            })
+            .collect()
+    }
+
+    /// Constructs the texture atlas of a tileset from its image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tileset is not defined or has no image.
+    pub fn texture_atlas(&self, uid: i64, texture: Handle<Texture>) -> LdtkResult<TextureAtlas> {
+        let tileset = self.tileset(uid)?;
+        let stride = (tileset.tile_grid_size + tileset.spacing) as f32;
+        let padding = tileset.padding as f32;
+        let size = Vec2::new(
+            padding * 2.0 + stride * tileset.columns as f32 - tileset.spacing as f32,
+            padding * 2.0 + stride * tileset.rows as f32 - tileset.spacing as f32,
+        );
+
+        let mut texture_atlas = TextureAtlas::new_empty(texture, size);
+        for row in 0..tileset.rows {
+            for column in 0..tileset.columns {
+                let min = Vec2::new(
+                    padding + stride * column as f32,
+                    padding + stride * row as f32,
+                );
+                texture_atlas.add_texture(Rect {
+                    min,
+                    max: min + Vec2::splat(tileset.tile_grid_size as f32),
+                });
+            }
+        }
+
+        Ok(texture_atlas)
+    }
+
+    /// Returns a tileset by its unique ID.
+    fn tileset(&self, uid: i64) -> LdtkResult<&RawTileset> {
+        let tileset = self
+            .project
+            .defs
+            .tilesets
+            .iter()
+            .find(|tileset| tileset.uid == uid)
+            .ok_or(ErrorKind::MissingTileset(uid))?;
+        if tileset.rel_path.is_none() {
+            return Err(ErrorKind::MissingTilesetImage(tileset.identifier.clone()).into());
+        }
+        Ok(tileset)
+    }
+
+    /// Consumes the file and constructs an [`LdtkProject`] with the texture
+    /// atlases of the tilesets by their unique ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an external level was not resolved, if a tileset
+    /// is not defined or has no texture atlas, or if a tilemap could not be
+    /// constructed.
+    pub fn into_project(
+        self,
+        texture_atlases: &HashMap<i64, Handle<TextureAtlas>>,
+    ) -> LdtkResult<LdtkProject> {
+        let mut levels = Vec::with_capacity(self.project.levels.len());
+        for level in self.project.levels.iter() {
+            levels.push(self.level(level, texture_atlases)?);
+        }
+
+        Ok(LdtkProject { levels })
+    }
+
+    /// Constructs a level with its tilemap.
+    fn level(
+        &self,
+        level: &RawLevel,
+        texture_atlases: &HashMap<i64, Handle<TextureAtlas>>,
+    ) -> LdtkResult<LdtkLevel> {
+        let layers = level.layer_instances.as_ref().ok_or_else(|| {
+            let path = level.external_rel_path.clone().unwrap_or_default();
+            ErrorKind::UnresolvedLevel(path)
+        })?;
+        let mut report = LdtkReport::default();
+
+        // The tileset of the bottom most layer with tiles is used for the
+        // whole level.
+        let tileset = layers
+            .iter()
+            .rev()
+            .filter(|layer| layer.visible && !layer.tiles().is_empty())
+            .find_map(RawLayer::tileset)
+            .map(|uid| -> LdtkResult<(&RawTileset, Handle<TextureAtlas>)> {
+                let tileset = self.tileset(uid)?;
+                let texture_atlas = texture_atlases
+                    .get(&uid)
+                    .cloned()
+                    .ok_or_else(|| ErrorKind::MissingTilesetImage(tileset.identifier.clone()))?;
+                Ok((tileset, texture_atlas))
+            })
+            .transpose()?;
+
+        let mut int_grids = HashMap::default();
+        let mut entities = Vec::new();
+        let mut tiles = Vec::new();
+        let mut sprite_layers = Vec::new();
+        for layer in layers.iter().rev() {
+            if !layer.visible {
+                report
+                    .unsupported
+                    .push(Unsupported::HiddenLayer(layer.identifier.clone()));
+                continue;
+            }
+            let origin_x = (layer.width / 2) as i32;
+            let origin_y = (layer.height / 2) as i32;
+
+            if !layer.int_grid_csv.is_empty() {
+                let identifiers = self
+                    .project
+                    .defs
+                    .layers
+                    .iter()
+                    .find(|definition| definition.uid == layer.layer_def_uid)
+                    .map(|definition| {
+                        definition
+                            .int_grid_values
+                            .iter()
+                            .filter_map(|value| Some((value.value, value.identifier.clone()?)))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                int_grids.insert(
+                    layer.identifier.clone(),
+                    IntGrid {
+                        width: layer.width,
+                        height: layer.height,
+                        values: layer.int_grid_csv.clone(),
+                        identifiers,
+                    },
+                );
+            }
+
+            for entity in layer.entity_instances.iter() {
+                let [column, row] = entity.grid;
+                let [pivot_x, pivot_y] = entity.pivot;
+                entities.push(LdtkEntity {
+                    identifier: entity.identifier.clone(),
+                    point: Point2::new(column - origin_x, origin_y - 1 - row),
+                    size: Vec2::new(entity.width as f32, entity.height as f32),
+                    pivot: Vec2::new(pivot_x, pivot_y),
+                    fields: entity
+                        .field_instances
+                        .iter()
+                        .map(|field| (field.identifier.clone(), field.value.clone()))
+                        .collect(),
+                });
+            }
+
+            let layer_tiles = layer.tiles();
+            let (tileset, _) = match &tileset {
+                Some(tileset) if !layer_tiles.is_empty() => tileset,
+                _ => continue,
+            };
+            if layer.tileset() != Some(tileset.uid) {
+                report.unsupported.push(Unsupported::Tileset {
+                    layer: layer.identifier.clone(),
+                    skipped: layer_tiles.len(),
+                });
+                continue;
+            }
+            if layer.grid_size != tileset.tile_grid_size {
+                report.unsupported.push(Unsupported::GridSize {
+                    layer: layer.identifier.clone(),
+                    size: layer.grid_size,
+                });
+                continue;
+            }
+            if layer.offset_x != 0 || layer.offset_y != 0 {
+                report
+                    .unsupported
+                    .push(Unsupported::LayerOffset(layer.identifier.clone()));
+            }
+
+            let mut tint = Color::WHITE;
+            tint.set_a(layer.opacity);
+            let grid_size = layer.grid_size.max(1) as i32;
+            let base = sprite_layers.len();
+            let mut stacks: HashMap<Point2, usize> = HashMap::default();
+            for tile in layer_tiles.iter() {
+                let mut flip = TileFlip::NONE;
+                flip.set(TileFlip::HORIZONTAL, tile.f & FLIPPED_X != 0);
+                flip.set(TileFlip::VERTICAL, tile.f & FLIPPED_Y != 0);
+                let [x, y] = tile.px;
+                let point = Point2::new(
+                    x.div_euclid(grid_size) - origin_x,
+                    origin_y - 1 - y.div_euclid(grid_size),
+                );
+                // Tiles stacked in the same cell are spread over sprite
+                // layers, in the order they are drawn.
+                let stack = stacks.entry(point).or_insert(0);
+                let sprite_order = base + *stack;
+                *stack += 1;
+                if sprite_order >= sprite_layers.len() {
+                    sprite_layers.push(0);
+                }
+                if let Some(count) = sprite_layers.get_mut(sprite_order) {
+                    *count += 1;
+                }
+                tiles.push(Tile {
+                    point: Point3::new(point.x, point.y, 0),
+                    sprite_order,
+                    sprite_index: tile.t as usize,
+                    tint,
+                    flip,
+                    rotation: 0,
+                });
+            }
+        }
+
+        let (texture_dimensions, texture_atlas) = match tileset {
+            Some((tileset, texture_atlas)) => (tileset.tile_grid_size, texture_atlas),
+            None => (
+                layers.first().map_or(16, |layer| layer.grid_size),
+                Handle::default(),
+            ),
+        };
+        let area = layers
+            .first()
+            .map_or(0, |layer| (layer.width * layer.height) as usize);
+        let mut builder = TilemapBuilder::new()
+            .texture_atlas(texture_atlas)
+            .texture_dimensions(texture_dimensions, texture_dimensions)
+            .z_layers(sprite_layers.len().max(1))
+            .auto_chunk();
+        for (sprite_order, count) in sprite_layers.iter().enumerate() {
+            // A layer that fills at least half of the level is better off
+            // dense.
+            let kind = if count * 2 >= area {
+                LayerKind::Dense
+            } else {
+                LayerKind::Sparse
+            };
+            builder = builder.add_layer(TilemapLayer { kind }, sprite_order);
+        }
+        let mut tilemap = builder.finish()?;
+        tilemap.insert_tiles(tiles)?;
+
+        Ok(LdtkLevel {
+            identifier: level.identifier.clone(),
+            world_position: Vec2::new(level.world_x as f32, level.world_y as f32),
+            tilemap,
+            int_grids,
+            entities,
+            report,
+        })
+    }
+}
+
+impl RawLayer {
+    /// Returns the tileset of the layer, if it has one.
+    fn tileset(&self) -> Option<i64> {
+        self.override_tileset_uid.or(self.tileset_uid)
+    }
+
+    /// Returns the tiles of the layer, in the order they are drawn.
+    fn tiles(&self) -> &[RawTileInstance] {
+        if self.kind == "Tiles" {
+            &self.grid_tiles
+        } else {
+            &self.auto_layer_tiles
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::RawTile;
+
+    const PROJECT: &str = r#"{
+        "externalLevels": true,
+        "defs": {
+            "tilesets": [
+                {
+                    "uid": 1, "identifier": "Terrain", "relPath": "terrain.png",
+                    "tileGridSize": 16, "spacing": 1, "padding": 2, "__cWid": 4, "__cHei": 3
+                },
+                {
+                    "uid": 2, "identifier": "Props", "relPath": "props.png",
+                    "tileGridSize": 16, "__cWid": 2, "__cHei": 2
+                }
+            ],
+            "layers": [{
+                "uid": 10,
+                "intGridValues": [{ "value": 1, "identifier": "wall" }, { "value": 2 }]
+            }]
+        },
+        "levels": [{
+            "identifier": "Level_0", "worldX": 256, "worldY": -64,
+            "layerInstances": null, "externalRelPath": "world/Level_0.ldtkl"
+        }]
+    }"#;
+
+    const LEVEL: &str = r#"{
+        "identifier": "Level_0", "worldX": 256, "worldY": -64,
+        "layerInstances": [
+            {
+                "__identifier": "Entities", "__type": "Entities",
+                "__cWid": 4, "__cHei": 2, "__gridSize": 16,
+                "entityInstances": [{
+                    "__identifier": "Player", "__grid": [3, 0], "__pivot": [0.5, 1],
+                    "width": 16, "height": 24,
+                    "fieldInstances": [{ "__identifier": "health", "__type": "Int", "__value": 3 }]
+                }]
+            },
+            {
+                "__identifier": "Props", "__type": "Tiles",
+                "__cWid": 4, "__cHei": 2, "__gridSize": 16, "__tilesetDefUid": 2,
+                "gridTiles": [{ "px": [0, 0], "src": [0, 0], "f": 0, "t": 1 }]
+            },
+            {
+                "__identifier": "Walls", "__type": "IntGrid", "layerDefUid": 10,
+                "__cWid": 4, "__cHei": 2, "__gridSize": 16, "__tilesetDefUid": 1,
+                "__opacity": 0.5,
+                "intGridCsv": [1, 1, 0, 0, 0, 0, 2, 0],
+                "autoLayerTiles": [
+                    { "px": [0, 0], "src": [2, 2], "f": 0, "t": 0 },
+                    { "px": [16, 0], "src": [19, 2], "f": 1, "t": 1 },
+                    { "px": [0, 0], "src": [36, 2], "f": 0, "t": 2 },
+                    { "px": [32, 16], "src": [2, 19], "f": 0, "t": 4 }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn project() {
+        let mut file = LdtkFile::parse(PROJECT).unwrap();
+        assert_eq!(
+            file.external_levels(),
+            vec!["world/Level_0.ldtkl".to_string()]
+        );
+        assert!(file.tileset_images().is_empty());
+        file.resolve_level("world/Level_0.ldtkl", LEVEL).unwrap();
+        assert!(file.external_levels().is_empty());
+        assert_eq!(
+            file.tileset_images(),
+            vec![
+                (1, PathBuf::from("terrain.png")),
+                (2, PathBuf::from("props.png"))
+            ]
+        );
+
+        let texture_atlas = file
+            .texture_atlas(1, Handle::weak(HandleId::random::<Texture>()))
+            .unwrap();
+        assert_eq!(texture_atlas.len(), 12);
+        assert_eq!(texture_atlas.size, Vec2::new(71.0, 54.0));
+        let rect = texture_atlas.textures.get(5).unwrap();
+        assert_eq!(rect.min, Vec2::new(19.0, 19.0));
+        assert_eq!(rect.max, Vec2::new(35.0, 35.0));
+
+        let mut texture_atlases = HashMap::default();
+        texture_atlases.insert(1, Handle::weak(HandleId::random::<TextureAtlas>()));
+        texture_atlases.insert(2, Handle::weak(HandleId::random::<TextureAtlas>()));
+        let mut project = file.into_project(&texture_atlases).unwrap();
+        let mut level = project.remove_level("Level_0").unwrap();
+        assert!(project.levels.is_empty());
+        assert_eq!(level.world_position, Vec2::new(256.0, -64.0));
+
+        // The stacked tile is placed on the next sprite layer.
+        let color = Color::rgba(1.0, 1.0, 1.0, 0.5);
+        let tilemap = &mut level.tilemap;
+        let tile = RawTile {
+            color,
+            ..Default::default()
+        };
+        assert_eq!(
+            tilemap.get_tile((-2, 0), 0),
+            Some(&RawTile { index: 0, ..tile })
+        );
+        assert_eq!(
+            tilemap.get_tile((-2, 0), 1),
+            Some(&RawTile { index: 2, ..tile })
+        );
+        assert_eq!(
+            tilemap.get_tile((0, -1), 0),
+            Some(&RawTile { index: 4, ..tile })
+        );
+        assert_eq!(
+            tilemap.get_tile((-1, 0), 0),
+            Some(&RawTile {
+                index: 1,
+                flip: TileFlip::HORIZONTAL,
+                ..tile
+            })
+        );
+        assert_eq!(tilemap.get_tile((0, -1), 1), None);
+
+        let walls = level.int_grids.get("Walls").unwrap();
+        assert_eq!(walls.get((-1, 0)), Some(1));
+        assert_eq!(walls.get((0, -1)), Some(2));
+        assert_eq!(walls.identifier(1), Some("wall"));
+        assert_eq!(walls.identifier(2), None);
+        assert_eq!(walls.iter().count(), 3);
+
+        let player = level.entities.first().unwrap();
+        assert_eq!(player.identifier, "Player");
+        assert_eq!(player.point, Point2::new(1, 0));
+        assert_eq!(player.fields.get("health"), Some(&Value::from(3)));
+
+        assert_eq!(
+            level.report.unsupported,
+            vec![Unsupported::Tileset {
+                layer: "Props".to_string(),
+                skipped: 1,
+            }]
+        );
+    }
+}
@@ -82,10 +82,17 @@ pub extern crate bevy_tilemap_types;
 pub use bevy_tilemap_types::dimension;
 #[cfg(feature = "types")]
 #[doc(inline)]
+pub use bevy_tilemap_types::hex;
+#[cfg(feature = "types")]
+#[doc(inline)]
 pub use bevy_tilemap_types::point;
 
+pub mod animation;
+pub mod autotile;
 pub mod chunk;
 pub mod default_plugin;
+#[cfg(feature = "editor")]
+pub mod editor;
 pub mod entity;
 pub mod prelude;
 pub mod stage {
@@ -96,9 +103,23 @@ pub mod stage {
     // pub const TILEMAP_UPDATE: &str = "tilemap_update";
 }
 pub mod event;
+pub mod fog;
+pub mod fov;
+pub mod generator;
+pub mod history;
+#[cfg(feature = "ldtk")]
+pub mod ldtk;
+pub mod pathfinding;
+pub mod shape;
+pub mod stamp;
+pub mod storage;
 mod system;
 pub mod tile;
+#[cfg(feature = "tiled")]
+pub mod tiled;
 pub mod tilemap;
+#[cfg(feature = "wfc")]
+pub mod wfc;
 
 use crate::{event::TilemapChunkEvent, lib::*};
 pub use crate::{
@@ -122,6 +143,8 @@ pub enum TilemapSystem {
 impl Plugin for TilemapPlugin {
     fn build(&self, app: &mut App) {
         app.add_asset::<Tilemap>()
+            .add_asset::<crate::stamp::TileStamp>()
+            .init_asset_loader::<crate::stamp::TileStampLoader>()
             .add_stage_before(
                 CoreStage::PostUpdate,
                 stage::TILEMAP,
@@ -152,11 +175,30 @@ impl Plugin for TilemapPlugin {
                     .label(TilemapSystem::AutoSpawn)
                     .after(TilemapSystem::Events),
             )
+            .add_system_to_stage(
+                stage::TILEMAP,
+                crate::system::tilemap_animation
+                    .system()
+                    .after(TilemapSystem::Events),
+            )
             .add_system_to_stage(
                 stage::TILEMAP,
                 crate::system::tilemap_visibility_change.system(),
+            )
+            .add_system_to_stage(
+                stage::TILEMAP,
+                crate::fog::tilemap_fog
+                    .system()
+                    .after(TilemapSystem::Events),
             );
 
+        #[cfg(feature = "ldtk")]
+        app.add_asset::<crate::ldtk::LdtkProject>()
+            .init_asset_loader::<crate::ldtk::LdtkLoader>();
+        #[cfg(feature = "tiled")]
+        app.add_asset::<crate::tiled::TiledMap>()
+            .init_asset_loader::<crate::tiled::TiledLoader>();
+
         let world = app.world.cell();
         // let mut render_graph = world.get_resource_mut::<RenderGraph>().unwrap();
         let mut pipelines = world
@@ -181,7 +223,8 @@ mod lib {
     pub(crate) use bevy::transform::components::Children;
     pub(crate) use bevy::{
         app::{App, CoreStage, Events, Plugin, PluginGroup, PluginGroupBuilder},
-        asset::{AddAsset, Assets, Handle, HandleUntyped},
+        asset::{AddAsset, AssetLoader, Assets, Handle, HandleUntyped, LoadContext, LoadedAsset},
+        core::Time,
         ecs::{
             bundle::Bundle,
             component::Component,
@@ -209,34 +252,51 @@ mod lib {
             texture::TextureFormat,
         },
         sprite::TextureAtlas,
+        tasks::{AsyncComputeTaskPool, IoTaskPool, Task},
         transform::{
             components::{GlobalTransform, Parent, Transform},
             hierarchy::{BuildChildren, DespawnRecursiveExt},
         },
-        utils::{HashMap, HashSet},
+        utils::{BoxedFuture, HashMap, HashSet},
         window::WindowResized,
     };
+    #[cfg(any(feature = "ldtk", feature = "tiled"))]
+    pub(crate) use bevy::{asset::AssetPath, render::texture::Texture, sprite::Rect};
+    #[cfg(feature = "editor")]
+    pub(crate) use bevy::{
+        ecs::query::With,
+        input::{keyboard::KeyCode, mouse::MouseButton, Input},
+        render::camera::OrthographicProjection,
+        window::Windows,
+    };
     pub(crate) use bevy_tilemap_types::{
         dimension::{Dimension2, Dimension3, DimensionError},
+        hex::{AxialPoint, HexLayout},
         point::{Point2, Point3},
     };
+    pub(crate) use std::path::{Path, PathBuf};
 
     pub(crate) use bitflags::*;
 
-    pub(crate) use serde::{Deserialize, Serialize};
+    pub(crate) use serde::{
+        de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer,
+    };
 
     pub(crate) use std::{
         boxed::Box,
         clone::Clone,
-        cmp::Ord,
+        cmp::{Ord, Reverse},
+        collections::{BinaryHeap, VecDeque},
         convert::{AsMut, AsRef, From, Into},
         default::Default,
         error::Error,
         fmt::{Debug, Display, Formatter, Result as FmtResult},
         iter::{Extend, IntoIterator, Iterator},
+        mem,
         ops::FnMut,
         option::Option::{self, *},
         result::Result::{self, *},
+        sync::Arc,
         vec::Vec,
     };
 
@@ -0,0 +1,335 @@
+//! Pathfinding over the tiles of a tilemap.
+//!
+//! A search moves from tile to tile over the neighbors of the tilemap's
+//! topology, also across chunks. Only tiles in chunks that exist are searched.
+//! Entering a tile costs what a user given function returns for its point and
+//! raw tile, which is `None` if the tile can not be entered. A cost of 0 is
+//! counted as 1. The costs can also come from a data layer with
+//! [`Tilemap::get_data`].
+//!
+//! * [`Tilemap::find_path`] finds the cheapest path between two points with
+//! A*.
+//! * [`Tilemap::dijkstra_map`] finds the cheapest path from every point to the
+//! closest of many goals at once, which any amount of agents can then follow.
+//!
+//! # Walking around walls
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::{pathfinding::Connectivity, point::Point3, prelude::*};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! const FLOOR: usize = 0;
+//! const WALL: usize = 1;
+//!
+//! let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+//! tilemap.insert_chunk((0, 0)).unwrap();
+//!
+//! let mut tiles = Vec::new();
+//! for y in -2..=2 {
+//!     for x in -2..=2 {
+//!         let sprite_index = if x == 0 && y < 2 { WALL } else { FLOOR };
+//!         tiles.push(Tile { point: (x, y), sprite_index, ..Default::default() });
+//!     }
+//! }
+//! tilemap.insert_tiles(tiles).unwrap();
+//!
+//! // Only floor tiles can be walked on.
+//! let path = tilemap
+//!     .find_path((-1, 0), (1, 0), 0, Connectivity::Edges, |_, tile| match tile {
+//!         Some(tile) if tile.index == FLOOR => Some(1),
+//!         _ => None,
+//!     })
+//!     .unwrap();
+//!
+//! // Around the top of the wall.
+//! assert_eq!(path.cost, 6);
+//! assert_eq!(path.points[3], Point3::new(0, 2, 0));
+//! ```
+//!
+//! [`Tilemap::get_data`]: crate::Tilemap::get_data
+//! [`Tilemap::find_path`]: crate::Tilemap::find_path
+//! [`Tilemap::dijkstra_map`]: crate::Tilemap::dijkstra_map
+
+use crate::{chunk::RawTile, lib::*, prelude::GridTopology, Tilemap};
+
+/// Which tiles can be moved to from a tile.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Connectivity {
+    /// Only the tiles that share an edge, which are 4 on square topologies
+    /// and 6 on hex topologies.
+    Edges,
+    /// Also the diagonal tiles of square topologies, which are 8 in total.
+    ///
+    /// A diagonal move costs the same as any other and is only possible if
+    /// both tiles next to it can be entered, so corners are never cut. Hex
+    /// topologies have the same 6 neighbors as with [`Edges`].
+    ///
+    /// [`Edges`]: Connectivity::Edges
+    Diagonals,
+}
+
+impl Default for Connectivity {
+    fn default() -> Connectivity {
+        Connectivity::Edges
+    }
+}
+
+/// A path between two points.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct Path {
+    /// The points of the path in order, including the start and the goal.
+    pub points: Vec<Point3>,
+    /// The sum of the costs of every tile entered along the path.
+    pub cost: u32,
+}
+
+/// The cheapest paths from every point to the closest of many goals.
+///
+/// Every point that can reach a goal has the cost of its cheapest path and
+/// the next step on it, so that many agents can head to the goals without a
+/// search of their own.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct DijkstraMap {
+    /// The cost of the cheapest path to a goal from each point.
+    costs: HashMap<Point3, u32>,
+    /// The next step towards a goal from each point that is not a goal.
+    steps: HashMap<Point3, Point3>,
+}
+
+impl DijkstraMap {
+    /// Returns the cost of the cheapest path from a point to a goal, or
+    /// `None` if no goal can be reached from it.
+    pub fn cost<P: Into<Point3>>(&self, point: P) -> Option<u32> {
+        self.costs.get(&point.into()).copied()
+    }
+
+    /// Returns the next point on the cheapest path from a point to a goal, or
+    /// `None` if the point is a goal or no goal can be reached from it.
+    pub fn next_step<P: Into<Point3>>(&self, point: P) -> Option<Point3> {
+        self.steps.get(&point.into()).copied()
+    }
+
+    /// Returns the cheapest path from a point to a goal, or `None` if no goal
+    /// can be reached from it.
+    pub fn path<P: Into<Point3>>(&self, point: P) -> Option<Path> {
+        let mut point = point.into();
+        let cost = self.cost(point)?;
+        let mut points = vec![point];
+        while let Some(next) = self.next_step(point) {
+            points.push(next);
+            point = next;
+        }
+        Some(Path { points, cost })
+    }
+
+    /// Returns an iterator of all points that can reach a goal with the cost
+    /// of their cheapest path.
+    pub fn iter(&self) -> impl Iterator<Item = (&Point3, &u32)> {
+        self.costs.iter()
+    }
+
+    /// Returns the amount of points that can reach a goal.
+    pub fn len(&self) -> usize {
+        self.costs.len()
+    }
+
+    /// Returns `true` if no point can reach a goal.
+    pub fn is_empty(&self) -> bool {
+        self.costs.is_empty()
+    }
+}
+
+/// The costs of entering the tiles of a tilemap, asked once per point.
+pub(crate) struct TileCosts<'a, F> {
+    /// The tilemap that is searched.
+    tilemap: &'a Tilemap,
+    /// The sprite order of the tiles that are searched.
+    sprite_order: usize,
+    /// Which tiles can be moved to from a tile.
+    connectivity: Connectivity,
+    /// The user given cost of entering a tile.
+    cost: F,
+    /// The costs that were already asked for.
+    known: HashMap<Point3, Option<u32>>,
+}
+
+impl<'a, F> TileCosts<'a, F>
+where
+    F: FnMut(Point3, Option<&RawTile>) -> Option<u32>,
+{
+    /// Constructs new tile costs for a sprite order of a tilemap.
+    pub(crate) fn new(
+        tilemap: &'a Tilemap,
+        sprite_order: usize,
+        connectivity: Connectivity,
+        cost: F,
+    ) -> TileCosts<'a, F> {
+        TileCosts {
+            tilemap,
+            sprite_order,
+            connectivity,
+            cost,
+            known: HashMap::default(),
+        }
+    }
+
+    /// Returns the cost of entering a tile, or `None` if it can not be entered
+    /// or its chunk does not exist.
+    pub(crate) fn cost(&mut self, point: Point3) -> Option<u32> {
+        if let Some(cost) = self.known.get(&point) {
+            return *cost;
+        }
+        let chunk_point: Point2 = self.tilemap.point_to_chunk_point(point).into();
+        let cost = if self.tilemap.chunks().contains_key(&chunk_point) {
+            let tile = self.tilemap.raw_tile(point, self.sprite_order);
+            (self.cost)(point, tile).map(|cost| cost.max(1))
+        } else {
+            None
+        };
+        self.known.insert(point, cost);
+        cost
+    }
+
+    /// Returns the tiles that can be moved to from a point with the cost of
+    /// entering them.
+    pub(crate) fn moves(&mut self, point: Point3) -> Vec<(Point3, u32)> {
+        let neighbors = self.tilemap.neighbor_points(point);
+        let square = self.tilemap.topology() == GridTopology::Square;
+        let costs = neighbors
+            .iter()
+            .map(|neighbor| self.cost(*neighbor))
+            .collect::<Vec<Option<u32>>>();
+        let passable = |direction: usize| {
+            costs
+                .get(direction % costs.len().max(1))
+                .map_or(false, Option::is_some)
+        };
+
+        let mut moves = Vec::with_capacity(neighbors.len());
+        for (direction, (neighbor, cost)) in neighbors.iter().zip(costs.iter()).enumerate() {
+            let cost = match cost {
+                Some(cost) => *cost,
+                None => continue,
+            };
+            // The diagonals of a square are every other neighbor.
+            if square && direction % 2 == 1 {
+                let corners = passable(direction + 7) && passable(direction + 1);
+                if self.connectivity == Connectivity::Edges || !corners {
+                    continue;
+                }
+            }
+            moves.push((*neighbor, cost));
+        }
+        moves
+    }
+}
+
+/// Returns the least amount of steps between two points.
+pub(crate) fn distance(
+    topology: GridTopology,
+    connectivity: Connectivity,
+    from: Point3,
+    to: Point3,
+) -> u32 {
+    let x = (from.x - to.x).unsigned_abs();
+    let y = (from.y - to.y).unsigned_abs();
+    match (topology.hex_layout(), connectivity) {
+        (Some(layout), _) => {
+            let from = AxialPoint::from_offset(from.into(), layout);
+            from.distance(AxialPoint::from_offset(to.into(), layout))
+        }
+        (None, Connectivity::Edges) => x + y,
+        (None, Connectivity::Diagonals) => x.max(y),
+    }
+}
+
+/// Finds the cheapest path from a start to a goal with A*.
+///
+/// The moves return the points that can be moved to from a point with the
+/// cost of entering them. The heuristic must never return more than the
+/// cheapest cost from a point to the goal.
+pub(crate) fn a_star<M, H>(start: Point3, goal: Point3, mut moves: M, heuristic: H) -> Option<Path>
+where
+    M: FnMut(Point3) -> Vec<(Point3, u32)>,
+    H: Fn(Point3) -> u32,
+{
+    let mut open = BinaryHeap::new();
+    let mut costs: HashMap<Point3, u32> = HashMap::default();
+    let mut came_from: HashMap<Point3, Point3> = HashMap::default();
+    open.push(Reverse((heuristic(start), 0, start)));
+    costs.insert(start, 0);
+
+    while let Some(Reverse((_, cost, point))) = open.pop() {
+        if point == goal {
+            let mut points = vec![goal];
+            let mut current = goal;
+            while let Some(previous) = came_from.get(&current) {
+                points.push(*previous);
+                current = *previous;
+            }
+            points.reverse();
+            return Some(Path { points, cost });
+        }
+        // Skip entries that were replaced by a cheaper path.
+        if costs.get(&point).map_or(false, |known| *known < cost) {
+            continue;
+        }
+        for (next, step) in moves(point) {
+            let next_cost = cost.saturating_add(step);
+            if costs.get(&next).map_or(true, |known| next_cost < *known) {
+                costs.insert(next, next_cost);
+                came_from.insert(next, point);
+                open.push(Reverse((
+                    next_cost.saturating_add(heuristic(next)),
+                    next_cost,
+                    next,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds the cheapest paths from every point to the closest goal.
+///
+/// The moves are searched backwards from the goals, they return the points
+/// that can move to a point with the cost of that move. Points that are more
+/// expensive than the max cost are not searched.
+pub(crate) fn dijkstra<M>(goals: Vec<Point3>, mut moves: M, max_cost: Option<u32>) -> DijkstraMap
+where
+    M: FnMut(Point3) -> Vec<(Point3, u32)>,
+{
+    let mut map = DijkstraMap::default();
+    let mut open = BinaryHeap::new();
+    for goal in goals {
+        map.costs.insert(goal, 0);
+        open.push(Reverse((0, goal)));
+    }
+
+    while let Some(Reverse((cost, point))) = open.pop() {
+        if map.costs.get(&point).map_or(false, |known| *known < cost) {
+            continue;
+        }
+        for (previous, step) in moves(point) {
+            let previous_cost = cost.saturating_add(step);
+            if max_cost.map_or(false, |max_cost| previous_cost > max_cost) {
+                continue;
+            }
+            if map
+                .costs
+                .get(&previous)
+                .map_or(true, |known| previous_cost < *known)
+            {
+                map.costs.insert(previous, previous_cost);
+                map.steps.insert(previous, point);
+                open.push(Reverse((previous_cost, previous)));
+            }
+        }
+    }
+
+    map
+}
@@ -5,20 +5,26 @@
 //! The current version of this prelude (version 0) is located in
 //! [`bevy_tilemap::prelude::v0`], and re-exports the following.
 //!
+//! * [`bevy_tilemap::animation`]::{[`AnimationFrame`], [`AnimationMode`],
+//! [`TileAnimation`]}, the frames and timing of an animated tile.
+//! * [`bevy_tilemap::autotile`]::{[`AutotileKind`], [`AutotileRules`]}, the
+//! rules that choose the sprites of a painted terrain.
 //! * [`bevy_tilemap::chunk`]::[`LayerKind`], the only public part
 //! of `chunk` module is the kind of layer you need to specify to create.
 //! * [`bevy_tilemap::default_plugin`]::[`TilemapDefaultPlugins`], the
 //! default plugins for the library.
 //! * [`bevy_tilemap::entity`]::[`TilemapBundle`], the component bundle
 //! for spawning with a Tilemap.
-//! * [`bevy_tilemap::tile`]::[`Tile`], a sprite tile which
-//! holds minimal amount of data for small data sizes.
+//! * [`bevy_tilemap::tile`]::{[`Tile`], [`TileFlip`]}, a sprite tile which
+//! holds minimal amount of data for small data sizes and how it is flipped.
 //! * [`bevy_tilemap::tilemap`]::{[`Tilemap`], [`TilemapBuilder`]},
 //! the core object that is used for virtually everything in this library.
 //! * [`bevy_tilemap`]::[`TilemapPlugin`], the main plugin with
 //! a collection of systems, components and assets to be used in a Bevy app.
 //!
 //! [`bevy_tilemap::prelude::v0`]: crate::prelude::v0
+//! [`bevy_tilemap::animation`]: crate::animation
+//! [`bevy_tilemap::autotile`]: crate::autotile
 //! [`bevy_tilemap::default_plugin`]: crate::default_plugin
 //! [`bevy_tilemap::chunk`]: crate::chunk
 //! [`bevy_tilemap::entity`]: crate::entity
@@ -29,10 +35,12 @@
 /// Version 0 prelude.
 pub mod v0 {
     pub use crate::{
+        animation::{AnimationFrame, AnimationMode, TileAnimation},
+        autotile::{AutotileKind, AutotileRules},
         chunk::{render::GridTopology, LayerKind},
         default_plugin::TilemapDefaultPlugins,
         entity::TilemapBundle,
-        tile::Tile,
+        tile::{Tile, TileFlip},
         tilemap::{Tilemap, TilemapBuilder, TilemapLayer},
         TilemapPlugin,
     };
@@ -0,0 +1,184 @@
+//! Shapes of tiles that can be drawn on a tilemap.
+//!
+//! A shape is rasterized into the points of its tiles with
+//! [`Tilemap::shape_points`] and drawn with [`Tilemap::draw_shape`], which
+//! inserts all of its tiles at once. Lines, circles and polygons follow the
+//! topology of the tilemap, on hex topologies a circle is a hex ring or a
+//! filled hexagon.
+//!
+//! [`Tilemap::shape_points`]: crate::Tilemap::shape_points
+//! [`Tilemap::draw_shape`]: crate::Tilemap::draw_shape
+
+use crate::{lib::*, Tilemap};
+
+/// A shape of tiles at the first z depth of a tilemap.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Shape {
+    /// The tiles between two opposite corners, including both.
+    Rectangle {
+        /// One corner of the rectangle.
+        corner: Point2,
+        /// The corner opposite of the first one.
+        opposite: Point2,
+        /// If the inside is drawn or only the border.
+        filled: bool,
+    },
+    /// The tiles on a straight line between two points, including both.
+    Line {
+        /// The start of the line.
+        from: Point2,
+        /// The end of the line.
+        to: Point2,
+    },
+    /// The tiles within a radius of a center.
+    ///
+    /// Hex topologies count the radius in steps between hexes, so the outline
+    /// is a hex ring.
+    Circle {
+        /// The tile in the center of the circle.
+        center: Point2,
+        /// The distance from the center to the edge in tiles.
+        radius: u32,
+        /// If the inside is drawn or only the edge.
+        filled: bool,
+    },
+    /// The tiles of a closed polygon through the centers of its vertices.
+    Polygon {
+        /// The corners of the polygon in order, the last connects back to the
+        /// first.
+        vertices: Vec<Point2>,
+        /// If the inside is drawn or only the edges.
+        filled: bool,
+    },
+}
+
+impl Shape {
+    /// Returns the points of the tiles of the shape on a tilemap, each once.
+    pub(crate) fn points(&self, tilemap: &Tilemap) -> Vec<Point3> {
+        let points = match self {
+            Shape::Rectangle {
+                corner,
+                opposite,
+                filled,
+            } => rectangle(*corner, *opposite, *filled),
+            Shape::Line { from, to } => tilemap.line(*from, *to),
+            Shape::Circle {
+                center,
+                radius,
+                filled,
+            } => match tilemap.topology().hex_layout() {
+                Some(layout) => {
+                    let center = AxialPoint::from_offset(*center, layout);
+                    let hexes = if *filled {
+                        center.spiral(*radius)
+                    } else {
+                        center.ring(*radius)
+                    };
+                    hexes
+                        .into_iter()
+                        .map(|hex| hex.to_offset(layout).into())
+                        .collect()
+                }
+                None => circle(*center, *radius, *filled),
+            },
+            Shape::Polygon { vertices, filled } => polygon(tilemap, vertices, *filled),
+        };
+
+        let mut seen = HashSet::default();
+        points
+            .into_iter()
+            .filter(|point| seen.insert(*point))
+            .collect()
+    }
+}
+
+/// Returns the points of a rectangle between two corners.
+fn rectangle(corner: Point2, opposite: Point2, filled: bool) -> Vec<Point3> {
+    let (min_x, max_x) = (corner.x.min(opposite.x), corner.x.max(opposite.x));
+    let (min_y, max_y) = (corner.y.min(opposite.y), corner.y.max(opposite.y));
+    let mut points = Vec::new();
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let border = x == min_x || x == max_x || y == min_y || y == max_y;
+            if filled || border {
+                points.push(Point3::new(x, y, 0));
+            }
+        }
+    }
+    points
+}
+
+/// Returns the points of a circle on a square topology.
+fn circle(center: Point2, radius: u32, filled: bool) -> Vec<Point3> {
+    let radius = radius as i32;
+    // Adding the radius once rounds the edge to look less jagged.
+    let inside = |x: i32, y: i32| x * x + y * y <= radius * radius + radius;
+    let mut points = Vec::new();
+    for y in -radius..=radius {
+        for x in -radius..=radius {
+            if !inside(x, y) {
+                continue;
+            }
+            let edge =
+                !inside(x + 1, y) || !inside(x - 1, y) || !inside(x, y + 1) || !inside(x, y - 1);
+            if filled || edge {
+                points.push(Point3::new(center.x + x, center.y + y, 0));
+            }
+        }
+    }
+    points
+}
+
+/// Returns the points of a polygon, the inside being every tile with its
+/// center inside of the polygon through the centers of the vertices.
+fn polygon(tilemap: &Tilemap, vertices: &[Point2], filled: bool) -> Vec<Point3> {
+    let mut points = Vec::new();
+    for (index, from) in vertices.iter().enumerate() {
+        let to = vertices.get(index + 1).or_else(|| vertices.first());
+        if let Some(to) = to {
+            points.extend(tilemap.line(*from, *to));
+        }
+    }
+    if !filled || vertices.len() < 3 {
+        return points;
+    }
+
+    let corners = vertices
+        .iter()
+        .map(|vertex| tilemap.tile_translation((*vertex).into()))
+        .collect::<Vec<Vec2>>();
+    let min_x = vertices.iter().map(|vertex| vertex.x).min().unwrap_or(0);
+    let max_x = vertices.iter().map(|vertex| vertex.x).max().unwrap_or(0);
+    let min_y = vertices.iter().map(|vertex| vertex.y).min().unwrap_or(0);
+    let max_y = vertices.iter().map(|vertex| vertex.y).max().unwrap_or(0);
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let point = Point3::new(x, y, 0);
+            if contains(&corners, tilemap.tile_translation(point)) {
+                points.push(point);
+            }
+        }
+    }
+    points
+}
+
+/// Returns `true` if a position is inside of a polygon by the even-odd rule.
+fn contains(corners: &[Vec2], position: Vec2) -> bool {
+    let mut inside = false;
+    let mut previous = match corners.last() {
+        Some(previous) => *previous,
+        None => return false,
+    };
+    for corner in corners {
+        let crosses = (corner.y > position.y) != (previous.y > position.y);
+        if crosses {
+            let x = corner.x
+                + (position.y - corner.y) / (previous.y - corner.y) * (previous.x - corner.x);
+            if position.x < x {
+                inside = !inside;
+            }
+        }
+        previous = *corner;
+    }
+    inside
+}
@@ -0,0 +1,203 @@
+//! Stamps of tiles that are reused across tilemaps, such as furniture,
+//! houses or trees.
+//!
+//! A [`TileStamp`] holds a rectangular block of tiles of every sprite order
+//! and z depth, relative to its bottom left corner. It is copied from a region
+//! of a tilemap with [`Tilemap::copy_stamp`] and pasted at any point with
+//! [`Tilemap::paste_stamp`], rotated or flipped on square topologies.
+//!
+//! Stamps are assets that are loaded from JSON `.stamp` files, which are the
+//! serialized stamps.
+//!
+//! # Copying and pasting a tree
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::{prelude::*, stamp::StampTransform, tile::TileFlip};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! const TRUNK: usize = 1;
+//! const LEAVES: usize = 2;
+//!
+//! let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+//! tilemap.add_layer(TilemapLayer::default(), 1).unwrap();
+//! tilemap.insert_chunk((0, 0)).unwrap();
+//! tilemap
+//!     .insert_tiles(vec![
+//!         Tile { point: (0, 0), sprite_index: TRUNK, ..Default::default() },
+//!         Tile { point: (0, 1), sprite_order: 1, sprite_index: LEAVES, ..Default::default() },
+//!     ])
+//!     .unwrap();
+//!
+//! let tree = tilemap.copy_stamp((0, 0), (0, 1));
+//! assert_eq!(tree.len(), 2);
+//!
+//! let upside_down = StampTransform { flip: TileFlip::VERTICAL, ..Default::default() };
+//! tilemap.paste_stamp(&tree, (5, 5), upside_down).unwrap();
+//! assert_eq!(tilemap.get_tile((5, 5), 1).map(|tile| tile.index), Some(LEAVES));
+//! assert_eq!(tilemap.get_tile((5, 6), 0).map(|tile| tile.index), Some(TRUNK));
+//! ```
+//!
+//! [`Tilemap::copy_stamp`]: crate::Tilemap::copy_stamp
+//! [`Tilemap::paste_stamp`]: crate::Tilemap::paste_stamp
+
+use crate::{chunk::RawTile, lib::*, tile::TileFlip};
+
+/// A tile of a stamp.
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StampTile {
+    /// The point of the tile relative to the bottom left corner of the stamp.
+    ///
+    /// On hex topologies the x and y are the axial q and r from the corner.
+    pub point: Point3,
+    /// The sprite order of the tile.
+    pub sprite_order: usize,
+    /// The raw tile.
+    pub tile: RawTile,
+}
+
+/// How a stamp is turned when it is pasted.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct StampTransform {
+    /// The counter-clockwise rotation of the stamp in steps of 90°.
+    pub rotation: u8,
+    /// The axes the stamp is flipped on, before it is rotated.
+    pub flip: TileFlip,
+}
+
+/// A rectangular block of tiles of every sprite order that can be pasted on
+/// tilemaps.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct TileStamp {
+    /// The dimensions of the block of tiles.
+    dimensions: Dimension2,
+    /// The tiles of the stamp.
+    tiles: Vec<StampTile>,
+}
+
+impl TypeUuid for TileStamp {
+    const TYPE_UUID: Uuid = Uuid::from_u128(263039781097012729642553025087007164628);
+}
+
+impl TileStamp {
+    /// Constructs a new empty stamp with dimensions.
+    pub fn new(width: u32, height: u32) -> TileStamp {
+        TileStamp {
+            dimensions: Dimension2::new(width, height),
+            tiles: Vec::new(),
+        }
+    }
+
+    /// Returns the width of the stamp in tiles.
+    pub fn width(&self) -> u32 {
+        self.dimensions.width
+    }
+
+    /// Returns the height of the stamp in tiles.
+    pub fn height(&self) -> u32 {
+        self.dimensions.height
+    }
+
+    /// Returns the tiles of the stamp.
+    pub fn tiles(&self) -> &[StampTile] {
+        &self.tiles
+    }
+
+    /// Returns the amount of tiles of the stamp.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Returns `true` if the stamp has no tiles.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Inserts a tile, replacing the tile at the same point and sprite order.
+    pub fn insert(&mut self, tile: StampTile) {
+        let existing = self.tiles.iter_mut().find(|existing| {
+            existing.point == tile.point && existing.sprite_order == tile.sprite_order
+        });
+        match existing {
+            Some(existing) => *existing = tile,
+            None => self.tiles.push(tile),
+        }
+    }
+
+    /// Returns the stamp flipped and then rotated on a square topology.
+    ///
+    /// The points stay relative to the bottom left corner and the sprites of
+    /// the tiles are turned with them.
+    pub fn transformed(&self, transform: StampTransform) -> TileStamp {
+        let StampTransform { rotation, flip } = transform;
+        let (width, height) = (self.width() as i32, self.height() as i32);
+        let mirrored = flip == TileFlip::HORIZONTAL || flip == TileFlip::VERTICAL;
+
+        let tiles = self
+            .tiles
+            .iter()
+            .map(|stamp_tile| {
+                let mut point = stamp_tile.point;
+                if flip.contains(TileFlip::HORIZONTAL) {
+                    point.x = width - 1 - point.x;
+                }
+                if flip.contains(TileFlip::VERTICAL) {
+                    point.y = height - 1 - point.y;
+                }
+                let mut turned_height = height;
+                for step in 0..rotation % 4 {
+                    point = Point3::new(turned_height - 1 - point.y, point.x, point.z);
+                    turned_height = if step % 2 == 0 { width } else { height };
+                }
+
+                // A mirror turns the rotation of the sprite the other way.
+                let tile_rotation = stamp_tile.tile.rotation % 4;
+                let tile_rotation = if mirrored {
+                    rotation % 4 + 4 - tile_rotation
+                } else {
+                    rotation % 4 + tile_rotation
+                };
+                StampTile {
+                    point,
+                    sprite_order: stamp_tile.sprite_order,
+                    tile: RawTile {
+                        flip: stamp_tile.tile.flip ^ flip,
+                        rotation: tile_rotation % 4,
+                        ..stamp_tile.tile
+                    },
+                }
+            })
+            .collect();
+
+        let dimensions = if rotation % 2 == 0 {
+            self.dimensions
+        } else {
+            Dimension2::new(self.height(), self.width())
+        };
+        TileStamp { dimensions, tiles }
+    }
+}
+
+/// The asset loader for stamp `.stamp` files.
+#[derive(Default)]
+pub struct TileStampLoader;
+
+impl AssetLoader for TileStampLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let stamp: TileStamp = serde_json::from_slice(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(stamp));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["stamp"]
+    }
+}
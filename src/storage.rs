@@ -0,0 +1,203 @@
+//! Storage of chunks that are unloaded from memory.
+//!
+//! A tilemap with a [`ChunkStorage`] unloads chunks after they are despawned
+//! and keeps at most [`max_resident_chunks`] chunks in memory, the ones that
+//! were despawned the longest ago are unloaded first. Spawning an unloaded
+//! chunk loads it back before anything else, so endless maps only hold the
+//! chunks around the camera.
+//!
+//! Chunks are stored as the JSON of their tiles, fog and data. The chunks
+//! that are unloaded after they are despawned are written on the IO task
+//! pool. The [`FileChunkStorage`] keeps every chunk in its own file in a
+//! directory.
+//!
+//! # Unloading and reloading a chunk
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::{prelude::*, storage::FileChunkStorage};
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+//! let directory = std::env::temp_dir().join("bevy_tilemap_storage_example");
+//! tilemap.set_chunk_storage(FileChunkStorage::new(&directory));
+//!
+//! tilemap.insert_chunk((0, 0)).unwrap();
+//! tilemap.insert_tile(Tile { point: (3, 4), sprite_index: 7, ..Default::default() }).unwrap();
+//!
+//! tilemap.unload_chunk((0, 0)).unwrap();
+//! assert!(!tilemap.contains_chunk((0, 0)));
+//!
+//! tilemap.spawn_chunk((0, 0)).unwrap();
+//! assert_eq!(tilemap.get_tile((3, 4), 0).map(|tile| tile.index), Some(7));
+//! # std::fs::remove_dir_all(&directory).unwrap();
+//! ```
+//!
+//! [`max_resident_chunks`]: crate::tilemap::TilemapBuilder::max_resident_chunks
+
+use crate::lib::*;
+use futures_lite::future;
+use std::{fs, io};
+
+/// Stores the data of chunks that are unloaded, by the point of the chunk.
+pub trait ChunkStorage: Send + Sync + 'static {
+    /// Stores the data of a chunk, replacing what was stored before.
+    ///
+    /// # Errors
+    ///
+    /// If the data could not be stored.
+    fn store(&self, point: Point2, data: &[u8]) -> io::Result<()>;
+
+    /// Returns the data of a chunk, or `None` if it was never stored.
+    ///
+    /// # Errors
+    ///
+    /// If the data could not be loaded.
+    fn load(&self, point: Point2) -> io::Result<Option<Vec<u8>>>;
+
+    /// Removes the data of a chunk if it was stored.
+    ///
+    /// # Errors
+    ///
+    /// If the data could not be removed.
+    fn remove(&self, point: Point2) -> io::Result<()>;
+}
+
+impl Debug for dyn ChunkStorage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("ChunkStorage")
+    }
+}
+
+/// Stores every chunk in its own file in a directory.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct FileChunkStorage {
+    /// The directory of the files.
+    directory: PathBuf,
+}
+
+impl FileChunkStorage {
+    /// Constructs a new storage in a directory, which is created when the
+    /// first chunk is stored.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> FileChunkStorage {
+        FileChunkStorage {
+            directory: directory.into(),
+        }
+    }
+
+    /// Returns the directory of the files.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns the path of the file of a chunk.
+    fn path(&self, point: Point2) -> PathBuf {
+        self.directory
+            .join(format!("{}_{}.chunk", point.x, point.y))
+    }
+}
+
+impl ChunkStorage for FileChunkStorage {
+    fn store(&self, point: Point2, data: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+        fs::write(self.path(point), data)
+    }
+
+    fn load(&self, point: Point2) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(point)) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn remove(&self, point: Point2) -> io::Result<()> {
+        match fs::remove_file(self.path(point)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The chunks that are being written to a chunk storage on the IO task pool.
+#[derive(Default)]
+pub(crate) struct StoringChunks {
+    /// The data of each chunk with the task writing it.
+    tasks: HashMap<Point2, (Arc<Vec<u8>>, Task<io::Result<()>>)>,
+}
+
+impl StoringChunks {
+    /// Returns `true` if no chunks are being written.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Returns the data of a chunk that is being written.
+    pub(crate) fn data(&self, point: Point2) -> Option<Arc<Vec<u8>>> {
+        self.tasks.get(&point).map(|(data, _)| data.clone())
+    }
+
+    /// Starts writing the data of a chunk, after the data that was being
+    /// written before it.
+    pub(crate) fn store(
+        &mut self,
+        task_pool: &IoTaskPool,
+        storage: Arc<dyn ChunkStorage>,
+        point: Point2,
+        data: Vec<u8>,
+    ) {
+        let data = Arc::new(data);
+        let before = self.tasks.remove(&point).map(|(_, task)| task);
+        let task_data = data.clone();
+        let task = task_pool.spawn(async move {
+            if let Some(before) = before {
+                // The data that is written next replaces it either way.
+                before.await.ok();
+            }
+            storage.store(point, &task_data)
+        });
+        self.tasks.insert(point, (data, task));
+    }
+
+    /// Waits until the data of a chunk is written, if it is being written.
+    ///
+    /// # Errors
+    ///
+    /// If the data could not be written.
+    pub(crate) fn finish(&mut self, point: Point2) -> io::Result<()> {
+        match self.tasks.remove(&point) {
+            Some((_, task)) => future::block_on(task),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the chunks that are written, and returns the data of those
+    /// that could not be written with the error.
+    pub(crate) fn take_failed(&mut self) -> Vec<(Point2, Arc<Vec<u8>>, io::Error)> {
+        let mut finished = Vec::new();
+        for (point, (_, task)) in self.tasks.iter_mut() {
+            if let Some(result) = future::block_on(future::poll_once(task)) {
+                finished.push((*point, result));
+            }
+        }
+        let mut failed = Vec::new();
+        for (point, result) in finished.into_iter() {
+            if let Some((data, _)) = self.tasks.remove(&point) {
+                if let Err(err) = result {
+                    failed.push((point, data, err));
+                }
+            }
+        }
+        failed
+    }
+}
+
+impl Debug for StoringChunks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("StoringChunks")
+            .field("points", &self.tasks.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
@@ -1,19 +1,22 @@
 //! The tilemap systems.
 
 use crate::{
+    animation::TileAnimations,
     chunk::{
         entity::{ChunkBundle, Modified},
         mesh::ChunkMesh,
         render::GridTopology,
+        task::{build_chunk, ChunkSource, MeshParts},
         Chunk, LayerKind,
     },
+    fog::FogOfWar,
     lib::*,
     Tilemap,
 };
 
 /// Takes a grid topology and returns altered translation coordinates.
 // TODO: set translation Z from somewhere else.
-fn topology_translation(
+pub(crate) fn topology_translation(
     topology: GridTopology,
     chunk_point: Point2,
     chunk_dimensions: Dimension3,
@@ -54,6 +57,69 @@ fn topology_translation(
     (translation_x, translation_y)
 }
 
+/// Returns what the meshes of the chunks of a tilemap are built from.
+fn mesh_parts(tilemap: &Tilemap) -> MeshParts {
+    MeshParts {
+        chunk_mesh: tilemap.chunk_mesh().clone(),
+        dimensions: tilemap.chunk_dimensions(),
+        animations: tilemap.animations().clone(),
+        fog_of_war: tilemap.fog_of_war(),
+    }
+}
+
+/// Spawns the entity of a chunk with its mesh and returns it.
+///
+/// The mesh is only updated again by `chunk_update` if the chunk is spawned
+/// as modified.
+fn spawn_chunk_entity(
+    commands: &mut Commands,
+    tilemap_visible: &Visible,
+    meshes: &mut Assets<Mesh>,
+    tilemap: &mut Tilemap,
+    point: Point2,
+    mesh: Mesh,
+    modified: Modified,
+) -> Option<Entity> {
+    let texture_atlas = tilemap.texture_atlas().clone_weak();
+    let pipeline_handle = tilemap.topology().into_pipeline_handle();
+    let translation = tilemap.chunk_translation(point);
+    let tile_scale = tilemap.tile_scale();
+    let chunk = if let Some(chunk) = tilemap.chunks_mut().get_mut(&point) {
+        chunk
+    } else {
+        warn!("Can not get chunk at {}, possible bug report me", &point);
+        return None;
+    };
+    let mesh_handle = meshes.add(mesh);
+    chunk.set_mesh(mesh_handle.clone());
+
+    let pipeline = RenderPipeline::new(pipeline_handle.clone_weak().typed());
+    let entity = commands
+        .spawn()
+        .insert_bundle(ChunkBundle {
+            point,
+            texture_atlas: texture_atlas.clone_weak(),
+            mesh: mesh_handle.clone_weak(),
+            transform: Transform {
+                translation,
+                scale: tile_scale,
+                ..Default::default()
+            },
+            render_pipelines: RenderPipelines::from_pipelines(vec![pipeline]),
+            draw: Default::default(),
+            visible: tilemap_visible.clone(),
+            main_pass: MainPass,
+            global_transform: Default::default(),
+            modified,
+        })
+        .id();
+
+    info!("Chunk {} spawned", point);
+
+    chunk.set_entity(entity);
+    Some(entity)
+}
+
 /// Handles all newly spawned chunks and attempts to spawn them.
 fn handle_spawned_chunks(
     commands: &mut Commands,
@@ -65,6 +131,7 @@ fn handle_spawned_chunks(
 ) {
     let capacity = spawned_chunks.len();
     let mut entities = Vec::with_capacity(capacity);
+    let parts = mesh_parts(tilemap);
     for point in spawned_chunks.into_iter() {
         if tilemap.spawned_chunks().contains(&(point.x, point.y)) {
             continue;
@@ -72,58 +139,103 @@ fn handle_spawned_chunks(
             tilemap.spawned_chunks_mut().insert((point.x, point.y));
         }
 
-        let chunk_dimensions = tilemap.chunk_dimensions();
-        let texture_dimensions = tilemap.texture_dimensions();
-        let texture_atlas = tilemap.texture_atlas().clone_weak();
-        let pipeline_handle = tilemap.topology().into_pipeline_handle();
-        let chunk_mesh = tilemap.chunk_mesh().clone();
-        let topology = tilemap.topology();
-        let chunk = if let Some(chunk) = tilemap.chunks_mut().get_mut(&point) {
-            chunk
+        let mesh = if let Some(chunk) = tilemap.chunks().get(&point) {
+            parts.build(chunk)
         } else {
             // NOTE: should this instead create a chunk if it doesn't exist yet?
             warn!("Can not get chunk at {}, possible bug report me", &point);
             continue;
         };
-        let mut mesh = Mesh::from(&chunk_mesh);
-        let (indexes, colors) = chunk.tiles_to_renderer_parts(chunk_dimensions);
-        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_INDEX, indexes);
-        mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_COLOR, colors);
-        let mesh_handle = meshes.add(mesh);
-        chunk.set_mesh(mesh_handle.clone());
-
-        let (translation_x, translation_y) = topology_translation(
-            topology,
-            chunk.point(),
-            chunk_dimensions,
-            texture_dimensions,
-        );
-        let translation = Vec3::new(translation_x, translation_y, 1.0);
-        let pipeline = RenderPipeline::new(pipeline_handle.clone_weak().typed());
-        let entity = commands
-            .spawn()
-            .insert_bundle(ChunkBundle {
-                point,
-                texture_atlas: texture_atlas.clone_weak(),
-                mesh: mesh_handle.clone_weak(),
-                transform: Transform::from_translation(translation),
-                render_pipelines: RenderPipelines::from_pipelines(vec![pipeline]),
-                draw: Default::default(),
-                visible: tilemap_visible.clone(),
-                main_pass: MainPass,
-                global_transform: Default::default(),
-                modified: Default::default(),
-            })
-            .id();
-
-        info!("Chunk {} spawned", point);
-
-        chunk.set_entity(entity);
-        entities.push(entity);
+        if let Some(entity) = spawn_chunk_entity(
+            commands,
+            tilemap_visible,
+            meshes,
+            tilemap,
+            point,
+            mesh,
+            Modified::default(),
+        ) {
+            entities.push(entity);
+        }
     }
     commands.entity(tilemap_entity).push_children(&entities);
 }
 
+/// Starts building the newly spawned chunks on the async compute task pool.
+fn build_spawned_chunks(
+    task_pool: &AsyncComputeTaskPool,
+    tilemap: &mut Tilemap,
+    spawned_chunks: Vec<Point2>,
+) {
+    let parts = mesh_parts(tilemap);
+    for point in spawned_chunks.into_iter() {
+        if tilemap.spawned_chunks().contains(&(point.x, point.y))
+            || tilemap.pending_chunks().contains(point)
+        {
+            continue;
+        }
+
+        let source = if let Some(chunk) = tilemap.chunks().get(&point) {
+            ChunkSource::Existing(Box::new(chunk.render_copy()))
+        } else if let Some(generator) = tilemap.chunk_generator() {
+            ChunkSource::Generated {
+                generator,
+                layer_kinds: tilemap.layer_kinds(),
+            }
+        } else {
+            warn!("Can not get chunk at {}, possible bug report me", &point);
+            continue;
+        };
+        let parts = parts.clone();
+        let task = task_pool.spawn(async move { build_chunk(point, source, parts) });
+        tilemap.pending_chunks_mut().insert(point, task);
+    }
+}
+
+/// Spawns the chunks that are done being built on the async compute task
+/// pool.
+fn handle_built_chunks(
+    commands: &mut Commands,
+    tilemap_entity: Entity,
+    tilemap_visible: &Visible,
+    meshes: &mut Assets<Mesh>,
+    tilemap: &mut Tilemap,
+) {
+    let mut entities = Vec::new();
+    for (point, built) in tilemap.pending_chunks_mut().take_finished() {
+        let mut stale = false;
+        if let Some(chunk) = built.generated {
+            // The chunk may have been inserted while it was generated.
+            if tilemap.chunks().contains_key(&point) {
+                stale = true;
+            } else {
+                tilemap.chunks_mut().insert(point, chunk);
+            }
+        }
+        // Only the chunks that were modified while they were built need their
+        // mesh updated by `chunk_update`.
+        stale |= tilemap
+            .chunks()
+            .get(&point)
+            .map_or(false, |chunk| chunk.edits() != built.edits);
+        tilemap.spawned_chunks_mut().insert((point.x, point.y));
+        if let Some(entity) = spawn_chunk_entity(
+            commands,
+            tilemap_visible,
+            meshes,
+            tilemap,
+            point,
+            built.mesh,
+            Modified(usize::from(stale)),
+        ) {
+            entities.push(entity);
+        }
+    }
+    if !entities.is_empty() {
+        commands.entity(tilemap_entity).push_children(&entities);
+    }
+}
+
 /// Handles all modified chunks and flags them.
 fn handle_modified_chunks(
     modified_query: &mut Query<&mut Modified>,
@@ -182,6 +294,8 @@ fn recalculate_mesh(
     chunk: &Chunk,
     chunk_mesh: &ChunkMesh,
     chunk_dimensions: Dimension3,
+    animations: &TileAnimations,
+    fog_of_war: Option<FogOfWar>,
 ) {
     let mesh = match meshes.get_mut(mesh) {
         None => {
@@ -190,7 +304,8 @@ fn recalculate_mesh(
         }
         Some(m) => m,
     };
-    let (indexes, colors) = chunk.tiles_to_renderer_parts(chunk_dimensions);
+    let (mut indexes, colors, flags) = chunk.tiles_to_renderer_parts(chunk_dimensions);
+    animations.apply(&mut indexes);
     let vertices: Vec<[f32; 3]> = chunk_mesh
         .vertices
         .clone()
@@ -201,6 +316,11 @@ fn recalculate_mesh(
     mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
     mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_INDEX, indexes);
     mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_COLOR, colors);
+    mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_FLAGS, flags);
+    mesh.set_attribute(
+        ChunkMesh::ATTRIBUTE_TILE_FOG,
+        chunk.fog_to_renderer_parts(chunk_dimensions, fog_of_war),
+    );
 }
 
 /// Adds a sprite layer to all chunks and recalculates the mesh.
@@ -211,11 +331,21 @@ fn handle_add_sprite_layers(
 ) {
     let chunk_dimensions = tilemap.chunk_dimensions();
     let chunk_mesh = tilemap.chunk_mesh().clone();
+    let animations = tilemap.animations().clone();
+    let fog_of_war = tilemap.fog_of_war();
     for chunk in tilemap.chunks_mut().values_mut() {
         for (kind, sprite_layer) in &add_sprite_layers {
             chunk.add_sprite_layer(kind, *sprite_layer, chunk_dimensions);
             if let Some(mesh) = chunk.mesh() {
-                recalculate_mesh(meshes, mesh, chunk, &chunk_mesh, chunk_dimensions);
+                recalculate_mesh(
+                    meshes,
+                    mesh,
+                    chunk,
+                    &chunk_mesh,
+                    chunk_dimensions,
+                    &animations,
+                    fog_of_war,
+                );
             }
         }
     }
@@ -229,11 +359,21 @@ fn handle_remove_sprite_layers(
 ) {
     let chunk_dimensions = tilemap.chunk_dimensions();
     let chunk_mesh = tilemap.chunk_mesh().clone();
+    let animations = tilemap.animations().clone();
+    let fog_of_war = tilemap.fog_of_war();
     for sprite_layer in remove_sprite_layers {
         for chunk in tilemap.chunks_mut().values_mut() {
             chunk.remove_sprite_layer(sprite_layer);
             if let Some(mesh) = chunk.mesh() {
-                recalculate_mesh(meshes, mesh, chunk, &chunk_mesh, chunk_dimensions);
+                recalculate_mesh(
+                    meshes,
+                    mesh,
+                    chunk,
+                    &chunk_mesh,
+                    chunk_dimensions,
+                    &animations,
+                    fog_of_war,
+                );
             }
         }
     }
@@ -245,17 +385,23 @@ fn handle_remove_sprite_layers(
 /// order of operation here. It was done in this order that made the most sense
 /// at the time of creation.
 ///
-/// 1. Spawn chunks
+/// 1. Spawn chunks, or start building them on the async compute task pool
+/// 1. Spawn chunks that are done being built
 /// 1. Modify chunks
-/// 1. Despawn chunks
+/// 1. Despawn chunks, and unload them on the IO task pool if there is a chunk
+///    storage
 pub(crate) fn tilemap_events(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
+    task_pool: Res<AsyncComputeTaskPool>,
+    io_task_pool: Res<IoTaskPool>,
     mut tilemap_query: Query<(Entity, &mut Tilemap, &Visible)>,
     mut modified_query: Query<&mut Modified>,
 ) {
     for (tilemap_entity, mut tilemap, tilemap_visible) in tilemap_query.iter_mut() {
         tilemap.chunk_events_update();
+        tilemap.events_update();
+        tilemap.flush_tile_writes();
         let mut reader = tilemap.chunk_events().get_reader();
 
         let mut modified_chunks = Vec::new();
@@ -287,14 +433,36 @@ pub(crate) fn tilemap_events(
             }
         }
 
+        // Counted before chunks are built, so a copy that is built includes
+        // its edits.
+        for point in modified_chunks.iter() {
+            if let Some(chunk) = tilemap.chunks_mut().get_mut(point) {
+                chunk.add_edit();
+            }
+        }
+
         if !spawned_chunks.is_empty() {
-            handle_spawned_chunks(
+            if tilemap.async_chunks() {
+                build_spawned_chunks(&task_pool, &mut tilemap, spawned_chunks);
+            } else {
+                handle_spawned_chunks(
+                    &mut commands,
+                    tilemap_entity,
+                    tilemap_visible,
+                    &mut meshes,
+                    &mut tilemap,
+                    spawned_chunks,
+                );
+            }
+        }
+
+        if !tilemap.pending_chunks().is_empty() {
+            handle_built_chunks(
                 &mut commands,
                 tilemap_entity,
                 tilemap_visible,
                 &mut meshes,
                 &mut tilemap,
-                spawned_chunks,
             );
         }
 
@@ -304,6 +472,11 @@ pub(crate) fn tilemap_events(
 
         if !despawned_chunks.is_empty() {
             handle_despawned_chunks(&mut commands, &mut tilemap, despawned_chunks);
+            tilemap.unload_despawned_chunks(&io_task_pool);
+        }
+
+        if tilemap.is_storing_chunks() {
+            tilemap.finish_stored_chunks();
         }
 
         if !add_sprite_layers.is_empty() {
@@ -316,6 +489,31 @@ pub(crate) fn tilemap_events(
     }
 }
 
+/// Advances the animations of every tilemap and updates the sprite indices of
+/// the chunks with animated tiles that are drawn with a different frame.
+pub(crate) fn tilemap_animation(
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut tilemap_query: Query<&mut Tilemap>,
+) {
+    for mut tilemap in tilemap_query.iter_mut() {
+        // Only borrow mutably when needed to not trigger change detection.
+        if tilemap.animations().is_empty() {
+            continue;
+        }
+        let changed = tilemap.animations_mut().advance(time.delta_seconds_f64());
+        if changed.is_empty() {
+            continue;
+        }
+
+        for (handle, indexes) in tilemap.animated_chunk_frames(&changed) {
+            if let Some(mesh) = meshes.get_mut(&handle) {
+                mesh.set_attribute(ChunkMesh::ATTRIBUTE_TILE_INDEX, indexes);
+            }
+        }
+    }
+}
+
 /// Checks for tilemap visibility changes and reflects them on all chunks.
 pub fn tilemap_visibility_change(
     tilemap_visible_query: Query<(Entity, &Tilemap)>,
@@ -554,4 +752,98 @@ mod tests {
         let meshes = app.world.get_resource::<Assets<Mesh>>().unwrap();
         assert_eq!(meshes.len(), 2);
     }
+
+    /// Adds a tilemap that generates its chunks on the async compute task
+    /// pool, held back until the gate is unlocked.
+    fn add_async_tilemap(gate: Arc<std::sync::Mutex<()>>, app: &mut App) {
+        app.add_plugin(CorePlugin)
+            .add_plugin(ScheduleRunnerPlugin {})
+            .add_plugin(AssetPlugin)
+            .add_stage("update", SystemStage::parallel())
+            .add_system_to_stage("update", tilemap_events.system())
+            .add_asset::<Mesh>();
+
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .texture_dimensions(32, 32)
+            .async_chunks()
+            .finish()
+            .unwrap();
+        tilemap.set_chunk_generator(move |_: Point2, _: Dimension3| {
+            let _open = gate.lock();
+            Vec::new()
+        });
+        app.world.spawn().insert_bundle(TilemapBundle {
+            tilemap,
+            visible: Default::default(),
+            transform: Default::default(),
+            global_transform: Default::default(),
+        });
+    }
+
+    /// Runs the tilemap events, waits for the chunks that are being built and
+    /// runs the tilemap events again to spawn them.
+    fn update_until_built(app: &mut App) {
+        app.update();
+        let mut tilemap_query = app.world.query::<&mut Tilemap>();
+        for mut tilemap in tilemap_query.iter_mut(&mut app.world) {
+            tilemap.pending_chunks_mut().wait();
+        }
+        app.update();
+    }
+
+    #[test]
+    fn built_chunk_is_not_modified() {
+        let gate = Arc::new(std::sync::Mutex::new(()));
+        let mut app = App::new();
+        add_async_tilemap(gate, &mut app);
+        {
+            let mut tilemap_query = app.world.query::<&mut Tilemap>();
+            let mut tilemap = tilemap_query.iter_mut(&mut app.world).next().unwrap();
+            tilemap.spawn_chunk((0, 0)).unwrap();
+        }
+
+        update_until_built(&mut app);
+
+        let modified = app
+            .world
+            .query::<&Modified>()
+            .iter(&app.world)
+            .map(|modified| modified.0)
+            .collect::<Vec<usize>>();
+        assert_eq!(modified, vec![0]);
+    }
+
+    #[test]
+    fn despawn_chunk_cancels_build() {
+        let gate = Arc::new(std::sync::Mutex::new(()));
+        let closed = gate.lock().unwrap();
+        let mut app = App::new();
+        add_async_tilemap(gate.clone(), &mut app);
+        {
+            let mut tilemap_query = app.world.query::<&mut Tilemap>();
+            let mut tilemap = tilemap_query.iter_mut(&mut app.world).next().unwrap();
+            tilemap.spawn_chunk((0, 0)).unwrap();
+        }
+
+        app.update();
+
+        {
+            let mut tilemap_query = app.world.query::<&mut Tilemap>();
+            let mut tilemap = tilemap_query.iter_mut(&mut app.world).next().unwrap();
+            assert!(tilemap.is_chunk_pending((0, 0)));
+            tilemap.despawn_chunk((0, 0)).unwrap();
+            assert!(!tilemap.is_chunk_pending((0, 0)));
+        }
+
+        drop(closed);
+        app.update();
+        app.update();
+
+        let mut tilemap_query = app.world.query::<&Tilemap>();
+        let tilemap = tilemap_query.iter(&app.world).next().unwrap();
+        assert!(tilemap.chunks().is_empty());
+        assert!(tilemap.spawned_chunks().is_empty());
+        assert_eq!(app.world.query::<&Modified>().iter(&app.world).count(), 0);
+    }
 }
@@ -2,6 +2,25 @@
 
 use crate::lib::*;
 
+bitflags! {
+    /// The axes a tile sprite is flipped on.
+    #[derive(Serialize, Deserialize, Reflect)]
+    pub struct TileFlip: u8 {
+        /// The sprite is not flipped.
+        const NONE = 0b00;
+        /// The sprite is flipped horizontally, mirroring left and right.
+        const HORIZONTAL = 0b01;
+        /// The sprite is flipped vertically, mirroring top and bottom.
+        const VERTICAL = 0b10;
+    }
+}
+
+impl Default for TileFlip {
+    fn default() -> Self {
+        TileFlip::NONE
+    }
+}
+
 /// A tile with an index value and color.
 
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -14,6 +33,11 @@ pub struct Tile<P: Into<Point3>> {
     pub sprite_index: usize,
     /// The desired tint and alpha of the tile. White means no change.
     pub tint: Color,
+    /// The axes the sprite is flipped on, before it is rotated.
+    pub flip: TileFlip,
+    /// The counter-clockwise rotation of the sprite in steps, which are 90°
+    /// for square topologies and 60° for hex topologies.
+    pub rotation: u8,
 }
 
 impl<P: Into<Point3> + Default> Default for Tile<P> {
@@ -23,6 +47,18 @@ impl<P: Into<Point3> + Default> Default for Tile<P> {
             sprite_order: 0,
             sprite_index: 0,
             tint: Color::WHITE,
+            flip: TileFlip::NONE,
+            rotation: 0,
         }
     }
 }
+
+/// A typed value that can be attached to tiles and chunks, such as the lock
+/// state of a door or the biome of a chunk.
+///
+/// Implemented for every type that is reflected, has a UUID and can be
+/// serialized. Values are serialized under the UUID of their type, which
+/// must not change once tilemaps with values of the type were saved.
+pub trait TileData: Reflect + TypeUuid + Serialize + DeserializeOwned {}
+
+impl<T: Reflect + TypeUuid + Serialize + DeserializeOwned> TileData for T {}
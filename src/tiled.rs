@@ -0,0 +1,1089 @@
+//! Loading of [Tiled] maps as Bevy assets.
+//!
+//! This module is only available with the `tiled` feature.
+//!
+//! ```toml
+//! [dependencies]
+//! bevy_tilemap = { version = "0.4", features = ["tiled"] }
+//! ```
+//!
+//! With the feature enabled, the [`TilemapPlugin`] registers an asset loader
+//! for `.tmx` files which produces a [`TiledMap`]. A `TiledMap` contains a
+//! ready to use [`Tilemap`] along with a [`TiledReport`] of everything in the
+//! file that could not be carried over.
+//!
+//! Orthogonal and hexagonal maps are supported. Every tile layer becomes a
+//! sprite layer, in the same order as in Tiled, and is either dense or sparse
+//! depending on how full it is. The tileset, embedded or in an external
+//! `.tsx` file, becomes the [`TextureAtlas`] of the tilemap.
+//!
+//! Tiled counts rows from the top of the map while tilemaps count them from
+//! the bottom, so the map is flipped as it is loaded and centered on the
+//! tilemap's origin. The staggered axis and index of a hexagonal map are
+//! translated to the [`GridTopology`] which renders it the same way.
+//!
+//! # Loading a map
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_tilemap::{prelude::*, tiled::TiledMap};
+//!
+//! struct Level(Handle<TiledMap>);
+//!
+//! fn load(mut commands: Commands, asset_server: Res<AssetServer>) {
+//!     commands.insert_resource(Level(asset_server.load("maps/level.tmx")));
+//! }
+//!
+//! fn spawn(mut commands: Commands, level: Res<Level>, mut maps: ResMut<Assets<TiledMap>>) {
+//!     if let Some(map) = maps.remove(&level.0) {
+//!         for unsupported in map.report.unsupported.iter() {
+//!             warn!("{}", unsupported);
+//!         }
+//!         commands.spawn().insert_bundle(TilemapBundle {
+//!             tilemap: map.tilemap,
+//!             visible: Visible {
+//!                 is_visible: true,
+//!                 is_transparent: true,
+//!             },
+//!             transform: Default::default(),
+//!             global_transform: Default::default(),
+//!         });
+//!     }
+//! }
+//! ```
+//!
+//! [Tiled]: https://www.mapeditor.org/
+//! [`TilemapPlugin`]: crate::TilemapPlugin
+//! [`GridTopology`]: crate::prelude::GridTopology
+
+use crate::{
+    chunk::LayerKind,
+    lib::*,
+    prelude::GridTopology,
+    tile::{Tile, TileFlip},
+    tilemap::{TilemapBuilder, TilemapError},
+    Tilemap, TilemapLayer,
+};
+use roxmltree::{Document, Node};
+use std::cmp::{max, min};
+
+/// Flag set on a global tile ID if the tile is flipped horizontally.
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+/// Flag set on a global tile ID if the tile is flipped vertically.
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+/// Flag set on a global tile ID if the tile is flipped diagonally.
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+/// Flag set on a global tile ID if a hexagonal tile is rotated by 120°.
+const ROTATED_HEXAGONAL_120: u32 = 0x1000_0000;
+/// All the flags that can be set on a global tile ID.
+const FLIPPED_FLAGS: u32 =
+    FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL_120;
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+/// The kinds of errors that can occur when loading a Tiled map.
+pub enum ErrorKind {
+    /// The XML of the file could not be parsed.
+    Xml(String),
+    /// A required element is missing.
+    MissingElement(&'static str),
+    /// A required attribute is missing or could not be parsed.
+    InvalidAttribute(&'static str),
+    /// The map orientation can not be rendered by a tilemap.
+    UnsupportedOrientation(String),
+    /// The layer data encoding or compression is not supported.
+    UnsupportedEncoding(String),
+    /// The layer data could not be decoded.
+    InvalidData(String),
+    /// None of the tilesets of the map have a single image.
+    MissingTilesetImage,
+    /// An external tileset was referenced but never resolved.
+    UnresolvedTileset(String),
+    /// The tilemap could not be constructed.
+    Tilemap(TilemapError),
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        use ErrorKind::*;
+        match self {
+            Xml(err) => write!(f, "could not parse the XML: {}", err),
+            MissingElement(name) => write!(f, "the `{}` element is missing", name),
+            InvalidAttribute(name) => {
+                write!(f, "the `{}` attribute is missing or invalid", name)
+            }
+            UnsupportedOrientation(orientation) => write!(
+                f,
+                "{} maps are not supported, only orthogonal and hexagonal maps",
+                orientation
+            ),
+            UnsupportedEncoding(encoding) => {
+                write!(f, "layer data encoded as {} is not supported", encoding)
+            }
+            InvalidData(layer) => write!(f, "the data of layer {} could not be decoded", layer),
+            MissingTilesetImage => write!(f, "none of the tilesets have a single image"),
+            UnresolvedTileset(source) => write!(
+                f,
+                "the external tileset {} was not resolved, try `resolve_tileset` first",
+                source
+            ),
+            Tilemap(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl Error for ErrorKind {}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+/// The error type for loading Tiled maps.
+pub struct TiledError(pub Box<ErrorKind>);
+
+impl Display for TiledError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for TiledError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl From<ErrorKind> for TiledError {
+    fn from(kind: ErrorKind) -> TiledError {
+        TiledError(Box::new(kind))
+    }
+}
+
+impl From<TilemapError> for TiledError {
+    fn from(err: TilemapError) -> TiledError {
+        TiledError(Box::new(ErrorKind::Tilemap(err)))
+    }
+}
+
+impl From<roxmltree::Error> for TiledError {
+    fn from(err: roxmltree::Error) -> TiledError {
+        TiledError(Box::new(ErrorKind::Xml(err.to_string())))
+    }
+}
+
+/// A Tiled result.
+pub type TiledResult<T> = Result<T, TiledError>;
+
+#[derive(Clone, PartialEq, Debug)]
+/// A feature of a Tiled map which could not be carried over to the tilemap.
+pub enum Unsupported {
+    /// Only one tileset can be used, the tiles of this tileset were skipped.
+    Tileset {
+        /// The name of the tileset.
+        name: String,
+        /// How many tiles were skipped.
+        skipped: usize,
+    },
+    /// A tileset made of a collection of images, which can not be made into a
+    /// texture atlas.
+    ImageCollection(String),
+    /// An object layer, which was skipped.
+    ObjectGroup(String),
+    /// An image layer, which was skipped.
+    ImageLayer(String),
+    /// A hidden layer, which was skipped.
+    HiddenLayer(String),
+    /// A layer with an offset or parallax, which was placed without it.
+    LayerOffset(String),
+    /// A hex side length which is not half of the tile size, the hexes will
+    /// not line up the same as in Tiled.
+    HexSideLength(u32),
+}
+
+impl Display for Unsupported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        use Unsupported::*;
+        match self {
+            Tileset { name, skipped } => write!(
+                f,
+                "only one tileset is supported, skipped {} tiles of tileset {}",
+                skipped, name
+            ),
+            ImageCollection(name) => {
+                write!(f, "image collection tileset {} is not supported", name)
+            }
+            ObjectGroup(name) => write!(f, "object layer {} is not supported", name),
+            ImageLayer(name) => write!(f, "image layer {} is not supported", name),
+            HiddenLayer(name) => write!(f, "hidden layer {} was skipped", name),
+            LayerOffset(name) => write!(f, "the offset of layer {} is not supported", name),
+            HexSideLength(length) => write!(
+                f,
+                "a hex side length of {} is not supported, it must be half of the tile size",
+                length
+            ),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+/// A report of everything in a Tiled map that could not be carried over.
+pub struct TiledReport {
+    /// All the unsupported features found in the map.
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl TiledReport {
+    /// Returns `true` if the whole map was carried over.
+    pub fn is_empty(&self) -> bool {
+        self.unsupported.is_empty()
+    }
+}
+
+/// A Tiled map which was loaded into a tilemap.
+#[derive(Debug)]
+pub struct TiledMap {
+    /// The tilemap with all the tiles of the map.
+    pub tilemap: Tilemap,
+    /// What could not be carried over from the map.
+    pub report: TiledReport,
+}
+
+impl TypeUuid for TiledMap {
+    const TYPE_UUID: Uuid = Uuid::from_u128(129992859104901946547385078262610206274);
+}
+
+/// The asset loader for Tiled `.tmx` files.
+#[derive(Default)]
+pub struct TiledLoader;
+
+impl AssetLoader for TiledLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let directory = load_context
+                .path()
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            let mut map = TmxMap::parse(std::str::from_utf8(bytes)?)?;
+            for source in map.external_tilesets() {
+                let tsx = load_context
+                    .read_asset_bytes(directory.join(&source))
+                    .await?;
+                map.resolve_tileset(&source, std::str::from_utf8(&tsx)?)?;
+            }
+
+            let image_path = AssetPath::new(directory.join(map.tileset_image()?), None);
+            let texture = load_context.get_handle(image_path.clone());
+            let texture_atlas = load_context.set_labeled_asset(
+                "texture_atlas",
+                LoadedAsset::new(map.texture_atlas(texture)?).with_dependency(image_path),
+            );
+            let tiled_map = map.into_tiled_map(texture_atlas)?;
+            load_context.set_default_asset(LoadedAsset::new(tiled_map));
+
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+/// The image of a tileset.
+#[derive(Clone, PartialEq, Debug)]
+struct TmxImage {
+    /// The path to the image, relative to the file the tileset is in.
+    source: String,
+    /// The width of the image in pixels, if known.
+    width: Option<u32>,
+    /// The height of the image in pixels, if known.
+    height: Option<u32>,
+}
+
+/// A tileset of a map.
+#[derive(Clone, PartialEq, Debug)]
+struct TmxTileset {
+    /// The first global tile ID of the tileset.
+    first_gid: u32,
+    /// The path to the external tileset, if it is not embedded.
+    source: Option<String>,
+    /// The name of the tileset.
+    name: String,
+    /// The width of a tile in pixels.
+    tile_width: u32,
+    /// The height of a tile in pixels.
+    tile_height: u32,
+    /// The spacing between tiles in pixels.
+    spacing: u32,
+    /// The margin around the tiles in pixels.
+    margin: u32,
+    /// The amount of tiles in the tileset, if known.
+    tile_count: Option<u32>,
+    /// The amount of columns in the tileset, if known.
+    columns: Option<u32>,
+    /// The single image of the tileset, if it is not an image collection.
+    image: Option<TmxImage>,
+    /// If the tileset is still waiting to be resolved.
+    unresolved: bool,
+}
+
+/// A tile layer of a map.
+#[derive(Clone, PartialEq, Debug)]
+struct TmxLayer {
+    /// The name of the layer.
+    name: String,
+    /// The tint of the layer, including its opacity.
+    tint: Color,
+    /// All the non-empty tiles as a X, Y coordinate and a global tile ID.
+    tiles: Vec<(i32, i32, u32)>,
+}
+
+/// A parsed Tiled map, which can be made into a [`TiledMap`].
+///
+/// This is what the asset loader uses internally, which is useful if the map
+/// is not loaded through the asset server.
+///
+/// # Examples
+/// ```
+/// use bevy::asset::{prelude::*, HandleId};
+/// use bevy::sprite::prelude::*;
+/// use bevy_tilemap::{prelude::*, tiled::TmxMap};
+///
+/// let tmx = r#"
+/// <map orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16">
+///   <tileset firstgid="1" name="tiles" tilewidth="16" tileheight="16" columns="4">
+///     <image source="tiles.png" width="64" height="64"/>
+///   </tileset>
+///   <layer name="ground" width="2" height="2">
+///     <data encoding="csv">1,2,3,4</data>
+///   </layer>
+/// </map>
+/// "#;
+///
+/// let map = TmxMap::parse(tmx).unwrap();
+/// assert_eq!(map.tileset_image().unwrap().to_str(), Some("tiles.png"));
+///
+/// // In production use a strong handle from an actual source.
+/// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+/// let tiled_map = map.into_tiled_map(texture_atlas_handle).unwrap();
+/// assert!(tiled_map.report.is_empty());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct TmxMap {
+    /// The topology that renders the map the same way.
+    topology: GridTopology,
+    /// The width of a tile in pixels.
+    tile_width: u32,
+    /// The height of a tile in pixels.
+    tile_height: u32,
+    /// All the tilesets of the map, ordered by their first global tile ID.
+    tilesets: Vec<TmxTileset>,
+    /// All the tile layers of the map, from the lowest to the highest.
+    layers: Vec<TmxLayer>,
+    /// Unsupported features found while parsing.
+    report: TiledReport,
+}
+
+impl TmxMap {
+    /// Parses a `.tmx` map.
+    ///
+    /// External tilesets are not read, they must be resolved with
+    /// [`resolve_tileset`] before the map can be used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the XML is invalid, if the map is not orthogonal
+    /// or hexagonal, or if any of the layer data can not be decoded.
+    ///
+    /// [`resolve_tileset`]: TmxMap::resolve_tileset
+    pub fn parse(tmx: &str) -> TiledResult<TmxMap> {
+        let document = Document::parse(tmx)?;
+        let root = document.root_element();
+        if !root.has_tag_name("map") {
+            return Err(ErrorKind::MissingElement("map").into());
+        }
+
+        let width: u32 = optional_attribute(root, "width")?.unwrap_or(0);
+        let height: u32 = optional_attribute(root, "height")?.unwrap_or(0);
+        let infinite = optional_attribute::<u32>(root, "infinite")?.unwrap_or(0) == 1;
+        // Tiled counts rows downwards, tilemaps count them upwards. Finite
+        // maps are centered on the origin.
+        let (origin_x, origin_y) = if infinite {
+            (0, 0)
+        } else {
+            ((width / 2) as i32, (height / 2) as i32)
+        };
+
+        let mut report = TiledReport::default();
+        let orientation = root.attribute("orientation").unwrap_or("orthogonal");
+        let topology = match orientation {
+            "orthogonal" => GridTopology::Square,
+            "hexagonal" => {
+                let stagger_odd = root.attribute("staggerindex") != Some("even");
+                let stagger = if stagger_odd { 1 } else { 0 };
+                let tile_height: u32 = attribute(root, "tileheight")?;
+                let tile_width: u32 = attribute(root, "tilewidth")?;
+                let side: Option<u32> = optional_attribute(root, "hexsidelength")?;
+                if root.attribute("staggeraxis") == Some("x") {
+                    if let Some(side) = side.filter(|side| *side != tile_width / 2) {
+                        report.unsupported.push(Unsupported::HexSideLength(side));
+                    }
+                    // Staggered columns are shifted down. The others are
+                    // shifted up, which is what the column topologies set.
+                    if (stagger - origin_x) & 1 == 1 {
+                        GridTopology::HexEvenCols
+                    } else {
+                        GridTopology::HexOddCols
+                    }
+                } else {
+                    if let Some(side) = side.filter(|side| *side != tile_height / 2) {
+                        report.unsupported.push(Unsupported::HexSideLength(side));
+                    }
+                    // Staggered rows are shifted right.
+                    if (origin_y - 1 - stagger) & 1 == 1 {
+                        GridTopology::HexOddRows
+                    } else {
+                        GridTopology::HexEvenRows
+                    }
+                }
+            }
+            _ => return Err(ErrorKind::UnsupportedOrientation(orientation.to_string()).into()),
+        };
+
+        let mut tilesets = Vec::new();
+        for node in root.children().filter(|node| node.has_tag_name("tileset")) {
+            let first_gid = attribute(node, "firstgid")?;
+            match node.attribute("source") {
+                Some(source) => tilesets.push(TmxTileset {
+                    first_gid,
+                    source: Some(source.to_string()),
+                    name: source.to_string(),
+                    tile_width: 0,
+                    tile_height: 0,
+                    spacing: 0,
+                    margin: 0,
+                    tile_count: None,
+                    columns: None,
+                    image: None,
+                    unresolved: true,
+                }),
+                None => tilesets.push(parse_tileset(node, first_gid, None)?),
+            }
+        }
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let mut layers = Vec::new();
+        parse_layers(root, origin_x, origin_y, &mut layers, &mut report)?;
+
+        Ok(TmxMap {
+            topology,
+            tile_width: attribute(root, "tilewidth")?,
+            tile_height: attribute(root, "tileheight")?,
+            tilesets,
+            layers,
+            report,
+        })
+    }
+
+    /// Returns the sources of all the external tilesets which still need to
+    /// be resolved.
+    pub fn external_tilesets(&self) -> Vec<String> {
+        self.tilesets
+            .iter()
+            .filter(|tileset| tileset.unresolved)
+            .filter_map(|tileset| tileset.source.clone())
+            .collect()
+    }
+
+    /// Resolves an external tileset with the contents of its `.tsx` file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the XML is invalid or is not a tileset.
+    pub fn resolve_tileset(&mut self, source: &str, tsx: &str) -> TiledResult<()> {
+        let document = Document::parse(tsx)?;
+        let root = document.root_element();
+        if !root.has_tag_name("tileset") {
+            return Err(ErrorKind::MissingElement("tileset").into());
+        }
+
+        for tileset in self.tilesets.iter_mut() {
+            if tileset.unresolved && tileset.source.as_deref() == Some(source) {
+                *tileset = parse_tileset(root, tileset.first_gid, Some(source))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The tileset which is used for the texture atlas, the first one with a
+    /// single image.
+    fn tileset(&self) -> TiledResult<&TmxTileset> {
+        if let Some(tileset) = self.tilesets.iter().find(|tileset| tileset.unresolved) {
+            let source = tileset.source.clone().unwrap_or_default();
+            return Err(ErrorKind::UnresolvedTileset(source).into());
+        }
+        self.tilesets
+            .iter()
+            .find(|tileset| tileset.image.is_some())
+            .ok_or_else(|| ErrorKind::MissingTilesetImage.into())
+    }
+
+    /// Returns the path of the image of the tileset which is used for the
+    /// texture atlas, relative to the map.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an external tileset was not resolved or if none of
+    /// the tilesets have a single image.
+    pub fn tileset_image(&self) -> TiledResult<PathBuf> {
+        let tileset = self.tileset()?;
+        let directory = tileset
+            .source
+            .as_deref()
+            .and_then(|source| Path::new(source).parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let image = tileset
+            .image
+            .as_ref()
+            .ok_or(ErrorKind::MissingTilesetImage)?;
+        Ok(directory.join(&image.source))
+    }
+
+    /// Constructs the texture atlas of the tileset from the tileset image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an external tileset was not resolved or if none of
+    /// the tilesets have a single image.
+    pub fn texture_atlas(&self, texture: Handle<Texture>) -> TiledResult<TextureAtlas> {
+        let tileset = self.tileset()?;
+        let image = tileset
+            .image
+            .as_ref()
+            .ok_or(ErrorKind::MissingTilesetImage)?;
+        let stride_x = tileset.tile_width + tileset.spacing;
+        let stride_y = tileset.tile_height + tileset.spacing;
+        let columns = tileset
+            .columns
+            .filter(|columns| *columns > 0)
+            .unwrap_or_else(|| {
+                let width = image.width.unwrap_or(0) + tileset.spacing;
+                width.saturating_sub(tileset.margin * 2) / stride_x.max(1)
+            });
+        let tile_count = tileset.tile_count.unwrap_or_else(|| {
+            let height = image.height.unwrap_or(0) + tileset.spacing;
+            columns * (height.saturating_sub(tileset.margin * 2) / stride_y.max(1))
+        });
+        let rows = (tile_count + columns.max(1) - 1) / columns.max(1);
+        let width = image
+            .width
+            .unwrap_or(tileset.margin * 2 + columns * stride_x);
+        let height = image.height.unwrap_or(tileset.margin * 2 + rows * stride_y);
+
+        let mut texture_atlas =
+            TextureAtlas::new_empty(texture, Vec2::new(width as f32, height as f32));
+        for index in 0..tile_count {
+            let x = (tileset.margin + (index % columns.max(1)) * stride_x) as f32;
+            let y = (tileset.margin + (index / columns.max(1)) * stride_y) as f32;
+            texture_atlas.add_texture(Rect {
+                min: Vec2::new(x, y),
+                max: Vec2::new(
+                    x + tileset.tile_width as f32,
+                    y + tileset.tile_height as f32,
+                ),
+            });
+        }
+
+        Ok(texture_atlas)
+    }
+
+    /// Consumes the map and constructs a [`TiledMap`] with the texture atlas
+    /// of the tileset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an external tileset was not resolved, if none of
+    /// the tilesets have a single image or if the tilemap could not be
+    /// constructed.
+    pub fn into_tiled_map(self, texture_atlas: Handle<TextureAtlas>) -> TiledResult<TiledMap> {
+        let first_gid = self.tileset()?.first_gid;
+        let mut report = self.report.clone();
+        for tileset in self.tilesets.iter() {
+            if tileset.image.is_none() {
+                report
+                    .unsupported
+                    .push(Unsupported::ImageCollection(tileset.name.clone()));
+            }
+        }
+
+        let mut builder = TilemapBuilder::new()
+            .texture_atlas(texture_atlas)
+            .topology(self.topology)
+            .texture_dimensions(self.tile_width, self.tile_height)
+            .z_layers(self.layers.len())
+            .auto_chunk();
+        let mut tiles = Vec::new();
+        let mut skipped = HashMap::default();
+        for (sprite_order, layer) in self.layers.iter().enumerate() {
+            for (x, y, gid) in layer.tiles.iter() {
+                let (flip, rotation) = orientation(*gid, self.topology != GridTopology::Square);
+                let gid = gid & !FLIPPED_FLAGS;
+                let owner = self
+                    .tilesets
+                    .iter()
+                    .rev()
+                    .find(|tileset| tileset.first_gid <= gid);
+                match owner {
+                    Some(tileset) if tileset.first_gid == first_gid => tiles.push(Tile {
+                        point: Point3::new(*x, *y, 0),
+                        sprite_order,
+                        sprite_index: (gid - first_gid) as usize,
+                        tint: layer.tint,
+                        flip,
+                        rotation,
+                    }),
+                    Some(tileset) => *skipped.entry(tileset.name.clone()).or_insert(0) += 1,
+                    None => {}
+                }
+            }
+
+            // A layer that fills at least half of its bounds is better off
+            // dense.
+            let bounds = layer
+                .tiles
+                .iter()
+                .fold(None, |bounds, (x, y, _)| match bounds {
+                    Some((min_x, min_y, max_x, max_y)) => Some((
+                        min(min_x, *x),
+                        min(min_y, *y),
+                        max(max_x, *x),
+                        max(max_y, *y),
+                    )),
+                    None => Some((*x, *y, *x, *y)),
+                });
+            let area = bounds.map_or(0, |(min_x, min_y, max_x, max_y)| {
+                ((max_x - min_x + 1) * (max_y - min_y + 1)) as usize
+            });
+            let kind = if area > 0 && layer.tiles.len() * 2 >= area {
+                LayerKind::Dense
+            } else {
+                LayerKind::Sparse
+            };
+            builder = builder.add_layer(TilemapLayer { kind }, sprite_order);
+        }
+        for (name, skipped) in skipped.into_iter() {
+            report
+                .unsupported
+                .push(Unsupported::Tileset { name, skipped });
+        }
+
+        let mut tilemap = builder.finish()?;
+        tilemap.insert_tiles(tiles)?;
+
+        Ok(TiledMap { tilemap, report })
+    }
+}
+
+/// Converts the flags of a global tile ID to a flip and a rotation.
+fn orientation(gid: u32, hexagonal: bool) -> (TileFlip, u8) {
+    let horizontal = gid & FLIPPED_HORIZONTALLY != 0;
+    let vertical = gid & FLIPPED_VERTICALLY != 0;
+    let diagonal = gid & FLIPPED_DIAGONALLY != 0;
+    let mut flip = TileFlip::NONE;
+    flip.set(TileFlip::HORIZONTAL, horizontal);
+    flip.set(TileFlip::VERTICAL, vertical);
+
+    if hexagonal {
+        // Hexes are rotated clockwise by 60° with the diagonal flag and by
+        // 120° with their own flag, before they are flipped. Flipping on one
+        // axis mirrors the direction of the rotation.
+        let clockwise = u8::from(diagonal) + u8::from(gid & ROTATED_HEXAGONAL_120 != 0) * 2;
+        if horizontal != vertical {
+            (flip, clockwise)
+        } else {
+            (flip, (6 - clockwise) % 6)
+        }
+    } else if diagonal {
+        // The axes are swapped before the tile is flipped, which is a quarter
+        // turn of a tile flipped horizontally.
+        let flip = if horizontal == vertical {
+            TileFlip::HORIZONTAL
+        } else {
+            TileFlip::NONE
+        };
+        (flip, if horizontal { 3 } else { 1 })
+    } else {
+        (flip, 0)
+    }
+}
+
+/// Returns a parsed attribute of a node.
+fn attribute<T: std::str::FromStr>(node: Node, name: &'static str) -> TiledResult<T> {
+    optional_attribute(node, name)?.ok_or_else(|| ErrorKind::InvalidAttribute(name).into())
+}
+
+/// Returns a parsed attribute of a node, if it exists.
+fn optional_attribute<T: std::str::FromStr>(
+    node: Node,
+    name: &'static str,
+) -> TiledResult<Option<T>> {
+    match node.attribute(name) {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| ErrorKind::InvalidAttribute(name).into()),
+        None => Ok(None),
+    }
+}
+
+/// Parses a `tileset` element, either embedded or from a `.tsx` file.
+fn parse_tileset(node: Node, first_gid: u32, source: Option<&str>) -> TiledResult<TmxTileset> {
+    let image = node
+        .children()
+        .find(|child| child.has_tag_name("image"))
+        .map(|image| -> TiledResult<TmxImage> {
+            Ok(TmxImage {
+                source: image
+                    .attribute("source")
+                    .ok_or(ErrorKind::InvalidAttribute("source"))?
+                    .to_string(),
+                width: optional_attribute(image, "width")?,
+                height: optional_attribute(image, "height")?,
+            })
+        })
+        .transpose()?;
+
+    Ok(TmxTileset {
+        first_gid,
+        source: source.map(str::to_string),
+        name: node.attribute("name").unwrap_or_default().to_string(),
+        tile_width: attribute(node, "tilewidth")?,
+        tile_height: attribute(node, "tileheight")?,
+        spacing: optional_attribute(node, "spacing")?.unwrap_or(0),
+        margin: optional_attribute(node, "margin")?.unwrap_or(0),
+        tile_count: optional_attribute(node, "tilecount")?,
+        columns: optional_attribute(node, "columns")?,
+        image,
+        unresolved: false,
+    })
+}
+
+/// Parses all the layers of a map or group, in order, flattening groups.
+fn parse_layers(
+    parent: Node,
+    origin_x: i32,
+    origin_y: i32,
+    layers: &mut Vec<TmxLayer>,
+    report: &mut TiledReport,
+) -> TiledResult<()> {
+    for node in parent.children().filter(Node::is_element) {
+        let name = node.attribute("name").unwrap_or_default().to_string();
+        match node.tag_name().name() {
+            "layer" => {
+                let visible = optional_attribute::<u32>(node, "visible")?.unwrap_or(1) == 1;
+                if !visible {
+                    report.unsupported.push(Unsupported::HiddenLayer(name));
+                    continue;
+                }
+                let offset = ["offsetx", "offsety", "parallaxx", "parallaxy"]
+                    .iter()
+                    .any(|attribute| node.has_attribute(*attribute));
+                if offset {
+                    report
+                        .unsupported
+                        .push(Unsupported::LayerOffset(name.clone()));
+                }
+
+                let opacity: f32 = optional_attribute(node, "opacity")?.unwrap_or(1.0);
+                let mut tint = match node.attribute("tintcolor") {
+                    Some(color) => {
+                        parse_color(color).ok_or(ErrorKind::InvalidAttribute("tintcolor"))?
+                    }
+                    None => Color::WHITE,
+                };
+                tint.set_a(tint.a() * opacity);
+
+                let width = optional_attribute(node, "width")?.unwrap_or(0);
+                let data = node
+                    .children()
+                    .find(|child| child.has_tag_name("data"))
+                    .ok_or(ErrorKind::MissingElement("data"))?;
+                let mut tiles = Vec::new();
+                let chunks = data
+                    .children()
+                    .filter(|child| child.has_tag_name("chunk"))
+                    .collect::<Vec<Node>>();
+                if chunks.is_empty() {
+                    parse_data(data, data, 0, 0, width, &name, &mut tiles)?;
+                } else {
+                    for chunk in chunks.into_iter() {
+                        let x = attribute(chunk, "x")?;
+                        let y = attribute(chunk, "y")?;
+                        let width = attribute(chunk, "width")?;
+                        parse_data(data, chunk, x, y, width, &name, &mut tiles)?;
+                    }
+                }
+
+                let tiles = tiles
+                    .into_iter()
+                    .map(|(x, y, gid)| (x - origin_x, origin_y - 1 - y, gid))
+                    .collect();
+                layers.push(TmxLayer { name, tint, tiles });
+            }
+            "group" => parse_layers(node, origin_x, origin_y, layers, report)?,
+            "objectgroup" => report.unsupported.push(Unsupported::ObjectGroup(name)),
+            "imagelayer" => report.unsupported.push(Unsupported::ImageLayer(name)),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses layer data, or a chunk of it, into tiles with Tiled coordinates.
+///
+/// The `data` node holds the encoding while the `node` holds the tiles, which
+/// is the same node for finite maps.
+fn parse_data(
+    data: Node,
+    node: Node,
+    x: i32,
+    y: i32,
+    width: u32,
+    layer: &str,
+    tiles: &mut Vec<(i32, i32, u32)>,
+) -> TiledResult<()> {
+    if let Some(compression) = data.attribute("compression") {
+        return Err(ErrorKind::UnsupportedEncoding(compression.to_string()).into());
+    }
+    let invalid = || TiledError::from(ErrorKind::InvalidData(layer.to_string()));
+
+    let gids = match data.attribute("encoding") {
+        Some("csv") => node
+            .text()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| gid.parse::<u32>().map_err(|_| invalid()))
+            .collect::<TiledResult<Vec<u32>>>()?,
+        Some("base64") => {
+            let bytes = decode_base64(node.text().unwrap_or_default()).ok_or_else(invalid)?;
+            bytes
+                .chunks_exact(4)
+                .map(|bytes| match *bytes {
+                    [a, b, c, d] => u32::from_le_bytes([a, b, c, d]),
+                    _ => 0,
+                })
+                .collect()
+        }
+        Some(encoding) => {
+            return Err(ErrorKind::UnsupportedEncoding(encoding.to_string()).into());
+        }
+        None => node
+            .children()
+            .filter(|child| child.has_tag_name("tile"))
+            .map(|tile| optional_attribute(tile, "gid").map(Option::unwrap_or_default))
+            .collect::<TiledResult<Vec<u32>>>()?,
+    };
+
+    let width = width.max(1) as usize;
+    for (index, gid) in gids.into_iter().enumerate() {
+        if gid != 0 {
+            tiles.push((x + (index % width) as i32, y + (index / width) as i32, gid));
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a Tiled color, either `#RRGGBB` or `#AARRGGBB`.
+fn parse_color(color: &str) -> Option<Color> {
+    let color = color.trim_start_matches('#');
+    let value = u32::from_str_radix(color, 16).ok()?;
+    let [a, r, g, b] = value.to_be_bytes();
+    match color.len() {
+        6 => Some(Color::rgba_u8(r, g, b, 255)),
+        8 => Some(Color::rgba_u8(r, g, b, a)),
+        _ => None,
+    }
+}
+
+/// Decodes standard base64, ignoring any whitespace.
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for byte in input.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::RawTile;
+
+    #[test]
+    fn orthogonal_map() {
+        let tmx = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <map version="1.5" orientation="orthogonal" renderorder="right-down"
+                 width="4" height="2" tilewidth="16" tileheight="16" infinite="0">
+              <tileset firstgid="1" source="terrain.tsx"/>
+              <tileset firstgid="101" name="props" tilewidth="16" tileheight="16">
+                <tile id="0"><image source="barrel.png" width="16" height="16"/></tile>
+              </tileset>
+              <layer id="1" name="ground" width="4" height="2">
+                <data encoding="csv">
+            1,2,3,4,
+            5,6,7,8
+            </data>
+              </layer>
+              <layer id="2" name="props" width="4" height="2" opacity="0.5">
+                <data encoding="base64">AAAAAGUAAAAAAAAAAAAAAAAAAAAJAACAAAAAAAAAAAA=</data>
+              </layer>
+              <objectgroup id="3" name="spawns"/>
+            </map>"#;
+        let tsx = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <tileset version="1.5" name="terrain" tilewidth="16" tileheight="16"
+                     spacing="1" margin="2" tilecount="16" columns="4">
+              <image source="terrain.png" width="71" height="71"/>
+            </tileset>"#;
+
+        let mut map = TmxMap::parse(tmx).unwrap();
+        assert_eq!(map.external_tilesets(), vec!["terrain.tsx".to_string()]);
+        assert!(map.tileset_image().is_err());
+        map.resolve_tileset("terrain.tsx", tsx).unwrap();
+        assert!(map.external_tilesets().is_empty());
+        assert_eq!(map.tileset_image().unwrap(), PathBuf::from("terrain.png"));
+
+        let texture_atlas = map
+            .texture_atlas(Handle::weak(HandleId::random::<Texture>()))
+            .unwrap();
+        assert_eq!(texture_atlas.len(), 16);
+        let rect = texture_atlas.textures.get(5).unwrap();
+        assert_eq!(rect.min, Vec2::new(19.0, 19.0));
+        assert_eq!(rect.max, Vec2::new(35.0, 35.0));
+
+        let mut tiled_map = map
+            .into_tiled_map(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .unwrap();
+        let tilemap = &mut tiled_map.tilemap;
+        assert_eq!(tilemap.topology(), GridTopology::Square);
+
+        // The top left tile of the map.
+        let tile = tilemap.get_tile((-2, 0), 0).unwrap();
+        assert_eq!(tile.index, 0);
+        // The bottom right tile of the map.
+        let tile = tilemap.get_tile((1, -1), 0).unwrap();
+        assert_eq!(tile.index, 7);
+        assert_eq!(tilemap.get_tile((2, 0), 0), None);
+
+        // The flipped tile still uses the first tileset.
+        let tile = tilemap.get_tile((-1, -1), 1).unwrap();
+        assert_eq!(
+            *tile,
+            RawTile {
+                index: 8,
+                color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                flip: TileFlip::HORIZONTAL,
+                rotation: 0,
+            }
+        );
+
+        let report = &tiled_map.report;
+        assert!(report
+            .unsupported
+            .contains(&Unsupported::ObjectGroup("spawns".to_string())));
+        assert!(report
+            .unsupported
+            .contains(&Unsupported::ImageCollection("props".to_string())));
+        assert!(report.unsupported.contains(&Unsupported::Tileset {
+            name: "props".to_string(),
+            skipped: 1,
+        }));
+    }
+
+    #[test]
+    fn hexagonal_topologies() {
+        let topologies = vec![
+            ("y", "odd", 4, GridTopology::HexEvenRows),
+            ("y", "even", 4, GridTopology::HexOddRows),
+            ("y", "odd", 3, GridTopology::HexOddRows),
+            ("x", "odd", 4, GridTopology::HexEvenCols),
+            ("x", "even", 4, GridTopology::HexOddCols),
+            ("x", "odd", 2, GridTopology::HexOddCols),
+        ];
+        for (axis, index, size, topology) in topologies.into_iter() {
+            let tmx = format!(
+                r#"<map orientation="hexagonal" width="{size}" height="{size}"
+                        tilewidth="32" tileheight="32" hexsidelength="16"
+                        staggeraxis="{axis}" staggerindex="{index}">
+                     <layer name="ground" width="{size}" height="{size}">
+                       <data><tile gid="1"/></data>
+                     </layer>
+                   </map>"#,
+                size = size,
+                axis = axis,
+                index = index,
+            );
+            let map = TmxMap::parse(&tmx).unwrap();
+            assert_eq!(map.topology, topology, "{} {} {}", axis, index, size);
+            assert!(map.report.is_empty());
+        }
+    }
+
+    #[test]
+    fn orientations() {
+        let h = FLIPPED_HORIZONTALLY;
+        let v = FLIPPED_VERTICALLY;
+        let d = FLIPPED_DIAGONALLY;
+        let orientations = vec![
+            (0, TileFlip::NONE, 0),
+            (h, TileFlip::HORIZONTAL, 0),
+            (v, TileFlip::VERTICAL, 0),
+            (h | v, TileFlip::HORIZONTAL | TileFlip::VERTICAL, 0),
+            (d, TileFlip::HORIZONTAL, 1),
+            (d | h, TileFlip::NONE, 3),
+            (d | v, TileFlip::NONE, 1),
+            (d | h | v, TileFlip::HORIZONTAL, 3),
+        ];
+        for (flags, flip, rotation) in orientations.into_iter() {
+            assert_eq!(orientation(flags | 1, false), (flip, rotation));
+        }
+
+        assert_eq!(orientation(d | 1, true), (TileFlip::NONE, 5));
+        assert_eq!(
+            orientation(ROTATED_HEXAGONAL_120 | h, true),
+            (TileFlip::HORIZONTAL, 2)
+        );
+    }
+
+    #[test]
+    fn base64() {
+        assert_eq!(decode_base64("TWFu"), Some(b"Man".to_vec()));
+        assert_eq!(decode_base64("TWE=\n"), Some(b"Ma".to_vec()));
+        assert_eq!(decode_base64("TQ=="), Some(b"M".to_vec()));
+        assert_eq!(decode_base64("T*=="), None);
+    }
+}
@@ -94,11 +94,22 @@
 //! ```
 
 use crate::{
-    chunk::{mesh::ChunkMesh, Chunk, LayerKind, RawTile},
-    event::TilemapChunkEvent,
+    animation::{AnimatedChunks, TileAnimation, TileAnimations},
+    autotile::{AutotileRules, Autotiles},
+    chunk::{mesh::ChunkMesh, task::PendingChunks, Chunk, LayerKind, RawTile, TileDataTypes},
+    event::{TilemapChunkEvent, TilemapEvent},
+    fog::{FogOfWar, FogState},
+    fov,
+    generator::{self, ChunkGenerator},
+    history::{Edit, History},
     lib::*,
+    pathfinding::{self, Connectivity, DijkstraMap, Path, TileCosts},
     prelude::GridTopology,
-    tile::Tile,
+    shape::Shape,
+    stamp::{StampTile, StampTransform, TileStamp},
+    storage::{ChunkStorage, StoringChunks},
+    system::topology_translation,
+    tile::{Tile, TileData},
     Component, Reflect, ReflectComponent,
 };
 
@@ -119,6 +130,33 @@ pub enum ErrorKind {
     MissingChunk,
     /// The chunk already exists.
     ChunkAlreadyExists(Point2),
+    /// The terrain does not exist.
+    TerrainDoesNotExist(u32),
+    /// The rules of the terrain do not fit the topology or the amount of
+    /// sprites is wrong.
+    InvalidTerrain(u32),
+    /// The tilemap was built without fog of war.
+    MissingFogOfWar,
+    /// If a data layer already exists this error is returned.
+    DataLayerExists(usize),
+    /// If a data layer does not already exist this error is returned.
+    DataLayerDoesNotExist(usize),
+    /// A transaction was begun and not yet committed.
+    TransactionInProgress,
+    /// No transaction was begun.
+    NoTransaction,
+    /// Stamps can only be rotated and flipped on square topologies.
+    UnsupportedStampTransform,
+    /// The tilemap has no chunk storage.
+    MissingChunkStorage,
+    /// The chunk is spawned or being built and can not be unloaded.
+    ChunkInUse(Point2),
+    /// The chunk could not be stored, loaded or removed from the chunk
+    /// storage.
+    ChunkStorage(Point2, String),
+    /// The chunks of a hex topology with staggered rows or columns have an
+    /// odd amount of them.
+    OddStaggeredChunks,
 }
 
 impl Display for ErrorKind {
@@ -145,6 +183,51 @@ impl Display for ErrorKind {
                 "the chunk {} already exists, if this was intentional run `remove_chunk` first",
                 p
             ),
+            TerrainDoesNotExist(n) => {
+                write!(f, "terrain {} does not exist, try `add_terrain` first", n)
+            }
+            InvalidTerrain(n) => write!(
+                f,
+                "the rules of terrain {} do not fit the topology or have the wrong amount of sprites",
+                n
+            ),
+            MissingFogOfWar => write!(
+                f,
+                "the tilemap has no fog of war, try `TilemapBuilder::fog_of_war` first"
+            ),
+            DataLayerExists(n) => write!(
+                f,
+                "data layer {} already exists, try `remove_data_layer` first",
+                n
+            ),
+            DataLayerDoesNotExist(n) => write!(
+                f,
+                "data layer {} does not exist, try `add_data_layer` first",
+                n
+            ),
+            TransactionInProgress => write!(
+                f,
+                "a transaction is in progress, try `commit_transaction` first"
+            ),
+            NoTransaction => write!(f, "no transaction was begun, try `begin_transaction` first"),
+            UnsupportedStampTransform => write!(
+                f,
+                "stamps can only be rotated and flipped on square topologies"
+            ),
+            MissingChunkStorage => write!(
+                f,
+                "the tilemap has no chunk storage, try `set_chunk_storage` first"
+            ),
+            ChunkInUse(p) => write!(
+                f,
+                "the chunk {} is spawned, try `despawn_chunk` first",
+                p
+            ),
+            ChunkStorage(p, err) => write!(f, "the chunk storage failed for chunk {}: {}", p, err),
+            OddStaggeredChunks => write!(
+                f,
+                "chunks of staggered hex topologies need an even amount of rows or columns"
+            ),
         }
     }
 }
@@ -182,6 +265,10 @@ impl From<DimensionError> for TilemapError {
 /// A map result.
 pub type TilemapResult<T> = Result<T, TilemapError>;
 
+/// A tile that changed as the chunk point, the tile point in the chunk, the
+/// sprite order and the raw tile before and after.
+type TileChange = (Point2, Point3, usize, Option<RawTile>, Option<RawTile>);
+
 bitflags! {
     #[derive(Serialize, Deserialize, Reflect)]
     struct AutoFlags: u16 {
@@ -201,6 +288,11 @@ const DEFAULT_TILE_SCALE: (f32, f32, f32) = (1.0, 1.0, 1.0);
 /// The default z layers.
 const DEFAULT_Z_LAYERS: usize = 5;
 
+/// The default tile scale of a deserialized tilemap.
+fn default_tile_scale() -> Vec3 {
+    DEFAULT_TILE_SCALE.into()
+}
+
 impl Default for AutoFlags {
     fn default() -> Self {
         AutoFlags::AUTO_CONFIGURE & AutoFlags::AUTO_CHUNK
@@ -239,6 +331,9 @@ pub struct Tilemap {
     chunk_mesh: ChunkMesh,
     /// A tiles dimensions in pixels.
     texture_dimensions: Dimension2,
+    /// The scale of a tile.
+    #[serde(default = "default_tile_scale")]
+    tile_scale: Vec3,
     /// The layers that are currently set in the tilemap in order from lowest
     /// to highest.
     layers: Vec<Option<TilemapLayer>>,
@@ -248,6 +343,46 @@ pub struct Tilemap {
     auto_spawn: Option<Dimension2>,
     /// Custom flags.
     custom_flags: Vec<u32>,
+    /// The animations of sprite indices.
+    #[serde(default)]
+    animations: TileAnimations,
+    #[serde(skip)]
+    /// The sprite indices of the chunk meshes before they are animated.
+    animated_chunks: AnimatedChunks,
+    /// The terrains and where they are painted.
+    #[serde(default)]
+    autotiles: Autotiles,
+    /// The fog of war, if there is any.
+    #[serde(default)]
+    fog_of_war: Option<FogOfWar>,
+    /// The kinds of the data layers.
+    #[serde(default)]
+    data_layers: HashMap<usize, LayerKind>,
+    /// True if events are sent for every tile that changes.
+    #[serde(default)]
+    tile_events: bool,
+    /// True if chunks are generated and built on the async compute task pool.
+    #[serde(default)]
+    async_chunks: bool,
+    /// The most chunks that are kept in memory when there is a chunk storage.
+    #[serde(default)]
+    max_resident_chunks: usize,
+    /// The transactions of edits that can be undone and redone.
+    #[serde(default)]
+    history: History,
+    #[serde(skip)]
+    /// The tiles that were borrowed mutably with their raw tile before, to
+    /// send events for the ones that changed.
+    tile_writes: Vec<(Point3, usize, RawTile)>,
+    #[serde(skip)]
+    /// The points that were visible when the fog of war was last lifted, or
+    /// `None` until they are found from the chunks again, such as after the
+    /// tilemap was deserialized.
+    fog_visible: Option<HashSet<Point3>>,
+    #[serde(skip)]
+    /// The spawned chunks of which the fog was set since it was last sent to
+    /// their meshes.
+    fog_changed: HashSet<Point2>,
     #[serde(skip)]
     /// The handle of the texture atlas.
     texture_atlas: Handle<TextureAtlas>,
@@ -256,6 +391,27 @@ pub struct Tilemap {
     #[serde(skip)]
     /// The events of the tilemap.
     chunk_events: Events<TilemapChunkEvent>,
+    #[serde(skip)]
+    /// The events of the tilemap data.
+    events: Events<TilemapEvent>,
+    #[serde(skip)]
+    /// Generates the chunks that are spawned before they exist.
+    chunk_generator: Option<Arc<dyn ChunkGenerator>>,
+    #[serde(skip)]
+    /// The chunks that are being built to be spawned.
+    pending_chunks: PendingChunks,
+    #[serde(skip)]
+    /// Stores the chunks that are unloaded.
+    chunk_storage: Option<Arc<dyn ChunkStorage>>,
+    #[serde(skip)]
+    /// The despawned chunks that can be unloaded, the longest despawned first.
+    unloadable_chunks: VecDeque<Point2>,
+    #[serde(skip)]
+    /// The unloaded chunks that are being written to the chunk storage.
+    storing_chunks: StoringChunks,
+    #[serde(skip)]
+    /// The tile data types that deserialized values are given back.
+    tile_data_types: TileDataTypes,
     /// A set of all spawned chunks.
     spawned: HashSet<(i32, i32)>,
 }
@@ -279,12 +435,21 @@ pub struct Tilemap {
 /// can exist on. Default is 20.
 /// - [`texture_atlas`]: specifies the texture atlas handle
 /// to use for the tilemap.
-/// - [`tile_scale`]: sets the tile scale in pixels.
+/// - [`tile_scale`]: sets the scale of the tiles.
 /// - [`add_layer`]: adds a layer to the tilemap.
 /// - [`auto_chunk`]: set if you want the tilemap to automatically spawn new
 /// chunks.
 /// - [`auto_spawn`]: set if you want the tilemap to automatically spawn and
 /// despawn chunks.
+/// - [`add_animation`]: adds an animation for a sprite index.
+/// - [`add_terrain`]: adds a terrain to paint with autotiling.
+/// - [`fog_of_war`]: sets the tilemap to hide what observers have not seen.
+/// - [`add_data_layer`]: adds a layer of data values that is never drawn.
+/// - [`tile_events`]: set if you want an event for every tile that changes.
+/// - [`async_chunks`]: set if you want chunks to be built on a task pool.
+/// - [`max_resident_chunks`]: sets how many chunks are kept in memory with a
+/// chunk storage.
+/// - [`history_len`]: sets how many transactions can be undone.
 ///
 /// The [`finish`] method will take ownership and consume the builder returning
 /// a [`TilemapResult`] with either an [`TilemapError`] or the [tilemap].
@@ -327,6 +492,14 @@ pub struct Tilemap {
 /// [`add_layer`]: TilemapBuilder::add_layer
 /// [`auto_chunk`]: TilemapBuilder::auto_chunk
 /// [`auto_spawn`]: TilemapBuilder::auto_spawn
+/// [`add_animation`]: TilemapBuilder::add_animation
+/// [`add_terrain`]: TilemapBuilder::add_terrain
+/// [`fog_of_war`]: TilemapBuilder::fog_of_war
+/// [`add_data_layer`]: TilemapBuilder::add_data_layer
+/// [`tile_events`]: TilemapBuilder::tile_events
+/// [`async_chunks`]: TilemapBuilder::async_chunks
+/// [`max_resident_chunks`]: TilemapBuilder::max_resident_chunks
+/// [`history_len`]: TilemapBuilder::history_len
 /// [tilemap]: Tilemap
 /// [`TilemapError`]: TilemapError
 /// [`TilemapResult`]: TilemapResult
@@ -357,6 +530,22 @@ pub struct TilemapBuilder {
     auto_flags: AutoFlags,
     /// The radius of chunks to spawn from a camera's transform.
     auto_spawn: Option<Dimension2>,
+    /// The animations of sprite indices.
+    animations: TileAnimations,
+    /// The terrains to paint with autotiling.
+    autotiles: Autotiles,
+    /// The fog of war, if there is any.
+    fog_of_war: Option<FogOfWar>,
+    /// The kinds of the data layers to be set.
+    data_layers: HashMap<usize, LayerKind>,
+    /// True if events are sent for every tile that changes.
+    tile_events: bool,
+    /// True if chunks are generated and built on the async compute task pool.
+    async_chunks: bool,
+    /// The most chunks that are kept in memory when there is a chunk storage.
+    max_resident_chunks: usize,
+    /// The most transactions that are kept to be undone.
+    history_len: Option<usize>,
 }
 
 impl Default for TilemapBuilder {
@@ -384,6 +573,14 @@ impl Default for TilemapBuilder {
             render_depth: 0,
             auto_flags: AutoFlags::NONE,
             auto_spawn: None,
+            animations: Default::default(),
+            autotiles: Default::default(),
+            fog_of_war: None,
+            data_layers: Default::default(),
+            tile_events: false,
+            async_chunks: false,
+            max_resident_chunks: 0,
+            history_len: None,
         }
     }
 }
@@ -451,6 +648,10 @@ impl TilemapBuilder {
     /// Chunk dimensions are in tiles. If this is not set then the default of
     /// 32x, 32y is used.
     ///
+    /// The rows of the even and odd row hex topologies and the columns of the
+    /// even and odd column hex topologies are staggered across chunks, so
+    /// their chunks need an even height or width.
+    ///
     /// # Examples
     /// ```
     /// use bevy_tilemap::prelude::*;
@@ -497,14 +698,16 @@ impl TilemapBuilder {
 
     /// Sets the tile scale.
     ///
-    /// By default this is the size of the texture for width and height which
-    /// means that a tile width of 1 is the width in texture pixels for example.
+    /// By default this is 1.0 for width, height and depth which means that a
+    /// tile is rendered with the same width and height as its texture in
+    /// pixels. A scale of 2.0 doubles the size of every tile, and the chunks
+    /// are spaced out accordingly.
     ///
     /// # Examples
     /// ```
     /// use bevy_tilemap::prelude::*;
     ///
-    /// let builder = TilemapBuilder::new().tile_scale(32.0, 32.0, 32.0);
+    /// let builder = TilemapBuilder::new().tile_scale(2.0, 2.0, 1.0);
     /// ```
     pub fn tile_scale(mut self, width: f32, height: f32, depth: f32) -> TilemapBuilder {
         self.tile_scale = Vec3::new(width, height, depth);
@@ -612,14 +815,147 @@ impl TilemapBuilder {
         self
     }
 
+    /// Adds an animation for a sprite index. Every tile with the sprite index
+    /// is drawn with the current frame of the animation.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let torch = TileAnimation::from_indices(vec![8, 9], 0.25, AnimationMode::Loop);
+    ///
+    /// let builder = TilemapBuilder::new().add_animation(8, torch);
+    /// ```
+    pub fn add_animation(mut self, sprite_index: usize, animation: TileAnimation) -> Self {
+        self.animations.insert(sprite_index, animation);
+        self
+    }
+
+    /// Adds a terrain with the rules that choose its sprites, which can then
+    /// be painted with [`Tilemap::insert_terrain`].
+    ///
+    /// The rules are checked against the topology when the builder finishes.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let water = AutotileRules::sequential(AutotileKind::Blob, 64);
+    ///
+    /// let builder = TilemapBuilder::new().add_terrain(1, water);
+    /// ```
+    pub fn add_terrain(mut self, terrain: u32, rules: AutotileRules) -> Self {
+        self.autotiles.insert(terrain, rules);
+        self
+    }
+
+    /// Sets the tilemap to have fog of war, which hides the tiles until an
+    /// entity with a [`FogObserver`] sees them.
+    ///
+    /// [`FogObserver`]: crate::fog::FogObserver
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::{fog::FogOfWar, prelude::*};
+    ///
+    /// let builder = TilemapBuilder::new().fog_of_war(FogOfWar::default());
+    /// ```
+    pub fn fog_of_war(mut self, fog_of_war: FogOfWar) -> Self {
+        self.fog_of_war = Some(fog_of_war);
+        self
+    }
+
+    /// Adds a data layer with values for tiles that are never drawn, such as
+    /// collision or movement costs.
+    ///
+    /// Data layers are separate from sprite layers. In the case that a data
+    /// layer is added twice, the first kind will be overwritten by the latter.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new()
+    ///     .add_data_layer(LayerKind::Dense, 0)
+    ///     .add_data_layer(LayerKind::Sparse, 1);
+    /// ```
+    pub fn add_data_layer(mut self, kind: LayerKind, data_layer: usize) -> Self {
+        self.data_layers.insert(data_layer, kind);
+        self
+    }
+
+    /// Sets the tilemap to send an event for every tile that changes.
+    ///
+    /// This is off by default as it costs a lookup for every changed tile.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().tile_events();
+    /// ```
+    pub fn tile_events(mut self) -> Self {
+        self.tile_events = true;
+        self
+    }
+
+    /// Sets the tilemap to generate chunks and build their meshes on the
+    /// async compute task pool.
+    ///
+    /// Chunks are then spawned a frame or more after they are asked to, once
+    /// they are built.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().async_chunks();
+    /// ```
+    pub fn async_chunks(mut self) -> Self {
+        self.async_chunks = true;
+        self
+    }
+
+    /// Sets the most chunks that are kept in memory when the tilemap has a
+    /// chunk storage. Default is 0.
+    ///
+    /// Spawned chunks always stay in memory, despawned chunks are unloaded
+    /// to the storage the longest despawned first.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().max_resident_chunks(64);
+    /// ```
+    pub fn max_resident_chunks(mut self, max_resident_chunks: usize) -> Self {
+        self.max_resident_chunks = max_resident_chunks;
+        self
+    }
+
+    /// Sets the most transactions that are kept to be undone. Default is 100.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// let builder = TilemapBuilder::new().history_len(20);
+    /// ```
+    pub fn history_len(mut self, max_len: usize) -> Self {
+        self.history_len = Some(max_len);
+        self
+    }
+
     /// Consumes the builder and returns a result.
     ///
     /// If successful a [`TilemapResult`] is return with [tilemap] on
     /// succes or a [`TilemapError`] if there is an issue.
     ///
     /// # Errors
-    /// If a texture atlas is not set this is the only way that an error can
-    /// occur. If this happens, be sure to use [`texture_atlas`].
+    /// If a texture atlas or the texture dimensions are not set, be sure to
+    /// use [`texture_atlas`]. If the rules of a terrain do not fit the
+    /// topology, or if the chunks of a hex topology with staggered rows or
+    /// columns have an odd amount of them.
     ///
     /// # Examples
     /// ```
@@ -650,6 +986,22 @@ impl TilemapBuilder {
         } else {
             return Err(ErrorKind::MissingTextureDimensions.into());
         };
+        let staggered = match self.topology {
+            GridTopology::HexEvenRows | GridTopology::HexOddRows => self.chunk_dimensions.height,
+            GridTopology::HexEvenCols | GridTopology::HexOddCols => self.chunk_dimensions.width,
+            _ => 0,
+        };
+        if staggered % 2 == 1 {
+            return Err(ErrorKind::OddStaggeredChunks.into());
+        }
+        let hex = self.topology != GridTopology::Square;
+        if let Some((terrain, _)) = self
+            .autotiles
+            .iter()
+            .find(|(_, rules)| !rules.is_valid(hex))
+        {
+            return Err(ErrorKind::InvalidTerrain(*terrain).into());
+        }
 
         let z_layers = if let Some(layers) = &self.layers {
             if self.z_layers > layers.len() {
@@ -688,13 +1040,33 @@ impl TilemapBuilder {
             layer_offset: self.layer_offset,
             chunk_mesh,
             texture_dimensions,
+            tile_scale: self.tile_scale,
             layers,
             auto_flags: self.auto_flags,
             auto_spawn: self.auto_spawn,
             custom_flags: Vec::new(),
+            animations: self.animations,
+            autotiles: self.autotiles,
+            fog_of_war: self.fog_of_war,
+            data_layers: self.data_layers,
+            tile_events: self.tile_events,
+            async_chunks: self.async_chunks,
+            max_resident_chunks: self.max_resident_chunks,
+            history: self.history_len.map(History::new).unwrap_or_default(),
+            tile_writes: Vec::new(),
+            fog_visible: Default::default(),
+            fog_changed: Default::default(),
             texture_atlas,
             chunks: Default::default(),
             chunk_events: Default::default(),
+            events: Default::default(),
+            chunk_generator: None,
+            pending_chunks: Default::default(),
+            chunk_storage: None,
+            unloadable_chunks: Default::default(),
+            storing_chunks: Default::default(),
+            tile_data_types: Default::default(),
+            animated_chunks: Default::default(),
             spawned: Default::default(),
         })
     }
@@ -713,6 +1085,7 @@ impl Default for Tilemap {
             layer_offset: Vec2::default(),
             chunk_mesh: ChunkMesh::default(),
             texture_dimensions: DEFAULT_TEXTURE_DIMENSIONS,
+            tile_scale: DEFAULT_TILE_SCALE.into(),
             layers: vec![
                 Some(TilemapLayer {
                     kind: LayerKind::Sparse,
@@ -725,9 +1098,28 @@ impl Default for Tilemap {
             auto_flags: AutoFlags::NONE,
             auto_spawn: None,
             custom_flags: Vec::new(),
+            animations: Default::default(),
+            autotiles: Default::default(),
+            fog_of_war: None,
+            data_layers: Default::default(),
+            tile_events: false,
+            async_chunks: false,
+            max_resident_chunks: 0,
+            history: History::default(),
+            tile_writes: Vec::new(),
+            fog_visible: Default::default(),
+            fog_changed: Default::default(),
             texture_atlas: Handle::default(),
             chunks: Default::default(),
             chunk_events: Default::default(),
+            events: Default::default(),
+            chunk_generator: None,
+            pending_chunks: Default::default(),
+            chunk_storage: None,
+            unloadable_chunks: Default::default(),
+            storing_chunks: Default::default(),
+            tile_data_types: Default::default(),
+            animated_chunks: Default::default(),
             spawned: Default::default(),
         }
     }
@@ -834,6 +1226,143 @@ impl Tilemap {
         &self.texture_atlas
     }
 
+    /// Adds an animation for a sprite index, replacing any previous
+    /// animation of the sprite index.
+    ///
+    /// Every tile with the sprite index is drawn with the current frame of
+    /// the animation, starting with the next update.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let water = TileAnimation::from_indices(vec![4, 5, 6], 0.2, AnimationMode::PingPong);
+    /// tilemap.add_animation(4, water);
+    ///
+    /// assert!(tilemap.animation(4).is_some());
+    /// ```
+    pub fn add_animation(&mut self, sprite_index: usize, animation: TileAnimation) {
+        self.animations.insert(sprite_index, animation);
+        self.animated_chunks.clear();
+    }
+
+    /// Removes the animation of a sprite index and returns it, if it had one.
+    ///
+    /// The tiles with the sprite index are drawn with it again, starting with
+    /// the next update.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let water = TileAnimation::from_indices(vec![4, 5, 6], 0.2, AnimationMode::PingPong);
+    /// tilemap.add_animation(4, water.clone());
+    ///
+    /// assert_eq!(tilemap.remove_animation(4), Some(water));
+    /// assert_eq!(tilemap.remove_animation(4), None);
+    /// ```
+    pub fn remove_animation(&mut self, sprite_index: usize) -> Option<TileAnimation> {
+        self.animated_chunks.clear();
+        self.animations.remove(sprite_index)
+    }
+
+    /// Returns a reference to the animation of a sprite index, if it has one.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert!(tilemap.animation(4).is_none());
+    /// ```
+    pub fn animation(&self, sprite_index: usize) -> Option<&TileAnimation> {
+        self.animations.get(sprite_index)
+    }
+
+    /// Adds a terrain with the rules that choose its sprites, replacing any
+    /// previous rules of the terrain.
+    ///
+    /// The tiles already painted with the terrain are chosen again with the
+    /// new rules.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let sand = AutotileRules::sequential(AutotileKind::Bitmask, 0);
+    /// assert!(tilemap.add_terrain(2, sand).is_ok());
+    ///
+    /// // Hex rules do not fit a square topology.
+    /// let hills = AutotileRules::sequential(AutotileKind::Hex, 16);
+    /// assert!(tilemap.add_terrain(3, hills).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rules do not fit the topology of the tilemap or
+    /// do not have the amount of sprites their kind needs.
+    pub fn add_terrain(&mut self, terrain: u32, rules: AutotileRules) -> TilemapResult<()> {
+        if !rules.is_valid(self.topology != GridTopology::Square) {
+            return Err(ErrorKind::InvalidTerrain(terrain).into());
+        }
+        self.autotiles.insert(terrain, rules);
+        let painted = self.autotiles.painted_with(terrain);
+        self.update_autotiles(painted)
+    }
+
+    /// Removes a terrain and returns its rules, if it existed.
+    ///
+    /// The tiles painted with the terrain keep their sprites, but are no
+    /// longer autotiled.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let sand = AutotileRules::sequential(AutotileKind::Bitmask, 0);
+    /// tilemap.add_terrain(2, sand.clone()).unwrap();
+    ///
+    /// assert_eq!(tilemap.remove_terrain(2), Some(sand));
+    /// assert_eq!(tilemap.remove_terrain(2), None);
+    /// ```
+    pub fn remove_terrain(&mut self, terrain: u32) -> Option<AutotileRules> {
+        self.autotiles.remove(terrain)
+    }
+
     /// Constructs a new chunk and stores it at a coordinate position.
     ///
     /// It requires that you give it a point. It then automatically sets
@@ -874,16 +1403,22 @@ impl Tilemap {
     /// and was intentional, it is best to remove the chunk first. This is
     /// simply a fail safe without actually returning the chunk as it is meant
     /// to be kept internal.
+    ///
+    /// If the chunk was unloaded to the chunk storage it is loaded instead,
+    /// or if it could not be loaded an error is returned.
     pub fn insert_chunk<P: Into<Point2>>(&mut self, point: P) -> TilemapResult<()> {
         let point: Point2 = point.into();
         if let Some(dimensions) = &self.dimensions {
             dimensions.check_point(point)?;
         }
-        let layer_kinds = self
-            .layers
-            .iter()
-            .map(|x| x.and_then(|y| Some(y.kind)))
-            .collect::<Vec<Option<LayerKind>>>();
+        if self.chunks.contains_key(&point) {
+            return Err(ErrorKind::ChunkAlreadyExists(point).into());
+        }
+        self.load_chunk(point)?;
+        if self.chunks.contains_key(&point) {
+            return Ok(());
+        }
+        let layer_kinds = self.layer_kinds();
         let chunk = Chunk::new(point, &layer_kinds, self.chunk_dimensions);
         match self.chunks.insert(point, chunk) {
             Some(_) => Err(ErrorKind::ChunkAlreadyExists(point).into()),
@@ -913,40 +1448,292 @@ impl Tilemap {
         self.chunks.contains_key(&point)
     }
 
-    #[deprecated(
-        since = "0.4.0",
-        note = "Please use `add_layer` method instead with the `TilemapLayer` struct"
-    )]
-    #[doc(hidden)]
-    pub fn add_layer_with_kind(
-        &mut self,
-        kind: LayerKind,
-        sprite_order: usize,
-    ) -> TilemapResult<()> {
-        let layer = TilemapLayer { kind };
-        if let Some(some_kind) = self.layers.get_mut(sprite_order) {
-            if some_kind.is_some() {
-                return Err(ErrorKind::LayerExists(sprite_order).into());
-            }
-            *some_kind = Some(layer);
-        }
-
-        for chunk in self.chunks.values_mut() {
-            chunk.add_sprite_layer(&kind, sprite_order, self.chunk_dimensions);
-        }
-
-        Ok(())
-    }
-
-    /// Adds a layer to the tilemap.
-    ///
-    /// ***Warning:*** This is very unwise and costly if there are many chunks
-    /// in the tilemap. You should only add layers when creating the tilemap.
-    /// The meshes for every single chunk has to be recalculated!
+    /// Returns the user data of a chunk, or `None` if the chunk does not
+    /// exist.
     ///
-    /// This method creates a layer across all chunks at the specified Z layer.
-    /// For ease of use, it by default makes a layer with a dense
-    /// [`LayerKind`] which is ideal for layers full of sprites.
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// assert_eq!(tilemap.chunk_user_data((0, 0)), Some(0));
+    /// assert_eq!(tilemap.chunk_user_data((1, 1)), None);
+    /// ```
+    pub fn chunk_user_data<P: Into<Point2>>(&self, point: P) -> Option<u128> {
+        let point: Point2 = point.into();
+        self.chunks.get(&point).map(Chunk::user_data)
+    }
+
+    /// Sets the user data of a chunk, which can be used for flags such as
+    /// whether the chunk was generated.
+    ///
+    /// A [`TilemapEvent::ChunkUserDataChanged`] is sent if the user data
+    /// changed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the chunk does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// const GENERATED: u128 = 0b01;
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// assert!(tilemap.set_chunk_user_data((0, 0), GENERATED).is_ok());
+    /// assert!(tilemap.set_chunk_user_data((1, 1), GENERATED).is_err());
+    /// assert_eq!(tilemap.chunk_user_data((0, 0)), Some(GENERATED));
+    /// ```
+    ///
+    /// [`TilemapEvent::ChunkUserDataChanged`]: crate::event::TilemapEvent::ChunkUserDataChanged
+    pub fn set_chunk_user_data<P: Into<Point2>>(
+        &mut self,
+        point: P,
+        user_data: u128,
+    ) -> TilemapResult<()> {
+        let point: Point2 = point.into();
+        let chunk = match self.chunks.get_mut(&point) {
+            Some(chunk) => chunk,
+            None => return Err(ErrorKind::MissingChunk.into()),
+        };
+        if chunk.user_data() != user_data {
+            chunk.set_user_data(user_data);
+            self.events
+                .send(TilemapEvent::ChunkUserDataChanged { point });
+        }
+        Ok(())
+    }
+
+    /// Attaches a typed metadata value to a chunk, replacing the value of the
+    /// same type.
+    ///
+    /// A chunk can have a value of every type, they are saved with the chunk
+    /// and removed with it. A [`TilemapEvent::ChunkMetadataChanged`] is sent.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the chunk does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "3406d13b-06ab-4b24-8ed1-77481ddd7ece"]
+    /// struct Biome {
+    ///     name: String,
+    /// }
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let desert = Biome { name: "desert".to_string() };
+    /// assert!(tilemap.insert_chunk_metadata((0, 0), desert).is_ok());
+    /// assert_eq!(
+    ///     tilemap.get_chunk_metadata::<_, Biome>((0, 0)).map(|biome| biome.name.as_str()),
+    ///     Some("desert")
+    /// );
+    /// ```
+    ///
+    /// [`TilemapEvent::ChunkMetadataChanged`]: crate::event::TilemapEvent::ChunkMetadataChanged
+    pub fn insert_chunk_metadata<P, T>(&mut self, point: P, value: T) -> TilemapResult<()>
+    where
+        P: Into<Point2>,
+        T: TileData,
+    {
+        let point: Point2 = point.into();
+        if !self.chunks.contains_key(&point) {
+            return Err(ErrorKind::MissingChunk.into());
+        }
+        self.register_tile_data::<T>();
+        if let Some(chunk) = self.chunks.get_mut(&point) {
+            chunk.metadata_mut().insert(value);
+        }
+        self.events
+            .send(TilemapEvent::ChunkMetadataChanged { point });
+        Ok(())
+    }
+
+    /// Gets a reference to the typed metadata value of a chunk.
+    ///
+    /// Values that were deserialized are only found once their type is
+    /// registered with [`register_tile_data`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "4fcc6c82-feec-4549-bd2c-e88ecd0b40f2"]
+    /// struct DirtyOnDisk(bool);
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_chunk_metadata((0, 0), DirtyOnDisk(true)).unwrap();
+    ///
+    /// assert_eq!(tilemap.get_chunk_metadata((0, 0)), Some(&DirtyOnDisk(true)));
+    /// assert_eq!(tilemap.get_chunk_metadata::<_, DirtyOnDisk>((1, 1)), None);
+    /// ```
+    ///
+    /// [`register_tile_data`]: Tilemap::register_tile_data
+    pub fn get_chunk_metadata<P, T>(&self, point: P) -> Option<&T>
+    where
+        P: Into<Point2>,
+        T: TileData,
+    {
+        let point: Point2 = point.into();
+        self.chunks.get(&point)?.metadata().get()
+    }
+
+    /// Gets a mutable reference to the typed metadata value of a chunk.
+    ///
+    /// No [`TilemapEvent::ChunkMetadataChanged`] is sent, as the value may not
+    /// be changed through the reference. Insert the value again to send one.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "4fcc6c82-feec-4549-bd2c-e88ecd0b40f2"]
+    /// struct DirtyOnDisk(bool);
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_chunk_metadata((0, 0), DirtyOnDisk(true)).unwrap();
+    ///
+    /// if let Some(dirty) = tilemap.get_chunk_metadata_mut::<_, DirtyOnDisk>((0, 0)) {
+    ///     dirty.0 = false;
+    /// }
+    /// assert_eq!(tilemap.get_chunk_metadata((0, 0)), Some(&DirtyOnDisk(false)));
+    /// ```
+    ///
+    /// [`TilemapEvent::ChunkMetadataChanged`]: crate::event::TilemapEvent::ChunkMetadataChanged
+    pub fn get_chunk_metadata_mut<P, T>(&mut self, point: P) -> Option<&mut T>
+    where
+        P: Into<Point2>,
+        T: TileData,
+    {
+        let point: Point2 = point.into();
+        self.chunks.get_mut(&point)?.metadata_mut().get_mut()
+    }
+
+    /// Removes the typed metadata value of a chunk and returns it.
+    ///
+    /// A [`TilemapEvent::ChunkMetadataChanged`] is sent if there was a value.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "4fcc6c82-feec-4549-bd2c-e88ecd0b40f2"]
+    /// struct DirtyOnDisk(bool);
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_chunk_metadata((0, 0), DirtyOnDisk(true)).unwrap();
+    ///
+    /// assert_eq!(tilemap.remove_chunk_metadata((0, 0)), Some(DirtyOnDisk(true)));
+    /// assert_eq!(tilemap.get_chunk_metadata::<_, DirtyOnDisk>((0, 0)), None);
+    /// ```
+    ///
+    /// [`TilemapEvent::ChunkMetadataChanged`]: crate::event::TilemapEvent::ChunkMetadataChanged
+    pub fn remove_chunk_metadata<P, T>(&mut self, point: P) -> Option<T>
+    where
+        P: Into<Point2>,
+        T: TileData,
+    {
+        let point: Point2 = point.into();
+        let value = self.chunks.get_mut(&point)?.metadata_mut().remove()?;
+        self.events
+            .send(TilemapEvent::ChunkMetadataChanged { point });
+        Some(value)
+    }
+
+    #[deprecated(
+        since = "0.4.0",
+        note = "Please use `add_layer` method instead with the `TilemapLayer` struct"
+    )]
+    #[doc(hidden)]
+    pub fn add_layer_with_kind(
+        &mut self,
+        kind: LayerKind,
+        sprite_order: usize,
+    ) -> TilemapResult<()> {
+        let layer = TilemapLayer { kind };
+        if let Some(some_kind) = self.layers.get_mut(sprite_order) {
+            if some_kind.is_some() {
+                return Err(ErrorKind::LayerExists(sprite_order).into());
+            }
+            *some_kind = Some(layer);
+        }
+
+        for chunk in self.chunks.values_mut() {
+            chunk.add_sprite_layer(&kind, sprite_order, self.chunk_dimensions);
+        }
+
+        Ok(())
+    }
+
+    /// Adds a layer to the tilemap.
+    ///
+    /// ***Warning:*** This is very unwise and costly if there are many chunks
+    /// in the tilemap. You should only add layers when creating the tilemap.
+    /// The meshes for every single chunk has to be recalculated!
+    ///
+    /// This method creates a layer across all chunks at the specified Z layer.
+    /// For ease of use, it by default makes a layer with a dense
+    /// [`LayerKind`] which is ideal for layers full of sprites.
     ///
     /// If you want to use a layer that is more performant and less data heavy,
     /// use [`add_layer_with_kind`] with [`LayerKind::Sparse`].
@@ -1056,6 +1843,10 @@ impl Tilemap {
         for chunk in self.chunks.values_mut() {
             chunk.move_sprite_layer(from_sprite_order, to_sprite_order);
         }
+        self.history.record(Edit::MoveLayer {
+            from: from_sprite_order,
+            to: to_sprite_order,
+        });
 
         Ok(())
     }
@@ -1067,7 +1858,9 @@ impl Tilemap {
     /// method instead.
     ///
     /// This method takes in a Z layer which is then flagged for deletion. If
-    /// the layer already does not exist, it does nothing.
+    /// the layer already does not exist, it does nothing. The tile data of
+    /// the layer is removed with it. During a transaction the layer and its
+    /// tiles are recorded, so that removing it can be undone.
     ///
     /// # Examples
     /// ```
@@ -1087,24 +1880,145 @@ impl Tilemap {
     ///
     /// [`move_layer`]: Tilemap::move_layer
     pub fn remove_layer(&mut self, z: usize) {
+        let layer = match self.layers.get(z) {
+            Some(layer) => *layer,
+            None => return,
+        };
+        if let Some(layer) = layer.filter(|_| self.history.is_recording()) {
+            // The tiles are recorded first, so undoing restores them after
+            // the layer.
+            self.flush_tile_writes();
+            let width = self.chunk_dimensions.width as usize;
+            let height = self.chunk_dimensions.height as usize;
+            let mut changes = Vec::new();
+            for (chunk_point, chunk) in self.chunks.iter() {
+                for (index, sprite_order, z_depth) in chunk.tile_indices() {
+                    if sprite_order != z {
+                        continue;
+                    }
+                    let old = chunk.get_tile(index, sprite_order, z_depth).copied();
+                    let tile_point = Point3::new(
+                        (index % width) as i32,
+                        ((index / width) % height) as i32,
+                        z_depth as i32,
+                    );
+                    changes.push((*chunk_point, tile_point, old));
+                }
+            }
+            for (chunk_point, tile_point, old) in changes {
+                self.history.record(Edit::Tile {
+                    point: self.tile_point_to_point(chunk_point, tile_point),
+                    sprite_order: z,
+                    old,
+                    new: None,
+                });
+            }
+            self.history.record(Edit::RemoveLayer {
+                sprite_order: z,
+                layer,
+            });
+        }
         if let Some(layer) = self.layers.get_mut(z) {
             *layer = None;
-        } else {
-            return;
         }
 
         for chunk in self.chunks.values_mut() {
             chunk.remove_sprite_layer(z);
+            chunk.clear_layer_tile_data(z);
+        }
+    }
+
+    /// Adds a data layer with values for tiles that are never drawn.
+    ///
+    /// Each chunk only stores the layer once a value is set in it.
+    ///
+    /// # Errors
+    ///
+    /// If the data layer already exists an error is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert!(tilemap.add_data_layer(LayerKind::Dense, 0).is_ok());
+    /// assert!(tilemap.add_data_layer(LayerKind::Sparse, 0).is_err());
+    /// ```
+    pub fn add_data_layer(&mut self, kind: LayerKind, data_layer: usize) -> TilemapResult<()> {
+        if self.data_layers.contains_key(&data_layer) {
+            return Err(ErrorKind::DataLayerExists(data_layer).into());
+        }
+        self.data_layers.insert(data_layer, kind);
+        Ok(())
+    }
+
+    /// Removes a data layer from the tilemap and inner chunks.
+    ///
+    /// **Warning**: This is destructive, all values of the data layer are
+    /// removed. If the data layer does not exist, it does nothing. During a
+    /// transaction the data layer and its values are recorded, so that
+    /// removing it can be undone.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.add_data_layer(LayerKind::Sparse, 1).unwrap();
+    ///
+    /// tilemap.remove_data_layer(1);
+    /// ```
+    pub fn remove_data_layer(&mut self, data_layer: usize) {
+        let kind = match self.data_layers.remove(&data_layer) {
+            Some(kind) => kind,
+            None => return,
+        };
+        if self.history.is_recording() {
+            // The values are recorded first, so undoing restores them after
+            // the data layer.
+            for (chunk_point, chunk) in self.chunks.iter() {
+                for (index, value) in chunk.data_values(data_layer) {
+                    self.history.record(Edit::Data {
+                        chunk_point: *chunk_point,
+                        index,
+                        data_layer,
+                        old: Some(value),
+                        new: None,
+                    });
+                }
+            }
+            self.history
+                .record(Edit::RemoveDataLayer { data_layer, kind });
+        }
+
+        for chunk in self.chunks.values_mut() {
+            chunk.remove_data_layer(data_layer);
         }
     }
 
     /// Spawns a chunk at a given index or coordinate.
     ///
-    /// Does nothing if the chunk does not exist.
+    /// If the chunk was unloaded it is loaded from the [chunk storage]. If the
+    /// chunk does not exist it is generated by the [chunk generator], else
+    /// nothing happens. With [`async_chunks`] the chunk is generated and
+    /// built on the async compute task pool and spawned once it is done.
     ///
     /// # Errors
     ///
-    /// If the coordinate or index is out of bounds.
+    /// If the coordinate or index is out of bounds, or if the chunk could not
+    /// be loaded.
     ///
     /// # Examples
     /// ```
@@ -1131,12 +2045,28 @@ impl Tilemap {
     /// assert!(tilemap.spawn_chunk((1, 1)).is_err());
     /// assert!(tilemap.spawn_chunk((-1, -1)).is_err());
     /// ```
+    ///
+    /// [chunk storage]: Tilemap::set_chunk_storage
+    /// [chunk generator]: Tilemap::set_chunk_generator
+    /// [`async_chunks`]: Tilemap::set_async_chunks
     pub fn spawn_chunk<P: Into<Point2>>(&mut self, point: P) -> TilemapResult<()> {
         let point: Point2 = point.into();
         if let Some(dimensions) = &self.dimensions {
             dimensions.check_point(point)?;
         }
 
+        self.unloadable_chunks
+            .retain(|unloadable| *unloadable != point);
+        if !self.chunks.contains_key(&point) {
+            self.load_chunk(point)?;
+        }
+
+        if !self.async_chunks && !self.chunks.contains_key(&point) {
+            if let Some(generator) = self.chunk_generator.clone() {
+                self.generate_chunk(point, generator.as_ref())?;
+            }
+        }
+
         if self.spawned.contains(&(point.x, point.y)) {
             return Ok(());
         } else {
@@ -1187,11 +2117,15 @@ impl Tilemap {
 
     /// De-spawns a spawned chunk at a given index or coordinate.
     ///
-    /// If the chunk is not spawned this will result in nothing.
+    /// If the chunk is not spawned this will result in nothing. A chunk that
+    /// is still being built on the async compute task pool is cancelled. With
+    /// a [chunk storage] the chunk is unloaded once more chunks than
+    /// [`max_resident_chunks`] are in memory.
     ///
     /// # Errors
     ///
-    /// If the coordinate or index is out of bounds, an error will be returned.
+    /// If the coordinate or index is out of bounds, or if the chunk neither
+    /// exists nor is being built, an error will be returned.
     ///
     /// # Examples
     /// ```
@@ -1221,6 +2155,9 @@ impl Tilemap {
     /// assert!(tilemap.despawn_chunk((0, 0)).is_ok());
     /// assert!(tilemap.despawn_chunk((-1, -1)).is_err());
     /// ```
+    ///
+    /// [chunk storage]: Tilemap::set_chunk_storage
+    /// [`max_resident_chunks`]: Tilemap::max_resident_chunks
     pub fn despawn_chunk<P: Into<Point2>>(&mut self, point: P) -> TilemapResult<()> {
         let point: Point2 = point.into();
         if let Some(dimensions) = &self.dimensions {
@@ -1228,40 +2165,365 @@ impl Tilemap {
         }
 
         self.spawned.remove(&(point.x, point.y));
+        let cancelled = self.pending_chunks.cancel(point);
 
         if self.chunks.get_mut(&point).is_some() {
             self.chunk_events
                 .send(TilemapChunkEvent::Despawned { point });
+            if self.chunk_storage.is_some() && !self.unloadable_chunks.contains(&point) {
+                self.unloadable_chunks.push_back(point);
+            }
+            Ok(())
+        } else if cancelled {
             Ok(())
         } else {
             Err(ErrorKind::MissingChunk.into())
         }
     }
 
-    /// Destructively removes a chunk at a coordinate position and despawns them
-    /// if needed.
-    ///
-    /// Internally, this sends an event to the tilemap's system flagging which
-    /// chunks must be removed by index and entity. A chunk is not recoverable
-    /// if this action is done.
-    ///
-    /// Does nothing if the chunk does not exist.
-    ///
-    /// # Errors
-    ///
-    /// If the coordinate or index is out of bounds, an error will be returned.
+    /// Sets the generator of the chunks that are spawned before they exist,
+    /// replacing the one before.
     ///
     /// # Examples
     /// ```
     /// use bevy::asset::{prelude::*, HandleId};
     /// use bevy::sprite::prelude::*;
-    /// use bevy_tilemap::prelude::*;
+    /// use bevy_tilemap::{dimension::Dimension3, point::{Point2, Point3}, prelude::*};
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let mut tilemap = TilemapBuilder::new()
-    ///     .texture_atlas(texture_atlas_handle)
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// // A single tile in the middle of every chunk.
+    /// tilemap.set_chunk_generator(|_: Point2, dimensions: Dimension3| {
+    ///     let point = Point3::new(dimensions.width as i32 / 2, dimensions.height as i32 / 2, 0);
+    ///     vec![Tile { point, sprite_index: 1, ..Default::default() }]
+    /// });
+    ///
+    /// assert!(tilemap.spawn_chunk((1, 0)).is_ok());
+    /// assert_eq!(tilemap.get_tile((32, 0), 0).map(|tile| tile.index), Some(1));
+    /// ```
+    pub fn set_chunk_generator<G: ChunkGenerator>(&mut self, generator: G) {
+        self.chunk_generator = Some(Arc::new(generator));
+    }
+
+    /// Removes the generator of chunks and returns it, chunks that do not
+    /// exist are no longer spawned after.
+    pub fn remove_chunk_generator(&mut self) -> Option<Arc<dyn ChunkGenerator>> {
+        self.chunk_generator.take()
+    }
+
+    /// Inserts a chunk with the tiles of a generator.
+    fn generate_chunk(
+        &mut self,
+        point: Point2,
+        generator: &dyn ChunkGenerator,
+    ) -> TilemapResult<()> {
+        if let Some(dimensions) = &self.dimensions {
+            dimensions.check_point(point)?;
+        }
+        if self.chunks.contains_key(&point) {
+            return Err(ErrorKind::ChunkAlreadyExists(point).into());
+        }
+        let layer_kinds = self.layer_kinds();
+        let chunk =
+            generator::generate_chunk(generator, point, &layer_kinds, self.chunk_dimensions);
+        self.chunks.insert(point, chunk);
+        Ok(())
+    }
+
+    /// Returns `true` if a chunk is being built on the async compute task
+    /// pool to be spawned.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .async_chunks()
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.spawn_chunk((0, 0)).unwrap();
+    ///
+    /// // The chunk is built once the tilemap systems run.
+    /// assert!(!tilemap.is_chunk_pending((0, 0)));
+    /// ```
+    pub fn is_chunk_pending<P: Into<Point2>>(&self, point: P) -> bool {
+        self.pending_chunks.contains(point.into())
+    }
+
+    /// Sets the storage of the chunks that are unloaded, replacing the one
+    /// before.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, storage::FileChunkStorage};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.set_chunk_storage(FileChunkStorage::new("saves/chunks"));
+    /// ```
+    pub fn set_chunk_storage<S: ChunkStorage>(&mut self, storage: S) {
+        self.chunk_storage = Some(Arc::new(storage));
+    }
+
+    /// Removes the storage of chunks and returns it, chunks are no longer
+    /// unloaded after.
+    pub fn remove_chunk_storage(&mut self) -> Option<Arc<dyn ChunkStorage>> {
+        self.unloadable_chunks.clear();
+        self.chunk_storage.take()
+    }
+
+    /// Returns the most chunks that are kept in memory when there is a chunk
+    /// storage.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .max_resident_chunks(64)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(tilemap.max_resident_chunks(), 64);
+    /// ```
+    pub fn max_resident_chunks(&self) -> usize {
+        self.max_resident_chunks
+    }
+
+    /// Sets the most chunks that are kept in memory when there is a chunk
+    /// storage.
+    ///
+    /// Spawned chunks always stay in memory, so there can be more of them.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.set_max_resident_chunks(16);
+    /// assert_eq!(tilemap.max_resident_chunks(), 16);
+    /// ```
+    pub fn set_max_resident_chunks(&mut self, max_resident_chunks: usize) {
+        self.max_resident_chunks = max_resident_chunks;
+    }
+
+    /// Stores a chunk in the chunk storage and removes it from memory.
+    ///
+    /// The chunk is written before this returns, unlike the chunks that are
+    /// unloaded after they are despawned, which are written on the IO task
+    /// pool. The chunk is loaded again when it is spawned or inserted.
+    ///
+    /// # Errors
+    ///
+    /// If the tilemap has no chunk storage, if the chunk does not exist or is
+    /// spawned, or if the chunk could not be stored.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, storage::FileChunkStorage};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// assert!(tilemap.unload_chunk((0, 0)).is_err());
+    ///
+    /// let directory = std::env::temp_dir().join("bevy_tilemap_unload_example");
+    /// tilemap.set_chunk_storage(FileChunkStorage::new(&directory));
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// assert!(tilemap.unload_chunk((0, 0)).is_ok());
+    /// assert!(!tilemap.contains_chunk((0, 0)));
+    /// # std::fs::remove_dir_all(&directory).unwrap();
+    /// ```
+    pub fn unload_chunk<P: Into<Point2>>(&mut self, point: P) -> TilemapResult<()> {
+        let point: Point2 = point.into();
+        let storage = self
+            .chunk_storage
+            .clone()
+            .ok_or(ErrorKind::MissingChunkStorage)?;
+        if self.is_chunk_in_use(point) {
+            return Err(ErrorKind::ChunkInUse(point).into());
+        }
+        let chunk = self.chunks.get(&point).ok_or(ErrorKind::MissingChunk)?;
+        let data = serde_json::to_vec(chunk)
+            .map_err(|err| ErrorKind::ChunkStorage(point, err.to_string()))?;
+        // The data of an earlier unload must not be written after this.
+        self.storing_chunks.finish(point).ok();
+        storage
+            .store(point, &data)
+            .map_err(|err| ErrorKind::ChunkStorage(point, err.to_string()))?;
+        self.chunks.remove(&point);
+        self.unloadable_chunks
+            .retain(|unloadable| *unloadable != point);
+        Ok(())
+    }
+
+    /// Returns `true` if a chunk is spawned, being built or still has its
+    /// entity.
+    fn is_chunk_in_use(&self, point: Point2) -> bool {
+        self.spawned.contains(&(point.x, point.y))
+            || self.pending_chunks.contains(point)
+            || self
+                .chunks
+                .get(&point)
+                .map_or(false, |chunk| chunk.get_entity().is_some())
+    }
+
+    /// Inserts a chunk from the chunk storage if it was stored there.
+    fn load_chunk(&mut self, point: Point2) -> TilemapResult<()> {
+        let storage = match &self.chunk_storage {
+            Some(storage) => storage,
+            None => return Ok(()),
+        };
+        let chunk = match self.storing_chunks.data(point) {
+            Some(data) => self.chunk_from_data(point, &data)?,
+            None => match storage
+                .load(point)
+                .map_err(|err| ErrorKind::ChunkStorage(point, err.to_string()))?
+            {
+                Some(data) => self.chunk_from_data(point, &data)?,
+                None => return Ok(()),
+            },
+        };
+        self.chunks.insert(point, chunk);
+        let visible = self.chunk_fog_visible(point);
+        if let Some(fog_visible) = &mut self.fog_visible {
+            fog_visible.extend(visible);
+        }
+        Ok(())
+    }
+
+    /// Deserializes a chunk that was stored.
+    fn chunk_from_data(&self, point: Point2, data: &[u8]) -> TilemapResult<Chunk> {
+        let mut chunk: Chunk = serde_json::from_slice(data)
+            .map_err(|err| ErrorKind::ChunkStorage(point, err.to_string()))?;
+        chunk.type_data(&self.tile_data_types);
+        // Layers that were added while the chunk was unloaded.
+        for (sprite_order, kind) in self.layer_kinds().into_iter().enumerate() {
+            if let Some(kind) = kind {
+                chunk.add_sprite_layer(&kind, sprite_order, self.chunk_dimensions);
+            }
+        }
+        Ok(chunk)
+    }
+
+    /// Unloads the despawned chunks, the longest despawned first, until at
+    /// most the maximum resident chunks are left in memory.
+    ///
+    /// The chunks are written on the IO task pool. Chunks that can not be
+    /// serialized stay in memory and are not unloaded again until they are
+    /// despawned again.
+    pub(crate) fn unload_despawned_chunks(&mut self, task_pool: &IoTaskPool) {
+        let storage = match &self.chunk_storage {
+            Some(storage) => storage.clone(),
+            None => return,
+        };
+        let mut resident = self.chunks.len();
+        let mut kept = VecDeque::new();
+        while let Some(point) = self.unloadable_chunks.pop_front() {
+            if !self.chunks.contains_key(&point) {
+                continue;
+            }
+            if resident <= self.max_resident_chunks || self.is_chunk_in_use(point) {
+                kept.push_back(point);
+                continue;
+            }
+            let data = match self.chunks.get(&point).map(serde_json::to_vec) {
+                Some(Ok(data)) => data,
+                Some(Err(err)) => {
+                    error!("chunk {} could not be unloaded: {}", point, err);
+                    continue;
+                }
+                None => continue,
+            };
+            self.chunks.remove(&point);
+            self.storing_chunks
+                .store(task_pool, storage.clone(), point, data);
+            resident -= 1;
+        }
+        self.unloadable_chunks = kept;
+    }
+
+    /// Removes the unloaded chunks that are written to the chunk storage, and
+    /// puts those that could not be written back in memory.
+    pub(crate) fn finish_stored_chunks(&mut self) {
+        for (point, data, err) in self.storing_chunks.take_failed() {
+            error!("chunk {} could not be stored, keeping it: {}", point, err);
+            if self.chunks.contains_key(&point) {
+                continue;
+            }
+            match self.chunk_from_data(point, &data) {
+                Ok(chunk) => {
+                    self.chunks.insert(point, chunk);
+                }
+                Err(err) => error!("{}", err),
+            }
+        }
+    }
+
+    /// Returns `true` if unloaded chunks are being written to the chunk
+    /// storage.
+    pub(crate) fn is_storing_chunks(&self) -> bool {
+        !self.storing_chunks.is_empty()
+    }
+
+    /// Destructively removes a chunk at a coordinate position and despawns them
+    /// if needed.
+    ///
+    /// Internally, this sends an event to the tilemap's system flagging which
+    /// chunks must be removed by index and entity. A chunk is not recoverable
+    /// if this action is done.
+    ///
+    /// Does nothing if the chunk does not exist.
+    ///
+    /// # Errors
+    ///
+    /// If the coordinate or index is out of bounds, an error will be returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
     ///     .dimensions(3, 3)
     ///     .texture_dimensions(32, 32)
     ///     .finish()
@@ -1280,6 +2542,15 @@ impl Tilemap {
         self.despawn_chunk(point)?;
 
         self.chunks.remove(&point);
+        self.unloadable_chunks
+            .retain(|unloadable| *unloadable != point);
+        if let Some(storage) = &self.chunk_storage {
+            // A chunk that is still being written would be written back.
+            self.storing_chunks.finish(point).ok();
+            storage
+                .remove(point)
+                .map_err(|err| ErrorKind::ChunkStorage(point, err.to_string()))?;
+        }
 
         Ok(())
     }
@@ -1322,133 +2593,503 @@ impl Tilemap {
         (x, y)
     }
 
-    /// Sorts tiles into the chunks they belong to.
-    fn sort_tiles_to_chunks<P, I>(
-        &mut self,
-        tiles: I,
-    ) -> TilemapResult<HashMap<Point2, Vec<Tile<Point3>>>>
-    where
-        P: Into<Point3>,
-        I: IntoIterator<Item = Tile<P>>,
-    {
-        let width = self.chunk_dimensions.width as i32;
-        let height = self.chunk_dimensions.height as i32;
-
-        let mut chunk_map: HashMap<Point2, Vec<Tile<Point3>>> = HashMap::default();
-        for tile in tiles.into_iter() {
-            let global_tile_point: Point3 = tile.point.into();
-            let chunk_point: Point2 = self.point_to_chunk_point(global_tile_point).into();
+    /// Takes a global tile point and returns the center of the tile relative
+    /// to the tilemap, before the tile scale is applied.
+    ///
+    /// This mirrors what the chunk translation and the topology vertex shaders
+    /// do to place a tile.
+    pub(crate) fn tile_translation(&self, point: Point3) -> Vec2 {
+        use GridTopology::*;
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let tile_point = self.point_to_tile_point(point);
+        let (chunk_x, chunk_y) = topology_translation(
+            self.topology,
+            chunk_point,
+            self.chunk_dimensions,
+            self.texture_dimensions,
+        );
 
-            if let Some(layer) = self.layers.get(tile.sprite_order as usize) {
-                if layer.as_ref().is_none() {
-                    self.add_layer(TilemapLayer::default(), tile.sprite_order as usize)?;
+        let texture_width = self.texture_dimensions.width as f32;
+        let texture_height = self.texture_dimensions.height as f32;
+        let x0 = tile_point.x as f32 - self.chunk_dimensions.width as f32 / 2.0
+            + self.layer_offset.x * point.z as f32;
+        let y0 = tile_point.y as f32 - self.chunk_dimensions.height as f32 / 2.0
+            + self.layer_offset.y * point.z as f32;
+        let mut x = x0 * texture_width;
+        let mut y = y0 * texture_height;
+        match self.topology {
+            Square => {}
+            HexY | HexEvenRows | HexOddRows => {
+                let row = (y0 + 0.01).floor() as i32 + 1;
+                if self.topology == HexY {
+                    x += (0.5 * texture_width).floor() * row as f32;
+                } else {
+                    let x_offset = (0.25 * texture_width).floor();
+                    if (row % 2 == 0) == (self.topology == HexEvenRows) {
+                        x -= x_offset;
+                    } else {
+                        x += x_offset;
+                    }
                 }
-            } else {
-                return Err(ErrorKind::LayerDoesNotExist(tile.sprite_order).into());
+                y -= row as f32 * (0.25 * texture_height).ceil();
+            }
+            HexX | HexEvenCols | HexOddCols => {
+                let col = (x0 + 0.01).floor() as i32 + 1;
+                if self.topology == HexX {
+                    y += (0.5 * texture_height).floor() * col as f32;
+                } else {
+                    let y_offset = (0.25 * texture_height).floor();
+                    if (col % 2 == 0) == (self.topology == HexEvenCols) {
+                        y -= y_offset;
+                    } else {
+                        y += y_offset;
+                    }
+                }
+                x -= col as f32 * (0.25 * texture_width).ceil();
             }
+        }
 
-            let tile_point = Point3::new(
-                global_tile_point.x - (width * chunk_point.x) + (width / 2),
-                global_tile_point.y - (height * chunk_point.y) + (height / 2),
-                global_tile_point.z,
-            );
+        // The shaders snap every vertex to a whole pixel.
+        Vec2::new(
+            chunk_x + (x.ceil() + (x + texture_width).ceil()) / 2.0,
+            chunk_y + (y.ceil() + (y + texture_height).ceil()) / 2.0,
+        )
+    }
 
-            let chunk_tile: Tile<Point3> = Tile {
-                point: tile_point,
-                sprite_order: tile.sprite_order,
-                sprite_index: tile.sprite_index,
-                tint: tile.tint,
-            };
-            if let Some(tiles) = chunk_map.get_mut(&chunk_point) {
-                tiles.push(chunk_tile);
-            } else {
-                let tiles = vec![chunk_tile];
-                chunk_map.insert(chunk_point, tiles);
+    /// Takes a global tile point and returns the points of the tiles next to
+    /// it, in clockwise order starting at the first one at or after north.
+    ///
+    /// Square topologies have 8 neighbors starting at north, hex topologies 6
+    /// starting at north east for hexes with a pointy top and at north for
+    /// hexes with a flat top. Which tiles of the staggered rows or columns of
+    /// a hex topology are next to each other is taken from where they are
+    /// drawn.
+    pub(crate) fn neighbor_points(&self, point: Point3) -> Vec<Point3> {
+        use GridTopology::*;
+        let Point3 { x, y, z } = point;
+        let offsets = match self.topology {
+            Square => vec![
+                (0, 1),
+                (1, 1),
+                (1, 0),
+                (1, -1),
+                (0, -1),
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+            ],
+            HexY | HexEvenRows | HexOddRows => {
+                // A row drawn further right than this one has its tile at the
+                // same X to the north east or south east instead of west.
+                let here = self.tile_translation(point).x;
+                let shift = |row: i32| {
+                    let there = self.tile_translation(Point3::new(x, y + row, z)).x;
+                    if there > here {
+                        -1
+                    } else {
+                        0
+                    }
+                };
+                let north = shift(1);
+                let south = shift(-1);
+                vec![
+                    (north + 1, 1),
+                    (1, 0),
+                    (south + 1, -1),
+                    (south, -1),
+                    (-1, 0),
+                    (north, 1),
+                ]
             }
-        }
-        Ok(chunk_map)
+            HexX | HexEvenCols | HexOddCols => {
+                // A column drawn further up than this one has its tile at the
+                // same Y to the north east or north west instead of south.
+                let here = self.tile_translation(point).y;
+                let shift = |col: i32| {
+                    let there = self.tile_translation(Point3::new(x + col, y, z)).y;
+                    if there > here {
+                        -1
+                    } else {
+                        0
+                    }
+                };
+                let east = shift(1);
+                let west = shift(-1);
+                vec![
+                    (0, 1),
+                    (1, east + 1),
+                    (1, east),
+                    (0, -1),
+                    (-1, west),
+                    (-1, west + 1),
+                ]
+            }
+        };
+        offsets
+            .into_iter()
+            .map(|(offset_x, offset_y)| Point3::new(x + offset_x, y + offset_y, z))
+            .collect()
     }
 
-    /// Sets many tiles, creating new chunks if needed.
-    ///
-    /// If setting a single tile is more preferable, then use the [`insert_tile`]
-    /// method instead.
+    /// Takes a global tile point and returns the center of that tile in world
+    /// space.
     ///
-    /// If the chunk does not yet exist, it will create a new one automatically.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the given coordinate or index is out of bounds, the
-    /// layer or chunk does not exist. If either the layer or chunk error occurs
-    /// then creating what is missing will resolve it.
+    /// The transform given must be the transform of the tilemap entity. The
+    /// topology, texture dimensions, tile scale and layer offset of the
+    /// tilemap are all taken into account. The Z value of the point is the
+    /// depth of the tile, which is only used for the layer offset.
     ///
     /// # Examples
-    ///
     /// ```
     /// use bevy::asset::{prelude::*, HandleId};
-    /// use bevy::render::prelude::*;
+    /// use bevy::math::Vec3;
     /// use bevy::sprite::prelude::*;
-    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    /// use bevy::transform::prelude::*;
+    /// use bevy_tilemap::prelude::*;
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let mut tilemap = TilemapBuilder::new()
-    ///     .texture_atlas(texture_atlas_handle)
-    ///     .dimensions(1, 1)
-    ///     .texture_dimensions(32, 32)
-    ///     .finish()
-    ///     .unwrap();
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// let transform = Transform::default();
     ///
-    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// assert_eq!(tilemap.tile_to_world(&transform, (0, 0)), Vec3::new(16.0, 16.0, 1.0));
+    /// assert_eq!(tilemap.tile_to_world(&transform, (-1, 2)), Vec3::new(-16.0, 80.0, 1.0));
+    /// ```
+    pub fn tile_to_world<P: Into<Point3>>(&self, transform: &Transform, point: P) -> Vec3 {
+        let translation = self.tile_translation(point.into());
+        let local = Vec3::new(
+            translation.x * self.tile_scale.x,
+            translation.y * self.tile_scale.y,
+            1.0,
+        );
+        transform.mul_vec3(local)
+    }
+
+    /// Takes a translation in world space and returns the global tile point
+    /// that it lands on.
     ///
-    /// let mut tiles = vec![
-    ///     Tile { point: (1, 1), sprite_index: 0, ..Default::default() },
-    ///     Tile { point: (2, 2), sprite_index: 1, ..Default::default() },
-    ///     Tile { point: (3, 3), sprite_index: 2, ..Default::default() },
-    /// ];
+    /// The transform given must be the transform of the tilemap entity. As a
+    /// translation alone can not tell which depth it is at, the Z depth has to
+    /// be given and is returned as the Z value of the point. This only matters
+    /// if the tilemap has a layer offset.
     ///
-    /// // Set multiple tiles and unwrap the result
-    /// tilemap.insert_tiles(tiles).unwrap();
+    /// For hex topologies the point returned is the hex whose center is the
+    /// closest to the translation.
     ///
-    /// assert_eq!(tilemap.get_tile((1, 1), 0), Some(&RawTile { index: 0, color: Color::WHITE }));
-    /// assert_eq!(tilemap.get_tile((2, 2), 0), Some(&RawTile { index: 1, color: Color::WHITE }));
-    /// assert_eq!(tilemap.get_tile((3, 3), 0), Some(&RawTile { index: 2, color: Color::WHITE }));
-    /// assert_eq!(tilemap.get_tile((4, 4), 0), None);
+    /// # Examples
     /// ```
-    ///
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::math::Vec3;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy::transform::prelude::*;
+    /// use bevy_tilemap::{point::Point3, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// let transform = Transform::default();
+    ///
+    /// let point = tilemap.world_to_tile(&transform, Vec3::new(40.0, -10.0, 0.0), 0);
+    /// assert_eq!(point, Point3::new(1, -1, 0));
+    /// ```
+    pub fn world_to_tile(&self, transform: &Transform, translation: Vec3, z: i32) -> Point3 {
+        use GridTopology::*;
+        let local = transform
+            .compute_matrix()
+            .inverse()
+            .transform_point3(translation);
+        let texture_width = self.texture_dimensions.width as f32;
+        let texture_height = self.texture_dimensions.height as f32;
+        let x = local.x / self.tile_scale.x - self.layer_offset.x * z as f32 * texture_width;
+        let y = local.y / self.tile_scale.y - self.layer_offset.y * z as f32 * texture_height;
+
+        // A rough guess first, which is then refined by finding the closest
+        // tile center around it.
+        let (guess_x, guess_y) = match self.topology {
+            Square => (x / texture_width - 0.5, y / texture_height - 0.5),
+            HexEvenRows | HexOddRows => {
+                let step = texture_height - (0.25 * texture_height).ceil();
+                (x / texture_width - 0.5, (y - 0.5 * texture_height) / step)
+            }
+            HexY => {
+                let step = texture_height - (0.25 * texture_height).ceil();
+                let guess_y = (y - 0.5 * texture_height) / step;
+                let guess_x = (x - guess_y * (0.5 * texture_width).floor()) / texture_width - 0.5;
+                (guess_x, guess_y)
+            }
+            HexEvenCols | HexOddCols => {
+                let step = texture_width - (0.25 * texture_width).ceil();
+                ((x - 0.5 * texture_width) / step, y / texture_height - 0.5)
+            }
+            HexX => {
+                let step = texture_width - (0.25 * texture_width).ceil();
+                let guess_x = (x - 0.5 * texture_width) / step;
+                let guess_y = (y - guess_x * (0.5 * texture_height).floor()) / texture_height - 0.5;
+                (guess_x, guess_y)
+            }
+        };
+        let guess = Point3::new(guess_x.round() as i32, guess_y.round() as i32, z);
+
+        let target = Vec2::new(local.x / self.tile_scale.x, local.y / self.tile_scale.y);
+        let mut closest = guess;
+        let mut closest_distance = f32::MAX;
+        for offset_y in -2..=2 {
+            for offset_x in -2..=2 {
+                let point = guess + Point3::new(offset_x, offset_y, 0);
+                let distance = self.tile_translation(point).distance_squared(target);
+                if distance < closest_distance {
+                    closest_distance = distance;
+                    closest = point;
+                }
+            }
+        }
+
+        closest
+    }
+
+    /// Takes a chunk point and returns the translation of the chunk in world
+    /// space.
+    ///
+    /// The transform given must be the transform of the tilemap entity. The
+    /// translation returned is the origin of the chunk's mesh which is the
+    /// center of the chunk for square topologies.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::math::Vec3;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy::transform::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// let transform = Transform::default();
+    ///
+    /// // 32 tiles of 32 pixels each.
+    /// assert_eq!(tilemap.chunk_to_world(&transform, (1, 0)), Vec3::new(1024.0, 0.0, 1.0));
+    /// ```
+    pub fn chunk_to_world<P: Into<Point2>>(&self, transform: &Transform, point: P) -> Vec3 {
+        transform.mul_vec3(self.chunk_translation(point.into()))
+    }
+
+    /// Takes a translation in world space and returns the chunk point of the
+    /// chunk that contains it.
+    ///
+    /// The transform given must be the transform of the tilemap entity.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::math::Vec3;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy::transform::prelude::*;
+    /// use bevy_tilemap::{point::Point2, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// let transform = Transform::default();
+    ///
+    /// let chunk_point = tilemap.world_to_chunk(&transform, Vec3::new(1000.0, -600.0, 0.0));
+    /// assert_eq!(chunk_point, Point2::new(1, -1));
+    /// ```
+    pub fn world_to_chunk(&self, transform: &Transform, translation: Vec3) -> Point2 {
+        let point = self.world_to_tile(transform, translation, 0);
+        self.point_to_chunk_point(point).into()
+    }
+
+    /// Returns the translation of a chunk entity relative to the tilemap.
+    pub(crate) fn chunk_translation(&self, point: Point2) -> Vec3 {
+        let (translation_x, translation_y) = topology_translation(
+            self.topology,
+            point,
+            self.chunk_dimensions,
+            self.texture_dimensions,
+        );
+        Vec3::new(
+            translation_x * self.tile_scale.x,
+            translation_y * self.tile_scale.y,
+            1.0,
+        )
+    }
+
+    /// Sorts tiles into the chunks they belong to.
+    fn sort_tiles_to_chunks<P, I>(
+        &mut self,
+        tiles: I,
+    ) -> TilemapResult<HashMap<Point2, Vec<Tile<Point3>>>>
+    where
+        P: Into<Point3>,
+        I: IntoIterator<Item = Tile<P>>,
+    {
+        let width = self.chunk_dimensions.width as i32;
+        let height = self.chunk_dimensions.height as i32;
+
+        let mut chunk_map: HashMap<Point2, Vec<Tile<Point3>>> = HashMap::default();
+        for tile in tiles.into_iter() {
+            let global_tile_point: Point3 = tile.point.into();
+            let chunk_point: Point2 = self.point_to_chunk_point(global_tile_point).into();
+
+            if let Some(layer) = self.layers.get(tile.sprite_order as usize) {
+                if layer.as_ref().is_none() {
+                    self.add_layer(TilemapLayer::default(), tile.sprite_order as usize)?;
+                }
+            } else {
+                return Err(ErrorKind::LayerDoesNotExist(tile.sprite_order).into());
+            }
+
+            let tile_point = Point3::new(
+                global_tile_point.x - (width * chunk_point.x) + (width / 2),
+                global_tile_point.y - (height * chunk_point.y) + (height / 2),
+                global_tile_point.z,
+            );
+
+            let chunk_tile: Tile<Point3> = Tile {
+                point: tile_point,
+                sprite_order: tile.sprite_order,
+                sprite_index: tile.sprite_index,
+                tint: tile.tint,
+                flip: tile.flip,
+                rotation: tile.rotation,
+            };
+            if let Some(tiles) = chunk_map.get_mut(&chunk_point) {
+                tiles.push(chunk_tile);
+            } else {
+                let tiles = vec![chunk_tile];
+                chunk_map.insert(chunk_point, tiles);
+            }
+        }
+        Ok(chunk_map)
+    }
+
+    /// Sets many tiles, creating new chunks if needed.
+    ///
+    /// If setting a single tile is more preferable, then use the [`insert_tile`]
+    /// method instead.
+    ///
+    /// If the chunk does not yet exist, it will create a new one automatically.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the given coordinate or index is out of bounds, the
+    /// layer or chunk does not exist. If either the layer or chunk error occurs
+    /// then creating what is missing will resolve it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::render::prelude::*;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .dimensions(1, 1)
+    ///     .texture_dimensions(32, 32)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let mut tiles = vec![
+    ///     Tile { point: (1, 1), sprite_index: 0, ..Default::default() },
+    ///     Tile { point: (2, 2), sprite_index: 1, ..Default::default() },
+    ///     Tile { point: (3, 3), sprite_index: 2, ..Default::default() },
+    /// ];
+    ///
+    /// // Set multiple tiles and unwrap the result
+    /// tilemap.insert_tiles(tiles).unwrap();
+    ///
+    /// assert_eq!(tilemap.get_tile((1, 1), 0), Some(&RawTile { index: 0, color: Color::WHITE, ..Default::default() }));
+    /// assert_eq!(tilemap.get_tile((2, 2), 0), Some(&RawTile { index: 1, color: Color::WHITE, ..Default::default() }));
+    /// assert_eq!(tilemap.get_tile((3, 3), 0), Some(&RawTile { index: 2, color: Color::WHITE, ..Default::default() }));
+    /// assert_eq!(tilemap.get_tile((4, 4), 0), None);
+    /// ```
+    ///
     /// [`insert_tile`]: Tilemap::insert_tile
     pub fn insert_tiles<P, I>(&mut self, tiles: I) -> TilemapResult<()>
     where
         P: Into<Point3>,
         I: IntoIterator<Item = Tile<P>>,
     {
+        if self.autotiles.is_unpainted() {
+            return self.set_tiles(tiles);
+        }
+
+        // Tiles inserted over painted tiles replace their terrain.
+        let tiles = tiles
+            .into_iter()
+            .map(|tile| Tile {
+                point: tile.point.into(),
+                sprite_order: tile.sprite_order,
+                sprite_index: tile.sprite_index,
+                tint: tile.tint,
+                flip: tile.flip,
+                rotation: tile.rotation,
+            })
+            .collect::<Vec<Tile<Point3>>>();
+        let erased = tiles
+            .iter()
+            .filter_map(|tile| {
+                self.autotiles.erase(tile.point, tile.sprite_order)?;
+                Some((tile.point, tile.sprite_order))
+            })
+            .collect::<Vec<(Point3, usize)>>();
+        self.set_tiles(tiles)?;
+        self.update_autotiles(erased)
+    }
+
+    /// Sets many tiles without touching the terrains, creating new chunks if
+    /// needed.
+    fn set_tiles<P, I>(&mut self, tiles: I) -> TilemapResult<()>
+    where
+        P: Into<Point3>,
+        I: IntoIterator<Item = Tile<P>>,
+    {
+        self.flush_tile_writes();
+        let tracked = self.tracks_tiles();
         let chunk_map = self.sort_tiles_to_chunks(tiles)?;
+        let mut changes = Vec::new();
         for (chunk_point, tiles) in chunk_map.into_iter() {
-            // Is there a better way to do this? Clippy hates if I don't do it
-            // like this talking about constructing regardless yet, here it is,
-            // copying stuff regardless because it doesn't like self in the
-            // `FnOnce`.
-            let layers = self.layers.clone();
-            let chunk_dimensions = self.chunk_dimensions;
-            let chunk = if self.auto_flags.contains(AutoFlags::AUTO_CHUNK) {
-                self.chunks.entry(chunk_point).or_insert_with(|| {
-                    let layer_kinds = layers
-                        .iter()
-                        .map(|x| x.and_then(|y| Some(y.kind)))
-                        .collect::<Vec<Option<LayerKind>>>();
-                    Chunk::new(chunk_point, &layer_kinds, chunk_dimensions)
-                })
-            } else {
-                match self.chunks.get_mut(&chunk_point) {
-                    Some(c) => c,
-                    None => return Err(ErrorKind::MissingChunk.into()),
+            // Unloaded chunks are loaded from the chunk storage rather than
+            // replaced by an empty chunk.
+            if !self.chunks.contains_key(&chunk_point)
+                && self.auto_flags.contains(AutoFlags::AUTO_CHUNK)
+            {
+                if let Err(err) = self.insert_chunk(chunk_point) {
+                    self.send_tile_changes(changes);
+                    return Err(err);
+                }
+            }
+            let chunk = match self.chunks.get_mut(&chunk_point) {
+                Some(c) => c,
+                None => {
+                    self.send_tile_changes(changes);
+                    return Err(ErrorKind::MissingChunk.into());
                 }
             };
 
             for tile in tiles.iter() {
                 let index = self.chunk_dimensions.encode_point_unchecked(tile.point);
+                if !tracked {
+                    chunk.set_tile(index, *tile);
+                    continue;
+                }
+                let z_depth = tile.point.z as usize;
+                let old = chunk.get_tile(index, tile.sprite_order, z_depth).copied();
                 chunk.set_tile(index, *tile);
+                let new = chunk.get_tile(index, tile.sprite_order, z_depth).copied();
+                changes.push((chunk_point, tile.point, tile.sprite_order, old, new));
             }
 
             if chunk.mesh().is_some() {
@@ -1457,6 +3098,7 @@ impl Tilemap {
                 });
             }
         }
+        self.send_tile_changes(changes);
 
         Ok(())
     }
@@ -1491,7 +3133,7 @@ impl Tilemap {
     /// let tile = Tile { point, sprite_index, ..Default::default() };
     ///
     /// assert!(tilemap.insert_tile(tile).is_ok());
-    /// assert_eq!(tilemap.get_tile((9, 3), 0), Some(&RawTile { index: 3, color: Color::WHITE }))
+    /// assert_eq!(tilemap.get_tile((9, 3), 0), Some(&RawTile { index: 3, color: Color::WHITE, ..Default::default() }))
     /// ```
     ///
     /// # Errors
@@ -1537,7 +3179,7 @@ impl Tilemap {
     /// tilemap.clear_tiles(to_remove).unwrap();
     /// assert_eq!(tilemap.get_tile((1, 1, 0), 0), None);
     /// assert_eq!(tilemap.get_tile((2, 2, 0), 0), None);
-    /// assert_eq!(tilemap.get_tile((3, 3, 0), 0), Some(&RawTile { index: 0, color: Color::WHITE} ));
+    /// assert_eq!(tilemap.get_tile((3, 3, 0), 0), Some(&RawTile { index: 0, color: Color::WHITE, ..Default::default() } ));
     /// ```
     ///
     /// # Errors
@@ -1556,25 +3198,46 @@ impl Tilemap {
                 sprite_index: 0,
                 sprite_order,
                 tint: Color::rgba(0.0, 0.0, 0.0, 0.0),
+                ..Default::default()
             });
         }
+        let erased = tiles
+            .iter()
+            .filter_map(|tile| {
+                self.autotiles.erase(tile.point, tile.sprite_order)?;
+                Some((tile.point, tile.sprite_order))
+            })
+            .collect::<Vec<(Point3, usize)>>();
+        self.flush_tile_writes();
+        let tracked = self.tracks_tiles();
         let chunk_map = self.sort_tiles_to_chunks(tiles)?;
+        let mut changes = Vec::new();
         for (chunk_point, tiles) in chunk_map.into_iter() {
             let chunk = match self.chunks.get_mut(&chunk_point) {
                 Some(c) => c,
-                None => return Err(ErrorKind::MissingChunk.into()),
+                None => {
+                    self.send_tile_changes(changes);
+                    return Err(ErrorKind::MissingChunk.into());
+                }
             };
             for tile in tiles.iter() {
                 let index = self.chunk_dimensions.encode_point_unchecked(tile.point);
-                chunk.remove_tile(index, tile.sprite_order, tile.point.z as usize);
+                let z_depth = tile.point.z as usize;
+                if tracked {
+                    let old = chunk.get_tile(index, tile.sprite_order, z_depth).copied();
+                    changes.push((chunk_point, tile.point, tile.sprite_order, old, None));
+                }
+                chunk.remove_tile(index, tile.sprite_order, z_depth);
+                chunk.clear_tile_data(index, tile.sprite_order);
             }
 
             self.chunk_events.send(TilemapChunkEvent::Modified {
                 point: chunk.point(),
             });
         }
+        self.send_tile_changes(changes);
 
-        Ok(())
+        self.update_autotiles(erased)
     }
 
     /// Takes a global tile point and returns a tile point in a chunk.
@@ -1589,182 +3252,2535 @@ impl Tilemap {
         )
     }
 
-    /// Clear a single tile at the specified point from the tilemap.
+    /// Takes a tile point in a chunk and returns the global tile point.
+    fn tile_point_to_point(&self, chunk_point: Point2, tile_point: Point3) -> Point3 {
+        let width = self.chunk_dimensions.width as i32;
+        let height = self.chunk_dimensions.height as i32;
+        Point3::new(
+            tile_point.x + (width * chunk_point.x) - (width / 2),
+            tile_point.y + (height * chunk_point.y) - (height / 2),
+            tile_point.z,
+        )
+    }
+
+    /// Sends an event for every tile that changed, from the chunk point, tile
+    /// point in the chunk, sprite order and the raw tile before and after.
+    fn send_tile_changes(&mut self, changes: Vec<TileChange>) {
+        for (chunk_point, tile_point, sprite_order, old, new) in changes {
+            let point = self.tile_point_to_point(chunk_point, tile_point);
+            self.track_tile_change(point, sprite_order, old, new);
+        }
+    }
+
+    /// Sends an event for every tile that was borrowed mutably and changed
+    /// since.
+    pub(crate) fn flush_tile_writes(&mut self) {
+        for (point, sprite_order, old) in mem::take(&mut self.tile_writes) {
+            let new = self.raw_tile(point, sprite_order).copied();
+            self.track_tile_change(point, sprite_order, Some(old), new);
+        }
+    }
+
+    /// Returns `true` if changed tiles are sent as events or recorded in a
+    /// transaction.
+    fn tracks_tiles(&self) -> bool {
+        self.tile_events || self.history.is_recording()
+    }
+
+    /// Sends an event for a tile that changed and records it in the open
+    /// transaction, if the raw tile is not the same.
+    fn track_tile_change(
+        &mut self,
+        point: Point3,
+        sprite_order: usize,
+        old: Option<RawTile>,
+        new: Option<RawTile>,
+    ) {
+        if old == new {
+            return;
+        }
+        self.history.record(Edit::Tile {
+            point,
+            sprite_order,
+            old,
+            new,
+        });
+        if self.tile_events {
+            self.events.send(TilemapEvent::TileChanged {
+                point,
+                sprite_order,
+                old,
+                new,
+            });
+        }
+    }
+
+    /// Records a data value that changed in the open transaction, if the
+    /// value is not the same.
+    fn record_data_change(
+        &mut self,
+        chunk_point: Point2,
+        index: usize,
+        data_layer: usize,
+        old: Option<i32>,
+        new: Option<i32>,
+    ) {
+        if old != new {
+            self.history.record(Edit::Data {
+                chunk_point,
+                index,
+                data_layer,
+                old,
+                new,
+            });
+        }
+    }
+
+    /// Clear a single tile at the specified point from the tilemap.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert!(tilemap.insert_chunk((0, 0)).is_ok());
+    ///
+    /// let point = (3, 1);
+    /// let sprite_index = 1;
+    /// let tile = Tile { point, sprite_index, ..Default::default() };
+    ///
+    /// // Set a single tile and unwrap the result
+    /// assert!(tilemap.insert_tile(tile).is_ok());
+    ///
+    /// // Later on...
+    /// assert!(tilemap.clear_tile(point, 0).is_ok());
+    /// assert_eq!(tilemap.get_tile((3, 1), 0), None);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error can occure if the point is outside of the tilemap. This can
+    /// only happen if the tilemap has dimensions.
+    pub fn clear_tile<P>(&mut self, point: P, sprite_order: usize) -> TilemapResult<()>
+    where
+        P: Into<Point3>,
+    {
+        let points = vec![(point, sprite_order)];
+        self.clear_tiles(points)
+    }
+
+    /// Paints many tiles with terrains, given as a point, sprite order and
+    /// terrain each.
+    ///
+    /// The sprite of every painted tile and of the painted tiles around it is
+    /// chosen by the rules of their terrain. Clearing or inserting a tile over
+    /// a painted tile removes its terrain.
+    ///
+    /// If the chunk does not yet exist, it will create a new one automatically
+    /// if the tilemap has auto chunk enabled.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// const GRASS: u32 = 0;
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .add_terrain(GRASS, AutotileRules::sequential(AutotileKind::Bitmask, 0))
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let terrains = vec![((0, 0), 0, GRASS), ((0, 1), 0, GRASS), ((0, -1), 0, GRASS)];
+    /// tilemap.insert_terrains(terrains).unwrap();
+    ///
+    /// // The middle tile has grass to the north and south.
+    /// assert_eq!(tilemap.get_tile((0, 0), 0).unwrap().index, 0b0101);
+    ///
+    /// // Clearing the tile to the north leaves only the south.
+    /// tilemap.clear_tile((0, 1), 0).unwrap();
+    /// assert_eq!(tilemap.get_tile((0, 0), 0).unwrap().index, 0b0100);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a terrain does not exist, a chunk does not exist
+    /// and auto chunk is disabled or the layer does not exist.
+    pub fn insert_terrains<P, I>(&mut self, terrains: I) -> TilemapResult<()>
+    where
+        P: Into<Point3>,
+        I: IntoIterator<Item = (P, usize, u32)>,
+    {
+        let mut checked = Vec::new();
+        for (point, sprite_order, terrain) in terrains {
+            let point: Point3 = point.into();
+            if self.autotiles.rules(terrain).is_none() {
+                return Err(ErrorKind::TerrainDoesNotExist(terrain).into());
+            }
+            let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+            if !self.auto_flags.contains(AutoFlags::AUTO_CHUNK)
+                && !self.chunks.contains_key(&chunk_point)
+            {
+                return Err(ErrorKind::MissingChunk.into());
+            }
+            checked.push((point, sprite_order, terrain));
+        }
+
+        let mut painted = Vec::with_capacity(checked.len());
+        for (point, sprite_order, terrain) in checked {
+            self.autotiles.paint(point, sprite_order, terrain);
+            painted.push((point, sprite_order));
+        }
+        self.update_autotiles(painted)
+    }
+
+    /// Paints a single tile with a terrain at a point and sprite order.
+    ///
+    /// If painting more than one tile at a time, it is better to use
+    /// [`insert_terrains`] which creates a single event for all.
+    ///
+    /// [`insert_terrains`]: Tilemap::insert_terrains
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .add_terrain(7, AutotileRules::sequential(AutotileKind::Bitmask, 16))
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// assert!(tilemap.insert_terrain((3, 3), 0, 7).is_ok());
+    /// assert!(tilemap.insert_terrain((3, 4), 0, 8).is_err());
+    ///
+    /// // A lone tile has no matching edges.
+    /// assert_eq!(tilemap.get_tile((3, 3), 0).unwrap().index, 16);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the terrain does not exist, the chunk does not
+    /// exist and auto chunk is disabled or the layer does not exist.
+    pub fn insert_terrain<P: Into<Point3>>(
+        &mut self,
+        point: P,
+        sprite_order: usize,
+        terrain: u32,
+    ) -> TilemapResult<()> {
+        self.insert_terrains(vec![(point, sprite_order, terrain)])
+    }
+
+    /// Returns the terrain painted at a point and sprite order, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .auto_chunk()
+    ///     .add_terrain(7, AutotileRules::sequential(AutotileKind::Bitmask, 16))
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// tilemap.insert_terrain((3, 3), 0, 7).unwrap();
+    ///
+    /// assert_eq!(tilemap.get_terrain((3, 3), 0), Some(7));
+    /// assert_eq!(tilemap.get_terrain((3, 3), 1), None);
+    /// ```
+    pub fn get_terrain<P: Into<Point3>>(&self, point: P, sprite_order: usize) -> Option<u32> {
+        self.autotiles.terrain(point.into(), sprite_order)
+    }
+
+    /// Chooses the sprites of the tiles painted at the points and around them
+    /// again.
+    fn update_autotiles(&mut self, points: Vec<(Point3, usize)>) -> TilemapResult<()> {
+        if points.is_empty() {
+            return Ok(());
+        }
+
+        let mut updates = HashSet::default();
+        for (point, sprite_order) in points.iter() {
+            for neighbor in self.neighbor_points(*point) {
+                updates.insert((neighbor, *sprite_order));
+            }
+        }
+        // Only the given points may need a new chunk, the tiles around them
+        // are skipped if their chunk was removed.
+        updates.retain(|(point, _)| {
+            let chunk_point: Point2 = self.point_to_chunk_point(*point).into();
+            self.chunks.contains_key(&chunk_point)
+        });
+        updates.extend(points);
+
+        let mut tiles = Vec::new();
+        for (point, sprite_order) in updates {
+            let terrain = match self.autotiles.terrain(point, sprite_order) {
+                Some(terrain) => terrain,
+                None => continue,
+            };
+            let same = self
+                .neighbor_points(point)
+                .into_iter()
+                .map(|neighbor| self.autotiles.terrain(neighbor, sprite_order) == Some(terrain))
+                .collect::<Vec<bool>>();
+            let sprite_index = match self
+                .autotiles
+                .rules(terrain)
+                .and_then(|rules| rules.sprite_index(&same))
+            {
+                Some(sprite_index) => sprite_index,
+                None => continue,
+            };
+            // Only the sprite changes, the tile keeps how it is drawn.
+            let tile = self
+                .get_tile(point, sprite_order)
+                .copied()
+                .unwrap_or_default();
+            tiles.push(Tile {
+                point,
+                sprite_order,
+                sprite_index,
+                tint: tile.color,
+                flip: tile.flip,
+                rotation: tile.rotation,
+            });
+        }
+        self.set_tiles(tiles)
+    }
+
+    /// Returns the fog state of a tile, or `None` if the tilemap has no fog of
+    /// war or the chunk of the tile does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{fog::{FogOfWar, FogState}, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .fog_of_war(FogOfWar::default())
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// assert_eq!(tilemap.fog_state((1, 1)), Some(FogState::Hidden));
+    /// assert_eq!(tilemap.fog_state((100, 1)), None);
+    /// ```
+    pub fn fog_state<P: Into<Point3>>(&self, point: P) -> Option<FogState> {
+        self.fog_of_war?;
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let chunk = self.chunks.get(&chunk_point)?;
+        Some(chunk.fog_state(self.fog_index(point)))
+    }
+
+    /// Sets the fog state of a tile, such as to reveal parts of the map
+    /// without an observer.
+    ///
+    /// A tile that is visible becomes explored when the observers next look
+    /// around and do not see it.
+    ///
+    /// Only the fog of the mesh of a spawned chunk is updated, its tiles are
+    /// not built again.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{fog::{FogOfWar, FogState}, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .fog_of_war(FogOfWar::default())
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// assert!(tilemap.set_fog_state((1, 1), FogState::Explored).is_ok());
+    /// assert!(tilemap.set_fog_state((100, 1), FogState::Explored).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Fails if the tilemap has no fog of war or the chunk of the tile does
+    /// not exist.
+    pub fn set_fog_state<P: Into<Point3>>(
+        &mut self,
+        point: P,
+        state: FogState,
+    ) -> TilemapResult<()> {
+        if self.fog_of_war.is_none() {
+            return Err(ErrorKind::MissingFogOfWar.into());
+        }
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let index = self.fog_index(point);
+        let area = self.fog_area();
+        let chunk = match self.chunks.get_mut(&chunk_point) {
+            Some(chunk) => chunk,
+            None => return Err(ErrorKind::MissingChunk.into()),
+        };
+        chunk.set_fog_state(index, state, area);
+        if chunk.mesh().is_some() {
+            self.fog_changed.insert(chunk_point);
+        }
+        if let Some(fog_visible) = &mut self.fog_visible {
+            if state == FogState::Visible {
+                fog_visible.insert(point);
+            } else {
+                fog_visible.remove(&point);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the fog state of a tile in its chunk.
+    fn fog_index(&self, point: Point3) -> usize {
+        let tile_point = self.point_to_tile_point(point);
+        let tile_point = Point3::new(tile_point.x, tile_point.y, 0);
+        self.chunk_dimensions.encode_point_unchecked(tile_point)
+    }
+
+    /// Returns the amount of fog states in a chunk.
+    fn fog_area(&self) -> usize {
+        (self.chunk_dimensions.width * self.chunk_dimensions.height) as usize
+    }
+
+    /// Returns the points that were visible when the fog of war was last
+    /// lifted, or `None` if they are not known since the tilemap was
+    /// deserialized.
+    pub(crate) fn fog_visible(&self) -> Option<&HashSet<Point3>> {
+        self.fog_visible.as_ref()
+    }
+
+    /// Returns the spawned chunks of which the fog was set since it was last
+    /// sent to their meshes.
+    pub(crate) fn fog_changed(&self) -> &HashSet<Point2> {
+        &self.fog_changed
+    }
+
+    /// Takes the spawned chunks of which the fog was set, to send their fog
+    /// to their meshes.
+    pub(crate) fn take_fog_changed(&mut self) -> HashSet<Point2> {
+        mem::take(&mut self.fog_changed)
+    }
+
+    /// Returns the points of a chunk with a visible fog state.
+    fn chunk_fog_visible(&self, chunk_point: Point2) -> Vec<Point3> {
+        let width = self.chunk_dimensions.width as i32;
+        let chunk = match self.chunks.get(&chunk_point) {
+            Some(chunk) => chunk,
+            None => return Vec::new(),
+        };
+        (0..self.fog_area())
+            .filter(|index| chunk.fog_state(*index) == FogState::Visible)
+            .map(|index| {
+                let index = index as i32;
+                let tile_point = Point3::new(index % width, index / width, 0);
+                self.tile_point_to_point(chunk_point, tile_point)
+            })
+            .collect()
+    }
+
+    /// Makes the points visible, and those that were visible before but are
+    /// not anymore explored. Returns the points of the chunks that changed.
+    pub(crate) fn update_fog(&mut self, visible: HashSet<Point3>) -> HashSet<Point2> {
+        let previous = match self.fog_visible.take() {
+            Some(previous) => previous,
+            None => self
+                .chunks
+                .keys()
+                .flat_map(|chunk_point| self.chunk_fog_visible(*chunk_point))
+                .collect(),
+        };
+        let explored = previous
+            .difference(&visible)
+            .map(|point| (*point, FogState::Explored));
+        let seen = visible.iter().map(|point| (*point, FogState::Visible));
+        let area = self.fog_area();
+
+        let mut changed = HashSet::default();
+        for (point, state) in explored.chain(seen) {
+            let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+            let index = self.fog_index(point);
+            if let Some(chunk) = self.chunks.get_mut(&chunk_point) {
+                if chunk.fog_state(index) != state {
+                    chunk.set_fog_state(index, state, area);
+                    changed.insert(chunk_point);
+                }
+            }
+        }
+        self.fog_visible = Some(visible);
+        changed
+    }
+
+    /// Sets the data value of a tile in a data layer, creating a chunk if
+    /// necessary and the tilemap automatically creates chunks.
+    ///
+    /// Data values are never drawn, so nothing has to be spawned again.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the data layer does not exist, or the chunk does not exist and
+    /// the tilemap does not automatically create chunks.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// const MOVEMENT_COST: usize = 0;
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .add_data_layer(LayerKind::Sparse, MOVEMENT_COST)
+    ///     .auto_chunk()
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// assert!(tilemap.insert_data((3, 1), MOVEMENT_COST, 5).is_ok());
+    /// assert!(tilemap.insert_data((3, 1), 1, 5).is_err());
+    /// assert_eq!(tilemap.get_data((3, 1), MOVEMENT_COST), Some(5));
+    /// ```
+    pub fn insert_data<P>(&mut self, point: P, data_layer: usize, value: i32) -> TilemapResult<()>
+    where
+        P: Into<Point3>,
+    {
+        let kind = match self.data_layers.get(&data_layer) {
+            Some(kind) => *kind,
+            None => return Err(ErrorKind::DataLayerDoesNotExist(data_layer).into()),
+        };
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        if !self.chunks.contains_key(&chunk_point)
+            && self.auto_flags.contains(AutoFlags::AUTO_CHUNK)
+        {
+            self.insert_chunk(chunk_point)?;
+        }
+        let index = self
+            .chunk_dimensions
+            .encode_point_unchecked(self.point_to_tile_point(point));
+        let area = self.chunk_dimensions.area() as usize;
+        let chunk = match self.chunks.get_mut(&chunk_point) {
+            Some(chunk) => chunk,
+            None => return Err(ErrorKind::MissingChunk.into()),
+        };
+        let old = chunk.get_data(index, data_layer);
+        chunk.set_data(index, data_layer, kind, value, area);
+        self.record_data_change(chunk_point, index, data_layer, old, Some(value));
+        Ok(())
+    }
+
+    /// Gets the data value of a tile in a data layer.
+    ///
+    /// Dense data layers have a value of 0 for tiles that were never set,
+    /// sparse data layers have none.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.add_data_layer(LayerKind::Dense, 0).unwrap();
+    /// tilemap.add_data_layer(LayerKind::Sparse, 1).unwrap();
+    ///
+    /// tilemap.insert_data((2, 2), 0, 7).unwrap();
+    /// tilemap.insert_data((2, 2), 1, 7).unwrap();
+    ///
+    /// assert_eq!(tilemap.get_data((2, 2), 0), Some(7));
+    /// assert_eq!(tilemap.get_data((3, 2), 0), Some(0));
+    /// assert_eq!(tilemap.get_data((3, 2), 1), None);
+    /// ```
+    pub fn get_data<P: Into<Point3>>(&self, point: P, data_layer: usize) -> Option<i32> {
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let chunk = self.chunks.get(&chunk_point)?;
+        let index = self
+            .chunk_dimensions
+            .encode_point_unchecked(self.point_to_tile_point(point));
+        match (
+            chunk.get_data(index, data_layer),
+            self.data_layers.get(&data_layer),
+        ) {
+            (Some(value), _) => Some(value),
+            // The chunk only has the layer once a value was set.
+            (None, Some(LayerKind::Dense)) => Some(0),
+            (None, _) => None,
+        }
+    }
+
+    /// Clears the data value of a tile in a data layer, which is 0 again for
+    /// dense data layers.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the data layer does not exist.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.add_data_layer(LayerKind::Sparse, 0).unwrap();
+    /// tilemap.insert_data((2, 2), 0, 7).unwrap();
+    ///
+    /// assert!(tilemap.clear_data((2, 2), 0).is_ok());
+    /// assert_eq!(tilemap.get_data((2, 2), 0), None);
+    /// ```
+    pub fn clear_data<P: Into<Point3>>(
+        &mut self,
+        point: P,
+        data_layer: usize,
+    ) -> TilemapResult<()> {
+        if !self.data_layers.contains_key(&data_layer) {
+            return Err(ErrorKind::DataLayerDoesNotExist(data_layer).into());
+        }
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let index = self
+            .chunk_dimensions
+            .encode_point_unchecked(self.point_to_tile_point(point));
+        if let Some(chunk) = self.chunks.get_mut(&chunk_point) {
+            let old = chunk.get_data(index, data_layer);
+            chunk.remove_data(index, data_layer);
+            let new = chunk.get_data(index, data_layer);
+            self.record_data_change(chunk_point, index, data_layer, old, new);
+        }
+        Ok(())
+    }
+
+    /// Registers a type of tile data and chunk metadata, so the values of the
+    /// type that were deserialized are found by [`get_tile_data`] and
+    /// [`get_chunk_metadata`].
+    ///
+    /// Values are deserialized without their type, which they get back when
+    /// the type is registered. Inserting a value registers its type, chunks
+    /// that are loaded from the chunk storage give values of the registered
+    /// types their type back and values that are borrowed mutably do so on
+    /// their own.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, storage::FileChunkStorage};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "96d9c440-e9db-4638-9321-a6687d98c7a1"]
+    /// struct Door {
+    ///     locked: bool,
+    /// }
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let directory = std::env::temp_dir().join("bevy_tilemap_register_example");
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle.clone(), 32, 32);
+    /// tilemap.set_chunk_storage(FileChunkStorage::new(&directory));
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile_data((3, 1), 0, Door { locked: true }).unwrap();
+    /// tilemap.unload_chunk((0, 0)).unwrap();
+    ///
+    /// // Another tilemap loads the chunk without knowing its tile data types.
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.set_chunk_storage(FileChunkStorage::new(&directory));
+    /// tilemap.spawn_chunk((0, 0)).unwrap();
+    /// assert_eq!(tilemap.get_tile_data::<_, Door>((3, 1), 0), None);
+    ///
+    /// tilemap.register_tile_data::<Door>();
+    /// assert_eq!(tilemap.get_tile_data((3, 1), 0), Some(&Door { locked: true }));
+    /// # std::fs::remove_dir_all(&directory).unwrap();
+    /// ```
+    ///
+    /// [`get_tile_data`]: Tilemap::get_tile_data
+    /// [`get_chunk_metadata`]: Tilemap::get_chunk_metadata
+    pub fn register_tile_data<T: TileData>(&mut self) {
+        if self.tile_data_types.register::<T>() {
+            for chunk in self.chunks.values_mut() {
+                chunk.type_data(&self.tile_data_types);
+            }
+        }
+    }
+
+    /// Attaches a typed value to a tile, replacing the value of the same type,
+    /// creating a chunk if necessary and the tilemap automatically creates
+    /// chunks.
+    ///
+    /// A tile can have a value of every type, whether there is a sprite or
+    /// not. The values move with their chunk and are cleared with the tile.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the chunk does not exist and the tilemap does not
+    /// automatically create chunks, or if a transaction is in progress as
+    /// tile data is not recorded.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "96d9c440-e9db-4638-9321-a6687d98c7a1"]
+    /// struct Door {
+    ///     locked: bool,
+    /// }
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert!(tilemap.insert_tile_data((3, 1), 0, Door { locked: true }).is_err());
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// assert!(tilemap.insert_tile_data((3, 1), 0, Door { locked: true }).is_ok());
+    /// assert_eq!(tilemap.get_tile_data((3, 1), 0), Some(&Door { locked: true }));
+    /// ```
+    pub fn insert_tile_data<P, T>(
+        &mut self,
+        point: P,
+        sprite_order: usize,
+        value: T,
+    ) -> TilemapResult<()>
+    where
+        P: Into<Point3>,
+        T: TileData,
+    {
+        if self.history.is_recording() {
+            return Err(ErrorKind::TransactionInProgress.into());
+        }
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        if !self.chunks.contains_key(&chunk_point)
+            && self.auto_flags.contains(AutoFlags::AUTO_CHUNK)
+        {
+            self.insert_chunk(chunk_point)?;
+        }
+        if !self.chunks.contains_key(&chunk_point) {
+            return Err(ErrorKind::MissingChunk.into());
+        }
+        self.register_tile_data::<T>();
+        let index = self
+            .chunk_dimensions
+            .encode_point_unchecked(self.point_to_tile_point(point));
+        if let Some(chunk) = self.chunks.get_mut(&chunk_point) {
+            chunk.insert_tile_data(index, sprite_order, value);
+        }
+        Ok(())
+    }
+
+    /// Gets a reference to the typed value attached to a tile.
+    ///
+    /// Values that were deserialized are only found once their type is
+    /// registered with [`register_tile_data`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "96d9c440-e9db-4638-9321-a6687d98c7a1"]
+    /// struct Door {
+    ///     locked: bool,
+    /// }
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile_data((3, 1), 0, Door { locked: false }).unwrap();
+    ///
+    /// assert_eq!(tilemap.get_tile_data((3, 1), 0), Some(&Door { locked: false }));
+    /// assert_eq!(tilemap.get_tile_data::<_, Door>((3, 1), 1), None);
+    /// ```
+    ///
+    /// [`register_tile_data`]: Tilemap::register_tile_data
+    pub fn get_tile_data<P, T>(&self, point: P, sprite_order: usize) -> Option<&T>
+    where
+        P: Into<Point3>,
+        T: TileData,
+    {
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let index = self
+            .chunk_dimensions
+            .encode_point_unchecked(self.point_to_tile_point(point));
+        self.chunks
+            .get(&chunk_point)?
+            .get_tile_data(index, sprite_order)
+    }
+
+    /// Gets a mutable reference to the typed value attached to a tile.
+    ///
+    /// Returns `None` while a transaction is in progress, as tile data is not
+    /// recorded.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "96d9c440-e9db-4638-9321-a6687d98c7a1"]
+    /// struct Door {
+    ///     locked: bool,
+    /// }
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile_data((3, 1), 0, Door { locked: true }).unwrap();
+    ///
+    /// if let Some(door) = tilemap.get_tile_data_mut::<_, Door>((3, 1), 0) {
+    ///     door.locked = false;
+    /// }
+    /// assert_eq!(tilemap.get_tile_data((3, 1), 0), Some(&Door { locked: false }));
+    /// ```
+    pub fn get_tile_data_mut<P, T>(&mut self, point: P, sprite_order: usize) -> Option<&mut T>
+    where
+        P: Into<Point3>,
+        T: TileData,
+    {
+        if self.history.is_recording() {
+            return None;
+        }
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let index = self
+            .chunk_dimensions
+            .encode_point_unchecked(self.point_to_tile_point(point));
+        self.chunks
+            .get_mut(&chunk_point)?
+            .get_tile_data_mut(index, sprite_order)
+    }
+
+    /// Removes the typed value attached to a tile and returns it.
+    ///
+    /// Returns `None` while a transaction is in progress, as tile data is not
+    /// recorded.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::reflect::{Reflect, TypeUuid};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    /// #[uuid = "96d9c440-e9db-4638-9321-a6687d98c7a1"]
+    /// struct Door {
+    ///     locked: bool,
+    /// }
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile_data((3, 1), 0, Door { locked: true }).unwrap();
+    ///
+    /// assert_eq!(tilemap.remove_tile_data((3, 1), 0), Some(Door { locked: true }));
+    /// assert_eq!(tilemap.get_tile_data::<_, Door>((3, 1), 0), None);
+    /// ```
+    pub fn remove_tile_data<P, T>(&mut self, point: P, sprite_order: usize) -> Option<T>
+    where
+        P: Into<Point3>,
+        T: TileData,
+    {
+        if self.history.is_recording() {
+            return None;
+        }
+        let point = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let index = self
+            .chunk_dimensions
+            .encode_point_unchecked(self.point_to_tile_point(point));
+        self.chunks
+            .get_mut(&chunk_point)?
+            .remove_tile_data(index, sprite_order)
+    }
+
+    /// Gets a raw tile from a given point and z order.
+    ///
+    /// This is different thant he usual [`Tile`] struct in that it only
+    /// contains the sprite index and the tint.
+    ///
+    /// [`Tile`]: crate::tile::Tile
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::render::prelude::*;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let point = (9, 3);
+    /// let sprite_index = 3;
+    /// let tile = Tile { point, sprite_index, ..Default::default() };
+    ///
+    /// assert!(tilemap.insert_tile(tile).is_ok());
+    /// assert_eq!(tilemap.get_tile((9, 3), 0), Some(&RawTile { index: 3, color: Color::WHITE, ..Default::default() }));
+    /// assert_eq!(tilemap.get_tile((10, 4), 0), None);
+    /// ```
+    pub fn get_tile<P>(&mut self, point: P, sprite_order: usize) -> Option<&RawTile>
+    where
+        P: Into<Point3>,
+    {
+        self.raw_tile(point.into(), sprite_order)
+    }
+
+    /// Returns a raw tile from a global tile point and sprite order.
+    pub(crate) fn raw_tile(&self, point: Point3, sprite_order: usize) -> Option<&RawTile> {
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let tile_point = self.point_to_tile_point(point);
+        let chunk = self.chunks.get(&chunk_point)?;
+        let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
+        chunk.get_tile(index, sprite_order, point.z as usize)
+    }
+
+    /// Returns the global tile points and sprite orders of all the tiles that
+    /// exist.
+    pub(crate) fn tile_points(&self) -> Vec<(Point3, usize)> {
+        self.chunks
+            .keys()
+            .flat_map(|chunk_point| self.chunk_tile_points(*chunk_point))
+            .collect()
+    }
+
+    /// Returns the global tile points and sprite orders of the tiles that
+    /// exist in a chunk.
+    pub(crate) fn chunk_tile_points(&self, chunk_point: Point2) -> Vec<(Point3, usize)> {
+        let width = self.chunk_dimensions.width as i32;
+        let height = self.chunk_dimensions.height as i32;
+        let mut points = Vec::new();
+        if let Some(chunk) = self.chunks.get(&chunk_point) {
+            for (index, sprite_order, z_depth) in chunk.tile_indices() {
+                let index = index as i32;
+                let point = Point3::new(
+                    chunk_point.x * width + index % width - width / 2,
+                    chunk_point.y * height + (index / width) % height - height / 2,
+                    z_depth as i32,
+                );
+                points.push((point, sprite_order));
+            }
+        }
+        points
+    }
+
+    /// Returns the global tile points and sprite orders of the tiles that
+    /// exist in the square of a radius around a point, at every z depth.
+    pub(crate) fn tile_points_around(&self, center: Point3, radius: u32) -> Vec<(Point3, usize)> {
+        let radius = radius as i32;
+        let mut points = Vec::new();
+        for y in center.y - radius..=center.y + radius {
+            for x in center.x - radius..=center.x + radius {
+                for z in 0..self.chunk_dimensions.depth as i32 {
+                    for sprite_order in 0..self.layers.len() {
+                        let point = Point3::new(x, y, z);
+                        if self.raw_tile(point, sprite_order).is_some() {
+                            points.push((point, sprite_order));
+                        }
+                    }
+                }
+            }
+        }
+        points
+    }
+
+    /// Sets the colors of raw tiles at global tile points and sprite orders,
+    /// flagging each chunk that was changed once.
+    pub(crate) fn set_tile_colors(&mut self, colors: Vec<(Point3, usize, Color)>) {
+        let mut modified = HashSet::default();
+        for (point, sprite_order, color) in colors {
+            let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+            let tile_point = self.point_to_tile_point(point);
+            let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
+            let tile = self
+                .chunks
+                .get_mut(&chunk_point)
+                .and_then(|chunk| chunk.get_tile_mut(index, sprite_order, point.z as usize));
+            if let Some(tile) = tile {
+                tile.color = color;
+                modified.insert(chunk_point);
+            }
+        }
+        for point in modified {
+            self.chunk_events
+                .send(TilemapChunkEvent::Modified { point });
+        }
+    }
+
+    /// Gets a mutable raw tile from a given point and z order.
+    ///
+    /// This is different thant he usual [`Tile`] struct in that it only
+    /// contains the sprite index and the tint.
+    ///
+    /// [`Tile`]: crate::tile::Tile
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::render::prelude::*;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let point = (2, 5);
+    /// let sprite_index = 2;
+    /// let tile = Tile { point, sprite_index, ..Default::default() };
+    ///
+    /// assert!(tilemap.insert_tile(tile).is_ok());
+    /// assert_eq!(tilemap.get_tile_mut((2, 5), 0), Some(&mut RawTile { index: 2, color: Color::WHITE, ..Default::default() }));
+    /// assert_eq!(tilemap.get_tile_mut((1, 4), 0), None);
+    /// ```
+    pub fn get_tile_mut<P>(&mut self, point: P, sprite_order: usize) -> Option<&mut RawTile>
+    where
+        P: Into<Point3>,
+    {
+        let point: Point3 = point.into();
+        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+        let tile_point = self.point_to_tile_point(point);
+        if self.tracks_tiles() {
+            if let Some(tile) = self.raw_tile(point, sprite_order).copied() {
+                self.tile_writes.push((point, sprite_order, tile));
+            }
+        }
+        let chunk = self.chunks.get_mut(&chunk_point)?;
+        let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
+        let mut layers = HashMap::default();
+        layers.insert(sprite_order, chunk_point);
+        self.chunk_events.send(TilemapChunkEvent::Modified {
+            point: chunk.point(),
+        });
+        chunk.get_tile_mut(index, sprite_order, point.z as usize)
+    }
+
+    /// Returns the points of all tiles next to a point with their raw tiles
+    /// at a sprite order, if there are any.
+    ///
+    /// The neighbors are in clockwise order starting at the first one at or
+    /// after north. Square topologies have 8 neighbors starting at north,
+    /// including the diagonals. Hex topologies have 6 neighbors starting at
+    /// north east for hexes with a pointy top and at north for hexes with a
+    /// flat top. Neighbors in other chunks are included.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::render::prelude::*;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{point::Point3, prelude::*, chunk::RawTile};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_chunk((1, 0)).unwrap();
+    ///
+    /// // The tiles are in different chunks.
+    /// let tile = Tile { point: (16, 0), sprite_index: 4, ..Default::default() };
+    /// tilemap.insert_tile(tile).unwrap();
+    ///
+    /// let neighbors = tilemap.neighbors((15, 0), 0);
+    /// assert_eq!(neighbors.len(), 8);
+    /// assert_eq!(neighbors[0], (Point3::new(15, 1, 0), None));
+    /// assert_eq!(
+    ///     neighbors[2],
+    ///     (Point3::new(16, 0, 0), Some(&RawTile { index: 4, color: Color::WHITE, ..Default::default() }))
+    /// );
+    /// ```
+    pub fn neighbors<P: Into<Point3>>(
+        &self,
+        point: P,
+        sprite_order: usize,
+    ) -> Vec<(Point3, Option<&RawTile>)> {
+        self.neighbor_points(point.into())
+            .into_iter()
+            .map(|neighbor| (neighbor, self.raw_tile(neighbor, sprite_order)))
+            .collect()
+    }
+
+    /// Returns the points of the tiles that share an edge with a point with
+    /// their raw tiles at a sprite order, if there are any.
+    ///
+    /// This is the same as [`neighbors`] without the diagonals of square
+    /// topologies, which leaves north, east, south and west. Hex topologies
+    /// have the same 6 neighbors.
+    ///
+    /// [`neighbors`]: Tilemap::neighbors
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{point::Point3, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .topology(GridTopology::HexOddRows)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// let square = Tilemap::new(Handle::weak(HandleId::random::<TextureAtlas>()), 32, 32);
+    /// let points = square
+    ///     .edge_neighbors((0, 0), 0)
+    ///     .into_iter()
+    ///     .map(|(point, _)| point)
+    ///     .collect::<Vec<Point3>>();
+    /// assert_eq!(
+    ///     points,
+    ///     vec![Point3::new(0, 1, 0), Point3::new(1, 0, 0), Point3::new(0, -1, 0), Point3::new(-1, 0, 0)]
+    /// );
+    ///
+    /// assert_eq!(tilemap.edge_neighbors((0, 0), 0).len(), 6);
+    /// ```
+    pub fn edge_neighbors<P: Into<Point3>>(
+        &self,
+        point: P,
+        sprite_order: usize,
+    ) -> Vec<(Point3, Option<&RawTile>)> {
+        let neighbors = self.neighbors(point, sprite_order);
+        if self.topology != GridTopology::Square {
+            return neighbors;
+        }
+        // Every other neighbor of a square is a diagonal.
+        neighbors.into_iter().step_by(2).collect()
+    }
+
+    /// Finds the cheapest path between two points with A*, moving over the
+    /// tiles of a sprite order.
+    ///
+    /// The cost function returns the cost of entering a point with its raw
+    /// tile, or `None` if it can not be entered. Costs of 0 are counted as 1.
+    /// Only points in chunks that exist are searched, which is what stops a
+    /// search without a path.
+    ///
+    /// Returns `None` if there is no path.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{pathfinding::Connectivity, point::Point3, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_chunk((1, 0)).unwrap();
+    ///
+    /// // Every tile can be entered, also across chunks.
+    /// let path = tilemap.find_path((0, 0), (20, 0), 0, Connectivity::Diagonals, |_, _| Some(1));
+    /// assert_eq!(path.unwrap().cost, 20);
+    ///
+    /// // The chunk at (2, 0) does not exist.
+    /// let path = tilemap.find_path((0, 0), (50, 0), 0, Connectivity::Diagonals, |_, _| Some(1));
+    /// assert!(path.is_none());
+    /// ```
+    pub fn find_path<P, F>(
+        &self,
+        start: P,
+        goal: P,
+        sprite_order: usize,
+        connectivity: Connectivity,
+        cost: F,
+    ) -> Option<Path>
+    where
+        P: Into<Point3>,
+        F: FnMut(Point3, Option<&RawTile>) -> Option<u32>,
+    {
+        let (start, goal) = (start.into(), goal.into());
+        let mut costs = TileCosts::new(self, sprite_order, connectivity, cost);
+        costs.cost(goal)?;
+        let topology = self.topology;
+        pathfinding::a_star(
+            start,
+            goal,
+            |point| costs.moves(point),
+            |point| pathfinding::distance(topology, connectivity, point, goal),
+        )
+    }
+
+    /// Finds the cheapest paths from every point to the closest of many
+    /// goals, moving over the tiles of a sprite order.
+    ///
+    /// The costs are the same as those of [`find_path`]. Points of which the
+    /// cheapest path costs more than the max cost are left out, if there is
+    /// one. Any amount of agents can then follow the returned map.
+    ///
+    /// [`find_path`]: Tilemap::find_path
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{pathfinding::Connectivity, point::Point3, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let goals = vec![(-5, 0), (5, 0)];
+    /// let map = tilemap.dijkstra_map(goals, 0, Connectivity::Edges, Some(10), |_, _| Some(1));
+    ///
+    /// assert_eq!(map.cost((1, 0)), Some(4));
+    /// assert_eq!(map.next_step((1, 0)), Some(Point3::new(2, 0, 0)));
+    /// assert_eq!(map.cost((5, 0)), Some(0));
+    /// assert_eq!(map.cost((5, 11)), None);
+    /// ```
+    pub fn dijkstra_map<P, I, F>(
+        &self,
+        goals: I,
+        sprite_order: usize,
+        connectivity: Connectivity,
+        max_cost: Option<u32>,
+        cost: F,
+    ) -> DijkstraMap
+    where
+        P: Into<Point3>,
+        I: IntoIterator<Item = P>,
+        F: FnMut(Point3, Option<&RawTile>) -> Option<u32>,
+    {
+        let goals = goals.into_iter().map(|goal| goal.into()).collect();
+        let mut costs = TileCosts::new(self, sprite_order, connectivity, cost);
+        // Searched backwards, moving from a neighbor costs entering the point.
+        let moves = |point| match costs.cost(point) {
+            Some(cost) => costs
+                .moves(point)
+                .into_iter()
+                .map(|(neighbor, _)| (neighbor, cost))
+                .collect(),
+            None => Vec::new(),
+        };
+        pathfinding::dijkstra(goals, moves, max_cost)
+    }
+
+    /// Returns the region of connected tiles of a sprite order around a start
+    /// point for which a predicate returns `true`, across chunks.
+    ///
+    /// The predicate is given the point and raw tile, if there is one. Tiles
+    /// are connected through their neighbors by the connectivity, like in
+    /// [`find_path`]. Only tiles in chunks that exist are searched. If the
+    /// start point itself does not match, the region is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{pathfinding::Connectivity, point::Point3, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// const FLOOR: usize = 0;
+    /// const WALL: usize = 1;
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// // A room of 3 by 3 floor tiles inside of walls.
+    /// let mut tiles = Vec::new();
+    /// for y in -2..=2i32 {
+    ///     for x in -2..=2i32 {
+    ///         let sprite_index = if x.abs() == 2 || y.abs() == 2 { WALL } else { FLOOR };
+    ///         tiles.push(Tile { point: (x, y), sprite_index, ..Default::default() });
+    ///     }
+    /// }
+    /// tilemap.insert_tiles(tiles).unwrap();
+    ///
+    /// let room = tilemap.flood_region((0, 0), 0, Connectivity::Edges, |_, tile| {
+    ///     tile.map_or(false, |tile| tile.index == FLOOR)
+    /// });
+    /// assert_eq!(room.len(), 9);
+    /// assert!(room.contains(&Point3::new(1, -1, 0)));
+    /// ```
+    ///
+    /// [`find_path`]: Tilemap::find_path
+    pub fn flood_region<P, F>(
+        &self,
+        start: P,
+        sprite_order: usize,
+        connectivity: Connectivity,
+        mut predicate: F,
+    ) -> HashSet<Point3>
+    where
+        P: Into<Point3>,
+        F: FnMut(Point3, Option<&RawTile>) -> bool,
+    {
+        let start = start.into();
+        let mut costs = TileCosts::new(self, sprite_order, connectivity, |point, tile| {
+            if predicate(point, tile) {
+                Some(1)
+            } else {
+                None
+            }
+        });
+        let mut region = HashSet::default();
+        if costs.cost(start).is_none() {
+            return region;
+        }
+        region.insert(start);
+        let mut open = vec![start];
+        while let Some(point) = open.pop() {
+            for (neighbor, _) in costs.moves(point) {
+                if region.insert(neighbor) {
+                    open.push(neighbor);
+                }
+            }
+        }
+        region
+    }
+
+    /// Sets every tile of a region at a sprite order to a raw tile.
+    ///
+    /// The tiles are inserted all at once, so every chunk that is touched is
+    /// only rebuilt once.
+    ///
+    /// # Errors
+    ///
+    /// Fails the same as [`insert_tiles`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{chunk::RawTile, pathfinding::Connectivity, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// const WATER: usize = 3;
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// // Every empty tile of the chunk.
+    /// let region = tilemap.flood_region((0, 0), 0, Connectivity::Edges, |_, tile| tile.is_none());
+    /// assert_eq!(region.len(), 32 * 32);
+    ///
+    /// let water = RawTile { index: WATER, ..Default::default() };
+    /// assert!(tilemap.fill_region(region, 0, water).is_ok());
+    /// assert_eq!(tilemap.get_tile((-16, 15), 0), Some(&water));
+    /// ```
+    ///
+    /// [`insert_tiles`]: Tilemap::insert_tiles
+    pub fn fill_region<P, I>(
+        &mut self,
+        region: I,
+        sprite_order: usize,
+        tile: RawTile,
+    ) -> TilemapResult<()>
+    where
+        P: Into<Point3>,
+        I: IntoIterator<Item = P>,
+    {
+        let tiles = region.into_iter().map(|point| Tile {
+            point: point.into(),
+            sprite_order,
+            sprite_index: tile.index,
+            tint: tile.color,
+            flip: tile.flip,
+            rotation: tile.rotation,
+        });
+        self.insert_tiles(tiles)
+    }
+
+    /// Returns the points of the tiles of a shape, each once.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{point::{Point2, Point3}, prelude::*, shape::Shape};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let outline = Shape::Rectangle {
+    ///     corner: Point2::new(0, 0),
+    ///     opposite: Point2::new(3, 2),
+    ///     filled: false,
+    /// };
+    /// let points = tilemap.shape_points(&outline);
+    /// assert_eq!(points.len(), 10);
+    /// assert!(!points.contains(&Point3::new(1, 1, 0)));
+    ///
+    /// let circle = Shape::Circle { center: Point2::new(0, 0), radius: 1, filled: true };
+    /// assert_eq!(tilemap.shape_points(&circle).len(), 9);
+    /// ```
+    pub fn shape_points(&self, shape: &Shape) -> Vec<Point3> {
+        shape.points(self)
+    }
+
+    /// Draws a shape with a sprite index at a sprite order.
+    ///
+    /// The tiles are inserted all at once, so every chunk that is touched is
+    /// only rebuilt once.
+    ///
+    /// # Errors
+    ///
+    /// Fails the same as [`insert_tiles`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{point::Point2, prelude::*, shape::Shape};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// const WALL: usize = 1;
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// let triangle = Shape::Polygon {
+    ///     vertices: vec![Point2::new(0, 0), Point2::new(6, 0), Point2::new(0, 6)],
+    ///     filled: true,
+    /// };
+    /// assert!(tilemap.draw_shape(&triangle, 0, WALL).is_ok());
+    /// assert_eq!(tilemap.get_tile((1, 1), 0).map(|tile| tile.index), Some(WALL));
+    /// assert_eq!(tilemap.get_tile((5, 5), 0), None);
+    /// ```
+    ///
+    /// [`insert_tiles`]: Tilemap::insert_tiles
+    pub fn draw_shape(
+        &mut self,
+        shape: &Shape,
+        sprite_order: usize,
+        sprite_index: usize,
+    ) -> TilemapResult<()> {
+        let tiles = self.shape_points(shape).into_iter().map(|point| Tile {
+            point,
+            sprite_order,
+            sprite_index,
+            ..Default::default()
+        });
+        self.insert_tiles(tiles)
+    }
+
+    /// Copies the tiles of every sprite order and z depth between two opposite
+    /// corners into a stamp.
+    ///
+    /// The stamp does not depend on the chunks it was copied from, so it can
+    /// be pasted on tilemaps with other chunk dimensions.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{point::Point3, prelude::*, stamp::StampTransform};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle.clone(), 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile(Tile { point: (3, 4), sprite_index: 1, ..Default::default() }).unwrap();
+    ///
+    /// let stamp = tilemap.copy_stamp((2, 2), (5, 4));
+    /// assert_eq!((stamp.width(), stamp.height()), (4, 3));
+    /// assert_eq!(stamp.tiles()[0].point, Point3::new(1, 2, 0));
+    ///
+    /// // Into a tilemap with smaller chunks that are made as needed.
+    /// let mut other = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .chunk_dimensions(8, 8, 1)
+    ///     .auto_chunk()
+    ///     .finish()
+    ///     .unwrap();
+    /// other.paste_stamp(&stamp, (2, 2), StampTransform::default()).unwrap();
+    /// assert_eq!(other.get_tile((3, 4), 0).map(|tile| tile.index), Some(1));
+    /// ```
+    pub fn copy_stamp<P: Into<Point2>>(&self, corner: P, opposite: P) -> TileStamp {
+        let (corner, opposite): (Point2, Point2) = (corner.into(), opposite.into());
+        let min = Point2::new(corner.x.min(opposite.x), corner.y.min(opposite.y));
+        let max = Point2::new(corner.x.max(opposite.x), corner.y.max(opposite.y));
+        let layout = self.topology.hex_layout();
+        let sprite_orders = self
+            .layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| layer.is_some())
+            .map(|(sprite_order, _)| sprite_order)
+            .collect::<Vec<usize>>();
+
+        let width = (max.x - min.x + 1) as u32;
+        let height = (max.y - min.y + 1) as u32;
+        let mut stamp = TileStamp::new(width, height);
+        for z in 0..self.chunk_dimensions.depth as i32 {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    let point = Point3::new(x, y, z);
+                    for sprite_order in &sprite_orders {
+                        let tile = match self.raw_tile(point, *sprite_order) {
+                            Some(tile) => *tile,
+                            None => continue,
+                        };
+                        let relative = match layout {
+                            Some(layout) => {
+                                let hex = AxialPoint::from_offset(point.into(), layout)
+                                    - AxialPoint::from_offset(min, layout);
+                                Point3::new(hex.q, hex.r, z)
+                            }
+                            None => Point3::new(x - min.x, y - min.y, z),
+                        };
+                        stamp.insert(StampTile {
+                            point: relative,
+                            sprite_order: *sprite_order,
+                            tile,
+                        });
+                    }
+                }
+            }
+        }
+        stamp
+    }
+
+    /// Copies the tiles between two opposite corners into a stamp and clears
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// Fails the same as [`clear_tiles`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile(Tile { point: (1, 1), sprite_index: 1, ..Default::default() }).unwrap();
+    ///
+    /// let clipboard = tilemap.cut_stamp((0, 0), (2, 2)).unwrap();
+    /// assert_eq!(clipboard.len(), 1);
+    /// assert_eq!(tilemap.get_tile((1, 1), 0), None);
+    /// ```
+    ///
+    /// [`clear_tiles`]: Tilemap::clear_tiles
+    pub fn cut_stamp<P: Into<Point2>>(
+        &mut self,
+        corner: P,
+        opposite: P,
+    ) -> TilemapResult<TileStamp> {
+        let (corner, opposite): (Point2, Point2) = (corner.into(), opposite.into());
+        let stamp = self.copy_stamp(corner, opposite);
+        let min = Point2::new(corner.x.min(opposite.x), corner.y.min(opposite.y));
+        let layout = self.topology.hex_layout();
+        let points = stamp
+            .tiles()
+            .iter()
+            .map(|stamp_tile| {
+                let point = stamp_tile.point;
+                let point = match layout {
+                    Some(layout) => {
+                        let hex = AxialPoint::from_offset(min, layout)
+                            + AxialPoint::new(point.x, point.y);
+                        let offset = hex.to_offset(layout);
+                        Point3::new(offset.x, offset.y, point.z)
+                    }
+                    None => Point3::new(min.x + point.x, min.y + point.y, point.z),
+                };
+                (point, stamp_tile.sprite_order)
+            })
+            .collect::<Vec<(Point3, usize)>>();
+        self.clear_tiles(points)?;
+        Ok(stamp)
+    }
+
+    /// Moves the tiles between two opposite corners so that their bottom left
+    /// corner is at a point, turned by a transform.
+    ///
+    /// Tiles at the destination that the region does not have are kept.
+    ///
+    /// # Errors
+    ///
+    /// Fails the same as [`cut_stamp`] and [`paste_stamp`]. The tiles are left
+    /// where they are if a chunk at the destination is missing and can not be
+    /// made.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, stamp::StampTransform};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile(Tile { point: (0, 0), sprite_index: 1, ..Default::default() }).unwrap();
+    ///
+    /// // Overlapping regions are moved as a whole.
+    /// assert!(tilemap.move_region((0, 0), (1, 1), (1, 0), StampTransform::default()).is_ok());
+    /// assert_eq!(tilemap.get_tile((0, 0), 0), None);
+    /// assert_eq!(tilemap.get_tile((1, 0), 0).map(|tile| tile.index), Some(1));
+    /// ```
+    ///
+    /// [`cut_stamp`]: Tilemap::cut_stamp
+    /// [`paste_stamp`]: Tilemap::paste_stamp
+    pub fn move_region<P, Q>(
+        &mut self,
+        corner: P,
+        opposite: P,
+        to: Q,
+        transform: StampTransform,
+    ) -> TilemapResult<()>
+    where
+        P: Into<Point2>,
+        Q: Into<Point3>,
+    {
+        if self.topology.hex_layout().is_some() && transform != StampTransform::default() {
+            return Err(ErrorKind::UnsupportedStampTransform.into());
+        }
+        let (corner, opposite): (Point2, Point2) = (corner.into(), opposite.into());
+        let stamp = self.copy_stamp(corner, opposite);
+        let tiles = self.stamp_tiles(&stamp, to.into(), transform);
+        self.check_tile_chunks(&tiles)?;
+        self.cut_stamp(corner, opposite)?;
+        self.insert_tiles(tiles)
+    }
+
+    /// Pastes a stamp with its bottom left corner at a point, turned by a
+    /// transform.
+    ///
+    /// The tiles are inserted all at once, tiles that the stamp does not have
+    /// are kept.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the stamp is rotated or flipped on a hex topology, else the
+    /// same as [`insert_tiles`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{
+    ///     chunk::RawTile,
+    ///     point::Point3,
+    ///     prelude::*,
+    ///     stamp::{StampTile, StampTransform, TileStamp},
+    /// };
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// const TABLE: usize = 4;
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// // A table that is 2 tiles wide.
+    /// let mut table = TileStamp::new(2, 1);
+    /// for x in 0..2 {
+    ///     let tile = RawTile { index: TABLE, ..Default::default() };
+    ///     table.insert(StampTile { point: Point3::new(x, 0, 0), sprite_order: 0, tile });
+    /// }
+    ///
+    /// let upright = StampTransform { rotation: 1, ..Default::default() };
+    /// assert!(tilemap.paste_stamp(&table, (4, 4), upright).is_ok());
+    /// assert_eq!(tilemap.get_tile((4, 5), 0).map(|tile| tile.rotation), Some(1));
+    /// assert_eq!(tilemap.get_tile((5, 4), 0), None);
+    /// ```
+    ///
+    /// [`insert_tiles`]: Tilemap::insert_tiles
+    pub fn paste_stamp<P: Into<Point3>>(
+        &mut self,
+        stamp: &TileStamp,
+        origin: P,
+        transform: StampTransform,
+    ) -> TilemapResult<()> {
+        let origin = origin.into();
+        let layout = self.topology.hex_layout();
+        if layout.is_some() && transform != StampTransform::default() {
+            return Err(ErrorKind::UnsupportedStampTransform.into());
+        }
+
+        let tiles = self.stamp_tiles(stamp, origin, transform);
+        self.insert_tiles(tiles)
+    }
+
+    /// Returns the tiles of a stamp placed with its bottom left corner at a
+    /// point, turned by a transform.
+    fn stamp_tiles(
+        &self,
+        stamp: &TileStamp,
+        origin: Point3,
+        transform: StampTransform,
+    ) -> Vec<Tile<Point3>> {
+        let layout = self.topology.hex_layout();
+        let stamp = stamp.transformed(transform);
+        stamp
+            .tiles()
+            .iter()
+            .map(|stamp_tile| {
+                let StampTile {
+                    point,
+                    sprite_order,
+                    tile,
+                } = *stamp_tile;
+                let point = match layout {
+                    Some(layout) => {
+                        let hex = AxialPoint::from_offset(origin.into(), layout)
+                            + AxialPoint::new(point.x, point.y);
+                        let offset = hex.to_offset(layout);
+                        Point3::new(offset.x, offset.y, origin.z + point.z)
+                    }
+                    None => origin + point,
+                };
+                Tile {
+                    point,
+                    sprite_order,
+                    sprite_index: tile.index,
+                    tint: tile.color,
+                    flip: tile.flip,
+                    rotation: tile.rotation,
+                }
+            })
+            .collect::<Vec<Tile<Point3>>>()
+    }
+
+    /// Checks that the chunks of all the tiles exist or can be made.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a chunk is missing and can not be made because auto chunk is
+    /// off or the chunk is outside of the tilemap dimensions.
+    fn check_tile_chunks(&self, tiles: &[Tile<Point3>]) -> TilemapResult<()> {
+        for tile in tiles {
+            let chunk_point: Point2 = self.point_to_chunk_point(tile.point).into();
+            if self.chunks.contains_key(&chunk_point) {
+                continue;
+            }
+            if !self.auto_flags.contains(AutoFlags::AUTO_CHUNK) {
+                return Err(ErrorKind::MissingChunk.into());
+            }
+            if let Some(dimensions) = &self.dimensions {
+                dimensions.check_point(chunk_point)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the points that can be seen from an origin within a radius,
+    /// looking past the tiles of a sprite order.
+    ///
+    /// The opaque function returns `true` if a point with its raw tile blocks
+    /// the view. Tiles that block the view can be seen themselves. Square
+    /// topologies use symmetric shadowcasting, hex topologies see every hex
+    /// with a line from the origin that is not blocked.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{point::Point3, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let visible = tilemap.field_of_view((0, 0), 3, 0, |_, _| false);
+    ///
+    /// assert!(visible.contains(&Point3::new(0, 3, 0)));
+    /// assert!(visible.contains(&Point3::new(2, 2, 0)));
+    /// assert!(!visible.contains(&Point3::new(3, 3, 0)));
+    /// ```
+    pub fn field_of_view<P, F>(
+        &self,
+        origin: P,
+        radius: u32,
+        sprite_order: usize,
+        mut opaque: F,
+    ) -> HashSet<Point3>
+    where
+        P: Into<Point3>,
+        F: FnMut(Point3, Option<&RawTile>) -> bool,
+    {
+        let origin = origin.into();
+        let opaque = |point| opaque(point, self.raw_tile(point, sprite_order));
+        match self.topology.hex_layout() {
+            Some(layout) => fov::hex_cast(origin, radius, layout, opaque),
+            None => fov::shadowcast(origin, radius, opaque),
+        }
+    }
+
+    /// Returns the points on a straight line between two points, including
+    /// both.
+    ///
+    /// Square topologies use Bresenham's line, hex topologies the hexes the
+    /// line passes through.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{point::Point3, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let line = tilemap.line((0, 0), (4, 2));
+    /// assert_eq!(line.len(), 5);
+    /// assert_eq!(line[2], Point3::new(2, 1, 0));
+    /// ```
+    pub fn line<P: Into<Point3>>(&self, from: P, to: P) -> Vec<Point3> {
+        let (from, to) = (from.into(), to.into());
+        match self.topology.hex_layout() {
+            Some(layout) => fov::hex_line(from, to, layout),
+            None => fov::line(from, to),
+        }
+    }
+
+    /// Returns `true` if none of the points on the [`line`] between two points
+    /// block the view, not counting both ends.
+    ///
+    /// The opaque function is the same as that of [`field_of_view`].
+    ///
+    /// [`line`]: Tilemap::line
+    /// [`field_of_view`]: Tilemap::field_of_view
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{chunk::RawTile, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile(Tile { point: (2, 0), sprite_index: 1, ..Default::default() }).unwrap();
+    ///
+    /// let opaque = |_, tile: Option<&RawTile>| tile.is_some();
+    /// assert!(!tilemap.line_of_sight((0, 0), (4, 0), 0, opaque));
+    /// assert!(tilemap.line_of_sight((0, 0), (2, 0), 0, opaque));
+    /// assert!(tilemap.line_of_sight((0, 0), (4, 3), 0, opaque));
+    /// ```
+    pub fn line_of_sight<P, F>(&self, from: P, to: P, sprite_order: usize, mut opaque: F) -> bool
+    where
+        P: Into<Point3>,
+        F: FnMut(Point3, Option<&RawTile>) -> bool,
+    {
+        let line = self.line(from, to);
+        let between = line.len().saturating_sub(1);
+        line.into_iter()
+            .take(between)
+            .skip(1)
+            .all(|point| !opaque(point, self.raw_tile(point, sprite_order)))
+    }
+
+    /// Clears a layer of all the tiles.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::render::prelude::*;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .dimensions(1, 1)
+    ///     .texture_dimensions(32, 32)
+    ///     .add_layer( TilemapLayer { kind: LayerKind::Dense}, 0)
+    ///     .add_layer( TilemapLayer { kind: LayerKind::Sparse}, 1)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// assert!(tilemap.clear_layer(0).is_ok());
+    /// assert!(tilemap.clear_layer(1).is_ok());
+    /// assert!(tilemap.clear_layer(2).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Fails if the layer does not exist.
+    pub fn clear_layer(&mut self, layer: usize) -> Result<(), TilemapError> {
+        if let Some(l) = self.layers.get(layer) {
+            if l.is_none() {
+                return Err(ErrorKind::LayerDoesNotExist(layer).into());
+            }
+        } else {
+            return Err(ErrorKind::LayerDoesNotExist(layer).into());
+        }
+
+        self.flush_tile_writes();
+        let width = self.chunk_dimensions.width as usize;
+        let height = self.chunk_dimensions.height as usize;
+        let tracked = self.tracks_tiles();
+        let mut changes = Vec::new();
+        for (chunk_point, chunk) in self.chunks.iter_mut() {
+            if tracked {
+                for (index, sprite_order, z_depth) in chunk.tile_indices() {
+                    if sprite_order != layer {
+                        continue;
+                    }
+                    let old = chunk.get_tile(index, sprite_order, z_depth).copied();
+                    let tile_point = Point3::new(
+                        (index % width) as i32,
+                        ((index / width) % height) as i32,
+                        z_depth as i32,
+                    );
+                    changes.push((*chunk_point, tile_point, sprite_order, old, None));
+                }
+            }
+            chunk.clear_layer(layer);
+            chunk.clear_layer_tile_data(layer);
+            self.chunk_events.send(TilemapChunkEvent::Modified {
+                point: *chunk_point,
+            });
+        }
+        self.send_tile_changes(changes);
+
+        Ok(())
+    }
+
+    /// Begins a named transaction that records all edits until it is
+    /// committed, which can then be undone and redone together.
+    ///
+    /// Tiles that are inserted, cleared or changed through a mutable
+    /// reference, data values, cleared layers, moved layers and removed
+    /// layers are recorded. Tile data is not recorded, so it can not be
+    /// changed during a transaction and tile data of cleared tiles is not
+    /// restored.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a transaction was already begun and not yet committed.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert!(tilemap.begin_transaction("paint").is_ok());
+    /// assert!(tilemap.begin_transaction("erase").is_err());
+    /// ```
+    pub fn begin_transaction<S: Into<String>>(&mut self, name: S) -> TilemapResult<()> {
+        // Earlier writes are not part of the transaction.
+        self.flush_tile_writes();
+        if self.history.begin(name.into()) {
+            Ok(())
+        } else {
+            Err(ErrorKind::TransactionInProgress.into())
+        }
+    }
+
+    /// Commits the transaction that was begun so that it can be undone.
+    ///
+    /// Nothing can be redone after a transaction is committed. A transaction
+    /// without any edits is not kept.
+    ///
+    /// # Errors
+    ///
+    /// Fails if no transaction was begun.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.begin_transaction("paint").unwrap();
+    /// tilemap.insert_tile(Tile { point: (1, 1), ..Default::default() }).unwrap();
+    ///
+    /// assert!(tilemap.commit_transaction().is_ok());
+    /// assert!(tilemap.commit_transaction().is_err());
+    /// assert!(tilemap.history().can_undo());
+    /// ```
+    pub fn commit_transaction(&mut self) -> TilemapResult<()> {
+        self.flush_tile_writes();
+        if self.history.commit() {
+            Ok(())
+        } else {
+            Err(ErrorKind::NoTransaction.into())
+        }
+    }
+
+    /// Undoes the latest transaction and returns its name, or `None` if there
+    /// is nothing to undo.
+    ///
+    /// Tiles are restored without autotiling and every chunk they are in is
+    /// marked as modified. Chunks that were removed since are inserted again.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a transaction was begun and not yet committed, or if an edit
+    /// can not be applied, such as a chunk that can not be loaded. The
+    /// transaction is then kept in the history and the edits before it stay
+    /// applied.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.insert_tile(Tile { point: (1, 1), sprite_index: 1, ..Default::default() }).unwrap();
+    ///
+    /// tilemap.begin_transaction("repaint").unwrap();
+    /// tilemap.insert_tile(Tile { point: (1, 1), sprite_index: 2, ..Default::default() }).unwrap();
+    /// tilemap.commit_transaction().unwrap();
+    ///
+    /// assert_eq!(tilemap.undo().unwrap(), Some("repaint".to_string()));
+    /// assert_eq!(tilemap.get_tile((1, 1), 0).map(|tile| tile.index), Some(1));
+    /// assert_eq!(tilemap.undo().unwrap(), None);
+    /// ```
+    pub fn undo(&mut self) -> TilemapResult<Option<String>> {
+        if self.history.is_recording() {
+            return Err(ErrorKind::TransactionInProgress.into());
+        }
+        self.flush_tile_writes();
+        let transaction = match self.history.next_undo() {
+            Some(transaction) => transaction.clone(),
+            None => return Ok(None),
+        };
+        self.apply_edits(transaction.edits().iter().rev(), true)?;
+        self.history.undone();
+        Ok(Some(transaction.name().to_string()))
+    }
+
+    /// Redoes the latest undone transaction and returns its name, or `None`
+    /// if there is nothing to redo.
+    ///
+    /// Tiles are restored without autotiling and every chunk they are in is
+    /// marked as modified. Chunks that were removed since are inserted again.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a transaction was begun and not yet committed, or if an edit
+    /// can not be applied, such as a chunk that can not be loaded. The
+    /// transaction is then kept in the history and the edits before it stay
+    /// applied.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    ///
+    /// tilemap.begin_transaction("paint").unwrap();
+    /// tilemap.insert_tile(Tile { point: (1, 1), sprite_index: 2, ..Default::default() }).unwrap();
+    /// tilemap.commit_transaction().unwrap();
+    /// tilemap.undo().unwrap();
+    ///
+    /// assert_eq!(tilemap.redo().unwrap(), Some("paint".to_string()));
+    /// assert_eq!(tilemap.get_tile((1, 1), 0).map(|tile| tile.index), Some(2));
+    /// assert_eq!(tilemap.redo().unwrap(), None);
+    /// ```
+    pub fn redo(&mut self) -> TilemapResult<Option<String>> {
+        if self.history.is_recording() {
+            return Err(ErrorKind::TransactionInProgress.into());
+        }
+        self.flush_tile_writes();
+        let transaction = match self.history.next_redo() {
+            Some(transaction) => transaction.clone(),
+            None => return Ok(None),
+        };
+        self.apply_edits(transaction.edits().iter(), false)?;
+        self.history.redone();
+        Ok(Some(transaction.name().to_string()))
+    }
+
+    /// Applies edits in order, restoring what was before them if undoing or
+    /// what was after them if redoing.
+    ///
+    /// Tiles and data values are restored in batches between the edits of
+    /// layers.
+    fn apply_edits<'a, I>(&mut self, edits: I, undo: bool) -> TilemapResult<()>
+    where
+        I: Iterator<Item = &'a Edit>,
+    {
+        let mut tiles = Vec::new();
+        let mut data = Vec::new();
+        for edit in edits {
+            match *edit {
+                Edit::Tile {
+                    point,
+                    sprite_order,
+                    old,
+                    new,
+                } => tiles.push((point, sprite_order, if undo { old } else { new })),
+                Edit::Data {
+                    chunk_point,
+                    index,
+                    data_layer,
+                    old,
+                    new,
+                } => data.push((chunk_point, index, data_layer, if undo { old } else { new })),
+                Edit::MoveLayer { from, to } => {
+                    self.restore_tiles(mem::take(&mut tiles))?;
+                    if undo {
+                        self.move_layer(to, from)?;
+                    } else {
+                        self.move_layer(from, to)?;
+                    }
+                }
+                Edit::RemoveLayer {
+                    sprite_order,
+                    layer,
+                } => {
+                    self.restore_tiles(mem::take(&mut tiles))?;
+                    if undo {
+                        self.restore_layer(sprite_order, layer)?;
+                    } else {
+                        self.remove_layer(sprite_order);
+                    }
+                }
+                Edit::RemoveDataLayer { data_layer, kind } => {
+                    self.restore_data(mem::take(&mut data))?;
+                    if undo {
+                        self.add_data_layer(kind, data_layer)?;
+                    } else {
+                        self.remove_data_layer(data_layer);
+                    }
+                }
+            }
+        }
+        self.restore_tiles(tiles)?;
+        self.restore_data(data)
+    }
+
+    /// Adds a layer that was removed back at its sprite order, moving the
+    /// sprite layers of the chunks back where they were.
+    fn restore_layer(&mut self, sprite_order: usize, layer: TilemapLayer) -> TilemapResult<()> {
+        match self.layers.get_mut(sprite_order) {
+            Some(slot @ None) => *slot = Some(layer),
+            Some(Some(_)) => return Err(ErrorKind::LayerExists(sprite_order).into()),
+            None => return Err(ErrorKind::LayerDoesNotExist(sprite_order).into()),
+        }
+        for chunk in self.chunks.values_mut() {
+            chunk.insert_sprite_layer(&layer.kind, sprite_order, self.chunk_dimensions);
+        }
+        Ok(())
+    }
+
+    /// Sets data values at indices of chunks, or clears them if they are
+    /// `None`.
+    fn restore_data(
+        &mut self,
+        data: Vec<(Point2, usize, usize, Option<i32>)>,
+    ) -> TilemapResult<()> {
+        let area = self.chunk_dimensions.area() as usize;
+        for (chunk_point, index, data_layer, value) in data {
+            let kind = match self.data_layers.get(&data_layer) {
+                Some(kind) => *kind,
+                None => return Err(ErrorKind::DataLayerDoesNotExist(data_layer).into()),
+            };
+            if !self.chunks.contains_key(&chunk_point) {
+                self.insert_chunk(chunk_point)?;
+            }
+            let chunk = match self.chunks.get_mut(&chunk_point) {
+                Some(chunk) => chunk,
+                None => return Err(ErrorKind::MissingChunk.into()),
+            };
+            match value {
+                Some(value) => chunk.set_data(index, data_layer, kind, value, area),
+                None => chunk.remove_data(index, data_layer),
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets raw tiles at global points and sprite orders, or clears them if
+    /// they are `None`, marking every chunk they are in as modified.
+    ///
+    /// Later tiles at the same point overwrite earlier ones.
+    fn restore_tiles(&mut self, tiles: Vec<(Point3, usize, Option<RawTile>)>) -> TilemapResult<()> {
+        let mut changes = Vec::new();
+        let mut modified = HashSet::default();
+        for (point, sprite_order, tile) in tiles {
+            let chunk_point: Point2 = self.point_to_chunk_point(point).into();
+            if !self.chunks.contains_key(&chunk_point) {
+                self.insert_chunk(chunk_point)?;
+            }
+            let tile_point = self.point_to_tile_point(point);
+            let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
+            let z_depth = point.z as usize;
+            let chunk = match self.chunks.get_mut(&chunk_point) {
+                Some(chunk) => chunk,
+                None => return Err(ErrorKind::MissingChunk.into()),
+            };
+            let old = chunk.get_tile(index, sprite_order, z_depth).copied();
+            match tile {
+                Some(tile) => chunk.set_tile(
+                    index,
+                    Tile {
+                        point: tile_point,
+                        sprite_order,
+                        sprite_index: tile.index,
+                        tint: tile.color,
+                        flip: tile.flip,
+                        rotation: tile.rotation,
+                    },
+                ),
+                None => chunk.remove_tile(index, sprite_order, z_depth),
+            }
+            let new = chunk.get_tile(index, sprite_order, z_depth).copied();
+            changes.push((chunk_point, tile_point, sprite_order, old, new));
+            modified.insert(chunk_point);
+        }
+        for point in modified {
+            self.chunk_events
+                .send(TilemapChunkEvent::Modified { point });
+        }
+        self.send_tile_changes(changes);
+        Ok(())
+    }
+
+    /// Returns a reference to the history of transactions.
+    ///
+    /// The history is saved with the tilemap and can also be serialized on
+    /// its own.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// assert!(!tilemap.history().can_undo());
+    /// assert_eq!(tilemap.history().max_len(), 100);
+    /// ```
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Returns a mutable reference to the history of transactions, for example
+    /// to clear it or to change how many transactions are kept.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{history::History, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.history_mut().set_max_len(10);
+    /// *tilemap.history_mut() = History::new(20);
+    ///
+    /// assert_eq!(tilemap.history().max_len(), 20);
+    /// ```
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// Returns the center tile, if the tilemap has dimensions.
+    ///
+    /// Returns `None` if the tilemap has no constrained dimensions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle.clone_weak())
+    ///     .dimensions(32, 32)
+    ///     .texture_dimensions(32, 32)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// let center = tilemap.center_tile_coord();
+    ///
+    /// // 32 * 32 / 2 = 512
+    /// assert_eq!(center, Some((512, 512)));
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let center = tilemap.center_tile_coord();
+    ///
+    /// assert_eq!(center, None);
+    /// ```
+    pub fn center_tile_coord(&self) -> Option<(i32, i32)> {
+        self.dimensions.map(|dimensions| {
+            (
+                (dimensions.width / 2 * self.chunk_dimensions.width) as i32,
+                (dimensions.height / 2 * self.chunk_dimensions.height) as i32,
+            )
+        })
+    }
+
+    /// The width of the tilemap in chunks, if it has dimensions.
     ///
     /// # Examples
     /// ```
     /// use bevy::asset::{prelude::*, HandleId};
     /// use bevy::sprite::prelude::*;
-    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    /// use bevy_tilemap::prelude::*;
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
-    ///
-    /// assert!(tilemap.insert_chunk((0, 0)).is_ok());
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle.clone_weak())
+    ///     .dimensions(32, 64)
+    ///     .texture_dimensions(32, 32)
+    ///     .finish()
+    ///     .unwrap();
     ///
-    /// let point = (3, 1);
-    /// let sprite_index = 1;
-    /// let tile = Tile { point, sprite_index, ..Default::default() };
+    /// let width = tilemap.width();
     ///
-    /// // Set a single tile and unwrap the result
-    /// assert!(tilemap.insert_tile(tile).is_ok());
+    /// assert_eq!(width, Some(32));
     ///
-    /// // Later on...
-    /// assert!(tilemap.clear_tile(point, 0).is_ok());
-    /// assert_eq!(tilemap.get_tile((3, 1), 0), None);
-    /// ```
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
     ///
-    /// # Errors
+    /// let width = tilemap.width();
     ///
-    /// An error can occure if the point is outside of the tilemap. This can
-    /// only happen if the tilemap has dimensions.
-    pub fn clear_tile<P>(&mut self, point: P, sprite_order: usize) -> TilemapResult<()>
-    where
-        P: Into<Point3>,
-    {
-        let points = vec![(point, sprite_order)];
-        self.clear_tiles(points)
+    /// assert_eq!(width, None);
+    /// ```
+    pub fn width(&self) -> Option<u32> {
+        self.dimensions.map(|dimensions| dimensions.width)
     }
 
-    /// Gets a raw tile from a given point and z order.
-    ///
-    /// This is different thant he usual [`Tile`] struct in that it only
-    /// contains the sprite index and the tint.
-    ///
-    /// [`Tile`]: crate::tile::Tile
+    /// The height of the tilemap in chunks, if it has dimensions.
     ///
     /// # Examples
     /// ```
     /// use bevy::asset::{prelude::*, HandleId};
-    /// use bevy::render::prelude::*;
     /// use bevy::sprite::prelude::*;
-    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    /// use bevy_tilemap::prelude::*;
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle.clone_weak())
+    ///     .dimensions(32, 64)
+    ///     .texture_dimensions(32, 32)
+    ///     .finish()
+    ///     .unwrap();
     ///
-    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// let height = tilemap.height();
     ///
-    /// let point = (9, 3);
-    /// let sprite_index = 3;
-    /// let tile = Tile { point, sprite_index, ..Default::default() };
+    /// assert_eq!(height, Some(64));
     ///
-    /// assert!(tilemap.insert_tile(tile).is_ok());
-    /// assert_eq!(tilemap.get_tile((9, 3), 0), Some(&RawTile { index: 3, color: Color::WHITE }));
-    /// assert_eq!(tilemap.get_tile((10, 4), 0), None);
+    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// let height = tilemap.height();
+    ///
+    /// assert_eq!(height, None);
     /// ```
-    pub fn get_tile<P>(&mut self, point: P, sprite_order: usize) -> Option<&RawTile>
-    where
-        P: Into<Point3>,
-    {
-        let point: Point3 = point.into();
-        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
-        let tile_point = self.point_to_tile_point(point);
-        let chunk = self.chunks.get(&chunk_point)?;
-        let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
-        chunk.get_tile(index, sprite_order, point.z as usize)
+    pub fn height(&self) -> Option<u32> {
+        self.dimensions.map(|dimensions| dimensions.height)
     }
 
-    /// Gets a mutable raw tile from a given point and z order.
-    ///
-    /// This is different thant he usual [`Tile`] struct in that it only
-    /// contains the sprite index and the tint.
-    ///
-    /// [`Tile`]: crate::tile::Tile
+    /// The width of all the chunks in tiles.
     ///
     /// # Examples
     /// ```
     /// use bevy::asset::{prelude::*, HandleId};
-    /// use bevy::render::prelude::*;
     /// use bevy::sprite::prelude::*;
-    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    /// use bevy_tilemap::prelude::*;
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
-    ///
-    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .chunk_dimensions(32, 64, 1)
+    ///     .texture_dimensions(32, 32)
+    ///     .finish()
+    ///     .unwrap();
     ///
-    /// let point = (2, 5);
-    /// let sprite_index = 2;
-    /// let tile = Tile { point, sprite_index, ..Default::default() };
+    /// let chunk_width: u32 = tilemap.chunk_width();
     ///
-    /// assert!(tilemap.insert_tile(tile).is_ok());
-    /// assert_eq!(tilemap.get_tile_mut((2, 5), 0), Some(&mut RawTile { index: 2, color: Color::WHITE }));
-    /// assert_eq!(tilemap.get_tile_mut((1, 4), 0), None);
+    /// assert_eq!(chunk_width, 32);
     /// ```
-    pub fn get_tile_mut<P>(&mut self, point: P, sprite_order: usize) -> Option<&mut RawTile>
-    where
-        P: Into<Point3>,
-    {
-        let point: Point3 = point.into();
-        let chunk_point: Point2 = self.point_to_chunk_point(point).into();
-        let tile_point = self.point_to_tile_point(point);
-        let chunk = self.chunks.get_mut(&chunk_point)?;
-        let index = self.chunk_dimensions.encode_point_unchecked(tile_point);
-        let mut layers = HashMap::default();
-        layers.insert(sprite_order, chunk_point);
-        self.chunk_events.send(TilemapChunkEvent::Modified {
-            point: chunk.point(),
-        });
-        chunk.get_tile_mut(index, sprite_order, point.z as usize)
+    pub fn chunk_width(&self) -> u32 {
+        self.chunk_dimensions.width
     }
 
-    /// Clears a layer of all the tiles.
+    /// The height of all the chunks in tiles.
     ///
     /// # Examples
     /// ```
     /// use bevy::asset::{prelude::*, HandleId};
-    /// use bevy::render::prelude::*;
     /// use bevy::sprite::prelude::*;
-    /// use bevy_tilemap::{prelude::*, chunk::RawTile};
+    /// use bevy_tilemap::prelude::*;
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let mut tilemap = TilemapBuilder::new()
+    /// let tilemap = TilemapBuilder::new()
     ///     .texture_atlas(texture_atlas_handle)
-    ///     .dimensions(1, 1)
+    ///     .chunk_dimensions(32, 64, 1)
     ///     .texture_dimensions(32, 32)
-    ///     .add_layer( TilemapLayer { kind: LayerKind::Dense}, 0)
-    ///     .add_layer( TilemapLayer { kind: LayerKind::Sparse}, 1)
     ///     .finish()
     ///     .unwrap();
     ///
-    /// assert!(tilemap.clear_layer(0).is_ok());
-    /// assert!(tilemap.clear_layer(1).is_ok());
-    /// assert!(tilemap.clear_layer(2).is_err());
-    /// ```
+    /// let chunk_height: u32 = tilemap.chunk_height();
     ///
-    /// # Errors
-    /// Fails if the layer does not exist.
-    pub fn clear_layer(&mut self, layer: usize) -> Result<(), TilemapError> {
-        if let Some(l) = self.layers.get(layer) {
-            if l.is_none() {
-                return Err(ErrorKind::LayerDoesNotExist(layer).into());
-            }
-        } else {
-            return Err(ErrorKind::LayerDoesNotExist(layer).into());
-        }
-
-        for chunk in self.chunks.values_mut() {
-            chunk.clear_layer(layer);
-        }
-
-        Ok(())
+    /// assert_eq!(chunk_height, 64);
+    /// ```
+    pub fn chunk_height(&self) -> u32 {
+        self.chunk_dimensions.height
     }
 
-    /// Returns the center tile, if the tilemap has dimensions.
-    ///
-    /// Returns `None` if the tilemap has no constrained dimensions.
+    /// The width of a tile in pixels.
     ///
     /// # Examples
-    ///
     /// ```
     /// use bevy::asset::{prelude::*, HandleId};
     /// use bevy::sprite::prelude::*;
@@ -1773,34 +5789,60 @@ impl Tilemap {
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let mut tilemap = TilemapBuilder::new()
-    ///     .texture_atlas(texture_atlas_handle.clone_weak())
-    ///     .dimensions(32, 32)
-    ///     .texture_dimensions(32, 32)
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 64)
     ///     .finish()
     ///     .unwrap();
     ///
-    /// let center = tilemap.center_tile_coord();
+    /// let tile_width: u32 = tilemap.tile_width();
     ///
-    /// // 32 * 32 / 2 = 512
-    /// assert_eq!(center, Some((512, 512)));
+    /// assert_eq!(tile_width, 32);
+    /// ```
+    pub fn tile_width(&self) -> u32 {
+        self.texture_dimensions.width
+    }
+
+    /// The height of a tile in pixels.
     ///
-    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::prelude::*;
     ///
-    /// let center = tilemap.center_tile_coord();
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// assert_eq!(center, None);
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 64)
+    ///     .finish()
+    ///     .unwrap();
+    ///
+    /// let tile_height: u32 = tilemap.tile_height();
+    ///
+    /// assert_eq!(tile_height, 64);
     /// ```
-    pub fn center_tile_coord(&self) -> Option<(i32, i32)> {
-        self.dimensions.map(|dimensions| {
-            (
-                (dimensions.width / 2 * self.chunk_dimensions.width) as i32,
-                (dimensions.height / 2 * self.chunk_dimensions.height) as i32,
-            )
-        })
+    pub fn tile_height(&self) -> u32 {
+        self.texture_dimensions.height
     }
 
-    /// The width of the tilemap in chunks, if it has dimensions.
+    /// Gets a reference to a chunk.
+    pub(crate) fn get_chunk(&self, point: &Point2) -> Option<&Chunk> {
+        self.chunks.get(point)
+    }
+
+    /// The topology of the tilemap grid.
+    ///
+    /// Currently there are 7 topologies which are set with [`GridTopology`]. By
+    /// default this is square as it is the most common topology.
+    ///
+    /// Typically, for most situations squares are used for local maps and hex
+    /// is used for war games or world maps. It is easier to define structures
+    /// with walls and floors with square but not impossible with hex.
+    ///
+    /// [`GridTopology`]: crate::chunk::render::GridTopology
     ///
     /// # Examples
     /// ```
@@ -1812,27 +5854,43 @@ impl Tilemap {
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
     /// let tilemap = TilemapBuilder::new()
-    ///     .texture_atlas(texture_atlas_handle.clone_weak())
-    ///     .dimensions(32, 64)
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .topology(GridTopology::HexX)
     ///     .texture_dimensions(32, 32)
     ///     .finish()
     ///     .unwrap();
     ///
-    /// let width = tilemap.width();
+    /// assert_eq!(tilemap.topology(), GridTopology::HexX);
+    /// ```
+    pub fn topology(&self) -> GridTopology {
+        self.topology
+    }
+
+    /// Returns the fog of war settings, if the tilemap has fog of war.
     ///
-    /// assert_eq!(width, Some(32));
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{fog::FogOfWar, prelude::*};
     ///
-    /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let width = tilemap.width();
+    /// let tilemap = TilemapBuilder::new()
+    ///     .texture_atlas(texture_atlas_handle)
+    ///     .texture_dimensions(32, 32)
+    ///     .fog_of_war(FogOfWar::default())
+    ///     .finish()
+    ///     .unwrap();
     ///
-    /// assert_eq!(width, None);
+    /// assert_eq!(tilemap.fog_of_war(), Some(FogOfWar::default()));
     /// ```
-    pub fn width(&self) -> Option<u32> {
-        self.dimensions.map(|dimensions| dimensions.width)
+    pub fn fog_of_war(&self) -> Option<FogOfWar> {
+        self.fog_of_war
     }
 
-    /// The height of the tilemap in chunks, if it has dimensions.
+    /// Returns the custom flags of the tilemap.
     ///
     /// # Examples
     /// ```
@@ -1843,28 +5901,18 @@ impl Tilemap {
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let tilemap = TilemapBuilder::new()
-    ///     .texture_atlas(texture_atlas_handle.clone_weak())
-    ///     .dimensions(32, 64)
-    ///     .texture_dimensions(32, 32)
-    ///     .finish()
-    ///     .unwrap();
-    ///
-    /// let height = tilemap.height();
-    ///
-    /// assert_eq!(height, Some(64));
-    ///
     /// let tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
     ///
-    /// let height = tilemap.height();
-    ///
-    /// assert_eq!(height, None);
+    /// assert!(tilemap.custom_flags().is_empty());
     /// ```
-    pub fn height(&self) -> Option<u32> {
-        self.dimensions.map(|dimensions| dimensions.height)
+    pub fn custom_flags(&self) -> &[u32] {
+        &self.custom_flags
     }
 
-    /// The width of all the chunks in tiles.
+    /// Sets the custom flags at an index, any flags before it that were never
+    /// set are 0.
+    ///
+    /// A [`TilemapEvent::CustomFlagsChanged`] is sent if the flags changed.
     ///
     /// # Examples
     /// ```
@@ -1875,22 +5923,29 @@ impl Tilemap {
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let tilemap = TilemapBuilder::new()
-    ///     .texture_atlas(texture_atlas_handle)
-    ///     .chunk_dimensions(32, 64, 1)
-    ///     .texture_dimensions(32, 32)
-    ///     .finish()
-    ///     .unwrap();
+    /// const RAINING: u32 = 0b01;
     ///
-    /// let chunk_width: u32 = tilemap.chunk_width();
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
     ///
-    /// assert_eq!(chunk_width, 32);
+    /// tilemap.set_custom_flags(1, RAINING);
+    ///
+    /// assert_eq!(tilemap.custom_flags(), &[0, RAINING]);
     /// ```
-    pub fn chunk_width(&self) -> u32 {
-        self.chunk_dimensions.width
+    ///
+    /// [`TilemapEvent::CustomFlagsChanged`]: crate::event::TilemapEvent::CustomFlagsChanged
+    pub fn set_custom_flags(&mut self, index: usize, flags: u32) {
+        if self.custom_flags.len() <= index {
+            self.custom_flags.resize(index + 1, 0);
+        }
+        if let Some(custom_flags) = self.custom_flags.get_mut(index) {
+            if *custom_flags != flags {
+                *custom_flags = flags;
+                self.events.send(TilemapEvent::CustomFlagsChanged { index });
+            }
+        }
     }
 
-    /// The height of all the chunks in tiles.
+    /// Returns `true` if an event is sent for every tile that changes.
     ///
     /// # Examples
     /// ```
@@ -1903,45 +5958,57 @@ impl Tilemap {
     ///
     /// let tilemap = TilemapBuilder::new()
     ///     .texture_atlas(texture_atlas_handle)
-    ///     .chunk_dimensions(32, 64, 1)
     ///     .texture_dimensions(32, 32)
+    ///     .tile_events()
     ///     .finish()
     ///     .unwrap();
     ///
-    /// let chunk_height: u32 = tilemap.chunk_height();
-    ///
-    /// assert_eq!(chunk_height, 64);
+    /// assert!(tilemap.tile_events());
     /// ```
-    pub fn chunk_height(&self) -> u32 {
-        self.chunk_dimensions.height
+    pub fn tile_events(&self) -> bool {
+        self.tile_events
     }
 
-    /// The width of a tile in pixels.
+    /// Sets if an event is sent for every tile that changes.
+    ///
+    /// Tiles are inserted, cleared and borrowed mutably faster without them,
+    /// which matters most for tilemaps that change many tiles every frame.
     ///
     /// # Examples
     /// ```
     /// use bevy::asset::{prelude::*, HandleId};
     /// use bevy::sprite::prelude::*;
-    /// use bevy_tilemap::prelude::*;
+    /// use bevy_tilemap::{event::TilemapEvent, point::Point3, prelude::*};
     ///
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let tilemap = TilemapBuilder::new()
-    ///     .texture_atlas(texture_atlas_handle)
-    ///     .texture_dimensions(32, 64)
-    ///     .finish()
-    ///     .unwrap();
-    ///
-    /// let tile_width: u32 = tilemap.tile_width();
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
     ///
-    /// assert_eq!(tile_width, 32);
+    /// tilemap.insert_chunk((0, 0)).unwrap();
+    /// tilemap.set_tile_events(true);
+    /// tilemap.insert_tile(Tile { point: (1, 2), sprite_index: 3, ..Default::default() }).unwrap();
+    ///
+    /// let events = tilemap.events();
+    /// let mut reader = events.get_reader();
+    /// match reader.iter(events).next() {
+    ///     Some(TilemapEvent::TileChanged { point, old, new, .. }) => {
+    ///         assert_eq!(*point, Point3::new(1, 2, 0));
+    ///         assert!(old.is_none());
+    ///         assert_eq!(new.map(|tile| tile.index), Some(3));
+    ///     }
+    ///     _ => panic!("expected a tile change"),
+    /// };
     /// ```
-    pub fn tile_width(&self) -> u32 {
-        self.texture_dimensions.width
+    pub fn set_tile_events(&mut self, tile_events: bool) {
+        if !tile_events {
+            self.tile_writes.clear();
+        }
+        self.tile_events = tile_events;
     }
 
-    /// The height of a tile in pixels.
+    /// Returns `true` if chunks are generated and built on the async compute
+    /// task pool.
     ///
     /// # Examples
     /// ```
@@ -1954,33 +6021,21 @@ impl Tilemap {
     ///
     /// let tilemap = TilemapBuilder::new()
     ///     .texture_atlas(texture_atlas_handle)
-    ///     .texture_dimensions(32, 64)
+    ///     .texture_dimensions(32, 32)
+    ///     .async_chunks()
     ///     .finish()
     ///     .unwrap();
     ///
-    /// let tile_height: u32 = tilemap.tile_height();
-    ///
-    /// assert_eq!(tile_height, 64);
+    /// assert!(tilemap.async_chunks());
     /// ```
-    pub fn tile_height(&self) -> u32 {
-        self.texture_dimensions.height
-    }
-
-    /// Gets a reference to a chunk.
-    pub(crate) fn get_chunk(&self, point: &Point2) -> Option<&Chunk> {
-        self.chunks.get(point)
+    pub fn async_chunks(&self) -> bool {
+        self.async_chunks
     }
 
-    /// The topology of the tilemap grid.
+    /// Sets if chunks are generated and built on the async compute task pool.
     ///
-    /// Currently there are 7 topologies which are set with [`GridTopology`]. By
-    /// default this is square as it is the most common topology.
-    ///
-    /// Typically, for most situations squares are used for local maps and hex
-    /// is used for war games or world maps. It is easier to define structures
-    /// with walls and floors with square but not impossible with hex.
-    ///
-    /// [`GridTopology`]: crate::chunk::render::GridTopology
+    /// Chunks that are already being built are still spawned when they are
+    /// done.
     ///
     /// # Examples
     /// ```
@@ -1991,17 +6046,13 @@ impl Tilemap {
     /// // In production use a strong handle from an actual source.
     /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
     ///
-    /// let tilemap = TilemapBuilder::new()
-    ///     .texture_atlas(texture_atlas_handle)
-    ///     .topology(GridTopology::HexX)
-    ///     .texture_dimensions(32, 32)
-    ///     .finish()
-    ///     .unwrap();
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
     ///
-    /// assert_eq!(tilemap.topology(), GridTopology::HexX);
+    /// tilemap.set_async_chunks(true);
+    /// assert!(tilemap.async_chunks());
     /// ```
-    pub fn topology(&self) -> GridTopology {
-        self.topology
+    pub fn set_async_chunks(&mut self, async_chunks: bool) {
+        self.async_chunks = async_chunks;
     }
 
     /// Returns a reference to the tilemap chunk events.
@@ -2035,6 +6086,39 @@ impl Tilemap {
         self.chunk_events.update()
     }
 
+    /// Returns a reference to the events of the tilemap data, such as changed
+    /// tiles, the user data and metadata of chunks and the custom flags.
+    ///
+    /// # Examples
+    /// ```
+    /// use bevy::asset::{prelude::*, HandleId};
+    /// use bevy::ecs::event::Events;
+    /// use bevy::sprite::prelude::*;
+    /// use bevy_tilemap::{event::TilemapEvent, prelude::*};
+    ///
+    /// // In production use a strong handle from an actual source.
+    /// let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+    ///
+    /// let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+    ///
+    /// tilemap.set_custom_flags(0, 1);
+    ///
+    /// let events: &Events<TilemapEvent> = tilemap.events();
+    /// let mut reader = events.get_reader();
+    /// assert_eq!(
+    ///     reader.iter(events).next(),
+    ///     Some(&TilemapEvent::CustomFlagsChanged { index: 0 })
+    /// );
+    /// ```
+    pub fn events(&self) -> &Events<TilemapEvent> {
+        &self.events
+    }
+
+    /// Updates the tilemap events. This should only be done once per frame.
+    pub(crate) fn events_update(&mut self) {
+        self.events.update()
+    }
+
     /// Returns an option containing a Dimension2.
     pub(crate) fn auto_spawn(&self) -> Option<Dimension2> {
         self.auto_spawn
@@ -2050,9 +6134,76 @@ impl Tilemap {
         self.chunk_dimensions
     }
 
-    /// Returns a copy of the chunk's texture dimensions.
-    pub(crate) fn texture_dimensions(&self) -> Dimension2 {
-        self.texture_dimensions
+    /// Returns a reference to the animations.
+    pub(crate) fn animations(&self) -> &TileAnimations {
+        &self.animations
+    }
+
+    /// Returns a mutable reference to the animations.
+    pub(crate) fn animations_mut(&mut self) -> &mut TileAnimations {
+        &mut self.animations
+    }
+
+    /// Returns the meshes of the chunks with tiles of the changed animated
+    /// sprite indices, with the sprite indices of their current frames.
+    pub(crate) fn animated_chunk_frames(
+        &mut self,
+        changed: &HashSet<usize>,
+    ) -> Vec<(Handle<Mesh>, Vec<f32>)> {
+        let dimensions = self.chunk_dimensions;
+        let chunks = &self.chunks;
+        self.animated_chunks.retain(|point| {
+            chunks
+                .get(&point)
+                .map_or(false, |chunk| chunk.mesh().is_some())
+        });
+        let mut frames = Vec::new();
+        for (point, chunk) in self.chunks.iter() {
+            let mesh = match chunk.mesh() {
+                Some(mesh) => mesh,
+                None => continue,
+            };
+            let indexes = || chunk.tiles_to_renderer_parts(dimensions).0;
+            if let Some(indexes) = self.animated_chunks.frames(
+                *point,
+                mesh,
+                chunk.edits(),
+                &self.animations,
+                changed,
+                indexes,
+            ) {
+                frames.push((mesh.clone(), indexes));
+            }
+        }
+        frames
+    }
+
+    /// Returns the kinds of the sprite layers of new chunks.
+    pub(crate) fn layer_kinds(&self) -> Vec<Option<LayerKind>> {
+        self.layers
+            .iter()
+            .map(|layer| layer.map(|layer| layer.kind))
+            .collect()
+    }
+
+    /// Returns the generator of chunks, if there is one.
+    pub(crate) fn chunk_generator(&self) -> Option<Arc<dyn ChunkGenerator>> {
+        self.chunk_generator.clone()
+    }
+
+    /// Returns a reference to the chunks that are being built.
+    pub(crate) fn pending_chunks(&self) -> &PendingChunks {
+        &self.pending_chunks
+    }
+
+    /// Returns a mutable reference to the chunks that are being built.
+    pub(crate) fn pending_chunks_mut(&mut self) -> &mut PendingChunks {
+        &mut self.pending_chunks
+    }
+
+    /// Returns a copy of the tile scale.
+    pub(crate) fn tile_scale(&self) -> Vec3 {
+        self.tile_scale
     }
 
     /// Returns a reference to the hash set of spawned chunks.
@@ -2089,6 +6240,7 @@ impl Tilemap {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::autotile::AutotileKind;
 
     impl Tilemap {
         /// Flags a tilemap chunk that it has been modified. Intended for testing
@@ -2099,6 +6251,586 @@ mod tests {
         }
     }
 
+    #[test]
+    fn world_tile_round_trip() {
+        let topologies = vec![
+            GridTopology::Square,
+            GridTopology::HexY,
+            GridTopology::HexX,
+            GridTopology::HexEvenRows,
+            GridTopology::HexOddRows,
+            GridTopology::HexEvenCols,
+            GridTopology::HexOddCols,
+        ];
+        let mut transform = Transform::from_translation(Vec3::new(100.0, -50.0, 3.0));
+        transform.scale = Vec3::new(2.0, 2.0, 1.0);
+
+        for topology in topologies.into_iter() {
+            let tilemap = TilemapBuilder::new()
+                .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+                .topology(topology)
+                .chunk_dimensions(8, 8, 2)
+                .texture_dimensions(20, 24)
+                .tile_scale(1.5, 1.5, 1.0)
+                .layer_offset(Vec2::new(0.25, 0.5))
+                .finish()
+                .unwrap();
+
+            for z in 0..2 {
+                for y in -20..20 {
+                    for x in -20..20 {
+                        let point = Point3::new(x, y, z);
+                        let translation = tilemap.tile_to_world(&transform, point);
+                        assert_eq!(
+                            tilemap.world_to_tile(&transform, translation, z),
+                            point,
+                            "{:?} failed to round trip",
+                            topology
+                        );
+                    }
+                }
+            }
+
+            for y in -3..3 {
+                for x in -3..3 {
+                    let chunk_point = Point2::new(x, y);
+                    let translation = tilemap.chunk_to_world(&transform, chunk_point);
+                    let tile_point = tilemap.world_to_tile(&transform, translation, 0);
+                    assert_eq!(
+                        tilemap.point_to_chunk_point(tile_point),
+                        (x, y),
+                        "{:?} failed to round trip",
+                        topology
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbors_are_mutual() {
+        let topologies = vec![
+            GridTopology::Square,
+            GridTopology::HexY,
+            GridTopology::HexX,
+            GridTopology::HexEvenRows,
+            GridTopology::HexOddRows,
+            GridTopology::HexEvenCols,
+            GridTopology::HexOddCols,
+        ];
+
+        for topology in topologies.into_iter() {
+            let tilemap = TilemapBuilder::new()
+                .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+                .topology(topology)
+                .chunk_dimensions(4, 4, 1)
+                .texture_dimensions(30, 34)
+                .finish()
+                .unwrap();
+
+            for y in -10..10 {
+                for x in -10..10 {
+                    let point = Point3::new(x, y, 0);
+                    let neighbors = tilemap.neighbor_points(point);
+                    let expected = if topology == GridTopology::Square {
+                        8
+                    } else {
+                        6
+                    };
+                    assert_eq!(neighbors.len(), expected);
+                    for neighbor in neighbors {
+                        assert!(
+                            tilemap.neighbor_points(neighbor).contains(&point),
+                            "{:?} neighbors of {} are not mutual",
+                            topology,
+                            point
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hex_layout_matches_neighbors() {
+        let topologies = vec![
+            GridTopology::HexY,
+            GridTopology::HexX,
+            GridTopology::HexEvenRows,
+            GridTopology::HexOddRows,
+            GridTopology::HexEvenCols,
+            GridTopology::HexOddCols,
+        ];
+
+        for topology in topologies.into_iter() {
+            let layout = topology.hex_layout().unwrap();
+            let tilemap = TilemapBuilder::new()
+                .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+                .topology(topology)
+                .chunk_dimensions(6, 4, 1)
+                .texture_dimensions(30, 34)
+                .finish()
+                .unwrap();
+
+            for y in -10..10 {
+                for x in -10..10 {
+                    let point = Point3::new(x, y, 0);
+                    let neighbors: Vec<Point3> = AxialPoint::from_offset(point.into(), layout)
+                        .neighbors()
+                        .iter()
+                        .map(|hex| {
+                            let Point2 { x, y } = hex.to_offset(layout);
+                            Point3::new(x, y, 0)
+                        })
+                        .collect();
+                    assert_eq!(
+                        tilemap.neighbor_points(point),
+                        neighbors,
+                        "{:?} layout does not match the neighbors of {}",
+                        topology,
+                        point
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fog_visible_is_found_from_chunks() {
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .texture_dimensions(32, 32)
+            .chunk_dimensions(8, 8, 1)
+            .fog_of_war(FogOfWar::default())
+            .finish()
+            .unwrap();
+        tilemap.insert_chunk((0, 0)).unwrap();
+        let mut visible = HashSet::default();
+        visible.insert(Point3::new(1, -2, 0));
+        tilemap.update_fog(visible);
+
+        // The visible points are skipped when the tilemap is serialized.
+        tilemap.fog_visible = None;
+
+        // The tile that was visible before it was saved is explored once it
+        // is not seen anymore.
+        let changed = tilemap.update_fog(HashSet::default());
+        assert!(changed.contains(&Point2::new(0, 0)));
+        assert_eq!(tilemap.fog_state((1, -2)), Some(FogState::Explored));
+        assert_eq!(tilemap.fog_visible(), Some(&HashSet::default()));
+    }
+
+    #[test]
+    fn autotile_across_chunks() {
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .chunk_dimensions(4, 4, 1)
+            .texture_dimensions(32, 32)
+            .auto_chunk()
+            .add_terrain(0, AutotileRules::sequential(AutotileKind::Bitmask, 0))
+            .finish()
+            .unwrap();
+
+        // The points are in the chunks (0, 0) and (1, 0).
+        tilemap
+            .insert_terrains(vec![((1, 0), 0, 0), ((2, 0), 0, 0)])
+            .unwrap();
+        assert_eq!(tilemap.chunks().len(), 2);
+        assert_eq!(
+            tilemap.get_tile((1, 0), 0).map(|tile| tile.index),
+            Some(0b0010)
+        );
+        assert_eq!(
+            tilemap.get_tile((2, 0), 0).map(|tile| tile.index),
+            Some(0b1000)
+        );
+
+        tilemap.clear_tile((2, 0), 0).unwrap();
+        assert_eq!(tilemap.get_terrain((2, 0), 0), None);
+        assert_eq!(tilemap.get_tile((1, 0), 0).map(|tile| tile.index), Some(0));
+
+        // Inserting a plain tile replaces the terrain.
+        tilemap.insert_terrain((1, 1), 0, 0).unwrap();
+        assert_eq!(
+            tilemap.get_tile((1, 0), 0).map(|tile| tile.index),
+            Some(0b0001)
+        );
+        tilemap
+            .insert_tile(Tile {
+                point: (1, 1),
+                sprite_index: 40,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(tilemap.get_tile((1, 1), 0).map(|tile| tile.index), Some(40));
+        assert_eq!(tilemap.get_tile((1, 0), 0).map(|tile| tile.index), Some(0));
+    }
+
+    #[test]
+    fn autotile_keeps_how_neighbors_are_drawn() {
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .texture_dimensions(32, 32)
+            .auto_chunk()
+            .add_terrain(0, AutotileRules::sequential(AutotileKind::Bitmask, 0))
+            .finish()
+            .unwrap();
+        tilemap.insert_terrain((1, 0), 0, 0).unwrap();
+        if let Some(tile) = tilemap.get_tile_mut((1, 0), 0) {
+            tile.color = Color::RED;
+            tile.flip = crate::tile::TileFlip::HORIZONTAL;
+            tile.rotation = 3;
+        }
+
+        tilemap.insert_terrain((2, 0), 0, 0).unwrap();
+        let tile = tilemap.get_tile((1, 0), 0).copied().unwrap();
+        assert_eq!(tile.index, 0b0010);
+        assert_eq!(tile.color, Color::RED);
+        assert_eq!(tile.flip, crate::tile::TileFlip::HORIZONTAL);
+        assert_eq!(tile.rotation, 3);
+    }
+
+    #[test]
+    fn clear_layer_clears_sprite_order_at_every_depth() {
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .chunk_dimensions(4, 4, 2)
+            .texture_dimensions(32, 32)
+            .add_layer(
+                TilemapLayer {
+                    kind: LayerKind::Sparse,
+                },
+                0,
+            )
+            .add_layer(
+                TilemapLayer {
+                    kind: LayerKind::Sparse,
+                },
+                1,
+            )
+            .finish()
+            .unwrap();
+        tilemap.insert_chunk((0, 0)).unwrap();
+
+        let mut tiles = Vec::new();
+        for z in 0..2 {
+            for sprite_order in 0..2 {
+                tiles.push(Tile {
+                    point: (1, 1, z),
+                    sprite_order,
+                    sprite_index: 1,
+                    ..Default::default()
+                });
+            }
+        }
+        tilemap.insert_tiles(tiles).unwrap();
+
+        tilemap.clear_layer(1).unwrap();
+
+        for z in 0..2 {
+            assert!(tilemap.get_tile((1, 1, z), 0).is_some());
+            assert!(tilemap.get_tile((1, 1, z), 1).is_none());
+        }
+    }
+
+    #[test]
+    fn undo_restores_removed_layer_and_data() {
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .texture_dimensions(32, 32)
+            .add_layer(
+                TilemapLayer {
+                    kind: LayerKind::Sparse,
+                },
+                1,
+            )
+            .finish()
+            .unwrap();
+        tilemap.insert_chunk((0, 0)).unwrap();
+        tilemap.add_data_layer(LayerKind::Sparse, 0).unwrap();
+        let tile = Tile {
+            point: (1, 1),
+            sprite_order: 1,
+            sprite_index: 4,
+            ..Default::default()
+        };
+        tilemap.insert_tile(tile).unwrap();
+        tilemap.insert_data((1, 1), 0, 9).unwrap();
+
+        tilemap.begin_transaction("remove").unwrap();
+        tilemap.insert_data((2, 1), 0, 3).unwrap();
+        tilemap.remove_layer(1);
+        tilemap.remove_data_layer(0);
+        tilemap.commit_transaction().unwrap();
+        assert!(tilemap.get_tile((1, 1), 1).is_none());
+        assert_eq!(tilemap.get_data((1, 1), 0), None);
+
+        assert_eq!(tilemap.undo().unwrap(), Some("remove".to_string()));
+        assert_eq!(tilemap.get_tile((1, 1), 1).map(|tile| tile.index), Some(4));
+        assert_eq!(tilemap.get_data((1, 1), 0), Some(9));
+        assert_eq!(tilemap.get_data((2, 1), 0), None);
+
+        assert_eq!(tilemap.redo().unwrap(), Some("remove".to_string()));
+        assert!(tilemap.get_tile((1, 1), 1).is_none());
+        assert!(tilemap.add_data_layer(LayerKind::Sparse, 0).is_ok());
+        assert_eq!(tilemap.get_data((1, 1), 0), None);
+    }
+
+    #[test]
+    fn failed_undo_keeps_transaction() {
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .texture_dimensions(32, 32)
+            .z_layers(3)
+            .finish()
+            .unwrap();
+        tilemap.begin_transaction("move").unwrap();
+        tilemap.move_layer(0, 2).unwrap();
+        tilemap.commit_transaction().unwrap();
+        tilemap
+            .add_layer(
+                TilemapLayer {
+                    kind: LayerKind::Dense,
+                },
+                0,
+            )
+            .unwrap();
+
+        assert!(tilemap.undo().is_err());
+        assert!(tilemap.history().can_undo());
+        assert!(!tilemap.history().can_redo());
+    }
+
+    #[derive(Reflect, TypeUuid, Serialize, Deserialize, PartialEq, Debug)]
+    #[uuid = "0c8d5b8e-3f4a-4b8e-8f1d-6a2f3c9e7b15"]
+    struct Door {
+        locked: bool,
+    }
+
+    #[test]
+    fn tile_data_is_rejected_in_transaction() {
+        let mut tilemap = Tilemap::new(Handle::weak(HandleId::random::<TextureAtlas>()), 32, 32);
+        tilemap.insert_chunk((0, 0)).unwrap();
+        tilemap
+            .insert_tile_data((1, 1), 0, Door { locked: true })
+            .unwrap();
+
+        tilemap.begin_transaction("unlock").unwrap();
+        assert!(tilemap
+            .insert_tile_data((1, 1), 0, Door { locked: false })
+            .is_err());
+        assert!(tilemap.get_tile_data_mut::<_, Door>((1, 1), 0).is_none());
+        assert!(tilemap.remove_tile_data::<_, Door>((1, 1), 0).is_none());
+        tilemap.commit_transaction().unwrap();
+
+        assert_eq!(
+            tilemap.remove_tile_data((1, 1), 0),
+            Some(Door { locked: true })
+        );
+    }
+
+    #[test]
+    fn removed_layer_drops_tile_data() {
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .texture_dimensions(32, 32)
+            .add_layer(
+                TilemapLayer {
+                    kind: LayerKind::Sparse,
+                },
+                1,
+            )
+            .finish()
+            .unwrap();
+        tilemap.insert_chunk((0, 0)).unwrap();
+        tilemap
+            .insert_tile_data((1, 1), 0, Door { locked: true })
+            .unwrap();
+        tilemap
+            .insert_tile_data((1, 1), 1, Door { locked: false })
+            .unwrap();
+
+        tilemap.remove_layer(1);
+        tilemap
+            .add_layer(
+                TilemapLayer {
+                    kind: LayerKind::Sparse,
+                },
+                1,
+            )
+            .unwrap();
+        assert_eq!(tilemap.get_tile_data::<_, Door>((1, 1), 1), None);
+        assert_eq!(
+            tilemap.get_tile_data((1, 1), 0),
+            Some(&Door { locked: true })
+        );
+    }
+
+    /// Stores chunks in memory, counting how often each was stored.
+    #[derive(Default)]
+    struct MemoryStorage {
+        /// The data and the amount of stores of each chunk.
+        chunks: std::sync::Mutex<HashMap<Point2, (Vec<u8>, usize)>>,
+    }
+
+    impl MemoryStorage {
+        /// Locks the chunks.
+        fn lock(
+            &self,
+        ) -> std::io::Result<std::sync::MutexGuard<'_, HashMap<Point2, (Vec<u8>, usize)>>> {
+            self.chunks
+                .lock()
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()))
+        }
+    }
+
+    impl ChunkStorage for MemoryStorage {
+        fn store(&self, point: Point2, data: &[u8]) -> std::io::Result<()> {
+            let mut chunks = self.lock()?;
+            let stores = chunks.get(&point).map_or(0, |(_, stores)| *stores);
+            chunks.insert(point, (data.to_vec(), stores + 1));
+            Ok(())
+        }
+
+        fn load(&self, point: Point2) -> std::io::Result<Option<Vec<u8>>> {
+            let chunks = self.lock()?;
+            Ok(chunks.get(&point).map(|(data, _)| data.clone()))
+        }
+
+        fn remove(&self, point: Point2) -> std::io::Result<()> {
+            self.lock()?.remove(&point);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn unloads_longest_despawned_chunks_down_to_limit() {
+        let task_pool = IoTaskPool(bevy::tasks::TaskPool::new());
+        let storage = Arc::new(MemoryStorage::default());
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .texture_dimensions(32, 32)
+            .max_resident_chunks(2)
+            .finish()
+            .unwrap();
+        tilemap.chunk_storage = Some(storage.clone());
+
+        let points = [(0, 0), (1, 0), (2, 0), (3, 0)];
+        for point in points.iter() {
+            tilemap.insert_chunk(*point).unwrap();
+            tilemap.spawn_chunk(*point).unwrap();
+            tilemap.spawned.insert(*point);
+        }
+        // Despawned last to first, so the last chunk was despawned the
+        // longest ago.
+        for point in points.iter().rev().take(3) {
+            tilemap.despawn_chunk(*point).unwrap();
+        }
+
+        tilemap.unload_despawned_chunks(&task_pool);
+        assert_eq!(tilemap.chunks.len(), 2);
+        assert!(tilemap.contains_chunk((0, 0)));
+        assert!(tilemap.contains_chunk((1, 0)));
+        assert!(!tilemap.contains_chunk((2, 0)));
+        assert!(!tilemap.contains_chunk((3, 0)));
+        assert_eq!(
+            tilemap.unloadable_chunks,
+            VecDeque::from(vec![Point2::new(1, 0)])
+        );
+
+        // Chunks that are being written are loaded from their data.
+        tilemap.insert_chunk((3, 0)).unwrap();
+        assert!(tilemap.contains_chunk((3, 0)));
+
+        while tilemap.is_storing_chunks() {
+            tilemap.finish_stored_chunks();
+        }
+        let stored = storage.chunks.lock().unwrap();
+        assert_eq!(
+            stored.get(&Point2::new(2, 0)).map(|(_, stores)| *stores),
+            Some(1)
+        );
+        assert_eq!(
+            stored.get(&Point2::new(3, 0)).map(|(_, stores)| *stores),
+            Some(1)
+        );
+        assert!(!stored.contains_key(&Point2::new(1, 0)));
+    }
+
+    #[test]
+    fn inserting_a_stored_chunk_loads_it() {
+        let storage = Arc::new(MemoryStorage::default());
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .texture_dimensions(32, 32)
+            .auto_chunk()
+            .finish()
+            .unwrap();
+        tilemap.chunk_storage = Some(storage);
+        tilemap
+            .insert_tile(Tile {
+                point: (1, 1),
+                sprite_index: 5,
+                ..Default::default()
+            })
+            .unwrap();
+        tilemap.unload_chunk((0, 0)).unwrap();
+
+        // Auto chunking loads the chunk instead of shadowing it.
+        tilemap
+            .insert_tile(Tile {
+                point: (2, 1),
+                sprite_index: 6,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(tilemap.get_tile((1, 1), 0).map(|tile| tile.index), Some(5));
+        assert_eq!(tilemap.get_tile((2, 1), 0).map(|tile| tile.index), Some(6));
+    }
+
+    #[test]
+    fn deserializes_without_later_fields() {
+        let tilemap = Tilemap::new(Handle::weak(HandleId::random::<TextureAtlas>()), 32, 32);
+        let mut value = serde_json::to_value(&tilemap).unwrap();
+        let fields = [
+            "tile_scale",
+            "animations",
+            "autotiles",
+            "fog_of_war",
+            "data_layers",
+        ];
+        for field in fields.iter() {
+            assert!(value.as_object_mut().unwrap().remove(*field).is_some());
+        }
+
+        let tilemap: Tilemap = serde_json::from_value(value).unwrap();
+        assert_eq!(tilemap.tile_scale, Vec3::ONE);
+        assert_eq!(tilemap.animations, TileAnimations::default());
+        assert_eq!(tilemap.autotiles, Autotiles::default());
+        assert!(tilemap.fog_of_war.is_none());
+        assert!(tilemap.data_layers.is_empty());
+    }
+
+    #[test]
+    fn failed_move_keeps_region() {
+        let mut tilemap = Tilemap::new(Handle::weak(HandleId::random::<TextureAtlas>()), 32, 32);
+        tilemap.insert_chunk((0, 0)).unwrap();
+        tilemap
+            .insert_tile(Tile {
+                point: (1, 1),
+                sprite_index: 3,
+                ..Default::default()
+            })
+            .unwrap();
+
+        // The chunk at the destination is missing and auto chunk is off.
+        assert!(tilemap
+            .move_region((0, 0), (2, 2), (100, 0), StampTransform::default())
+            .is_err());
+        assert_eq!(tilemap.get_tile((1, 1), 0).map(|tile| tile.index), Some(3));
+        assert_eq!(tilemap.get_tile((101, 1), 0), None);
+    }
+
     // fn new_tilemap_no_auto() -> Tilemap {
     //     let texture_atlas_handle = Handle::weak(Handllet modified_layer = layer_query.get()eId::random::<TextureAtlas>());
 
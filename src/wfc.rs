@@ -0,0 +1,792 @@
+//! Procedural generation of tiles with wave function collapse.
+//!
+//! The [`WfcRules`] tell which tiles can be next to each other in every
+//! direction of the topology, with a weight of how often each tile is
+//! chosen. They are learned from an example region of a tilemap or added by
+//! hand. A [`WaveFunctionCollapse`] then fills any region of a tilemap with
+//! tiles that follow the rules, including next to the tiles around the region
+//! that already exist.
+//!
+//! The directions are those of the edges of a tile, 4 for square topologies
+//! starting at north and going clockwise, 6 for hex topologies in the same
+//! order as [`AxialPoint::DIRECTIONS`] for rows and starting at north for
+//! columns.
+//!
+//! Generating is deterministic for a seed. When the tiles run out somewhere
+//! the last choices are undone and others are tried, up to a max amount of
+//! backtracks after which it starts over.
+//!
+//! # Learning from an example
+//! ```
+//! use bevy::asset::{prelude::*, HandleId};
+//! use bevy::sprite::prelude::*;
+//! use bevy_tilemap::{
+//!     prelude::*,
+//!     shape::Shape,
+//!     wfc::{WaveFunctionCollapse, WfcRules},
+//! };
+//!
+//! // In production use a strong handle from an actual source.
+//! let texture_atlas_handle = Handle::weak(HandleId::random::<TextureAtlas>());
+//!
+//! const BLACK: usize = 0;
+//! const WHITE: usize = 1;
+//!
+//! let mut tilemap = Tilemap::new(texture_atlas_handle, 32, 32);
+//! tilemap.insert_chunk((0, 0)).unwrap();
+//!
+//! // A checkerboard to learn from.
+//! let mut example = Vec::new();
+//! for y in -10..-7 {
+//!     for x in -10..-7 {
+//!         let sprite_index = if (x + y) % 2 == 0 { BLACK } else { WHITE };
+//!         example.push(Tile { point: (x, y), sprite_index, ..Default::default() });
+//!     }
+//! }
+//! tilemap.insert_tiles(example).unwrap();
+//! let rules = WfcRules::from_example(&tilemap, (-10, -10), (-8, -8), 0);
+//! assert_eq!(rules.tiles().len(), 2);
+//!
+//! let region = tilemap.shape_points(&Shape::Rectangle {
+//!     corner: (0, 0).into(),
+//!     opposite: (5, 5).into(),
+//!     filled: true,
+//! });
+//! let report = WaveFunctionCollapse::new(rules)
+//!     .seed(42)
+//!     .fill(&mut tilemap, region, 0)
+//!     .unwrap();
+//! assert_eq!(report.attempts, 1);
+//!
+//! let first = tilemap.get_tile((0, 0), 0).unwrap().index;
+//! let next = tilemap.get_tile((1, 0), 0).unwrap().index;
+//! assert_ne!(first, next);
+//! ```
+//!
+//! [`AxialPoint::DIRECTIONS`]: crate::hex::AxialPoint::DIRECTIONS
+
+use crate::{
+    chunk::RawTile, lib::*, prelude::GridTopology, tile::Tile, tilemap::TilemapError, Tilemap,
+};
+
+/// The default amount of times generating starts over before it fails.
+const DEFAULT_ATTEMPTS: u32 = 10;
+/// The default amount of choices that are undone before starting over.
+const DEFAULT_MAX_BACKTRACKS: u32 = 1000;
+
+/// Which tiles can be next to each other and how often they are chosen.
+#[derive(Clone, PartialEq, Debug)]
+pub struct WfcRules {
+    /// The amount of directions of the topology.
+    directions: usize,
+    /// The tiles that can be chosen.
+    tiles: Vec<RawTile>,
+    /// How often each tile is chosen compared to the others.
+    weights: Vec<u32>,
+    /// The tiles that can be next to each tile in each direction.
+    allowed: Vec<Vec<HashSet<usize>>>,
+}
+
+impl WfcRules {
+    /// Constructs new empty rules for the directions of a topology.
+    pub fn new(topology: GridTopology) -> WfcRules {
+        let directions = if topology.hex_layout().is_some() {
+            6
+        } else {
+            4
+        };
+        WfcRules {
+            directions,
+            tiles: Vec::new(),
+            weights: Vec::new(),
+            allowed: Vec::new(),
+        }
+    }
+
+    /// Learns the rules from the tiles of a sprite order between two
+    /// opposite corners of a tilemap.
+    ///
+    /// Every tile is weighted by how often it is found and can be next to the
+    /// tiles it is next to in the example.
+    pub fn from_example<P: Into<Point2>>(
+        tilemap: &Tilemap,
+        corner: P,
+        opposite: P,
+        sprite_order: usize,
+    ) -> WfcRules {
+        let (corner, opposite): (Point2, Point2) = (corner.into(), opposite.into());
+        let min = Point2::new(corner.x.min(opposite.x), corner.y.min(opposite.y));
+        let max = Point2::new(corner.x.max(opposite.x), corner.y.max(opposite.y));
+        let inside = |point: Point3| {
+            point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y
+        };
+
+        let mut rules = WfcRules::new(tilemap.topology());
+        let mut indices = HashMap::default();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let point = Point3::new(x, y, 0);
+                if let Some(tile) = tilemap.raw_tile(point, sprite_order) {
+                    indices.insert(point, rules.add_tile(*tile, 1));
+                }
+            }
+        }
+        for (point, tile) in indices.iter() {
+            for (direction, neighbor) in rules.neighbors(tilemap, *point).iter().enumerate() {
+                if !inside(*neighbor) {
+                    continue;
+                }
+                if let Some(neighbor) = indices.get(neighbor) {
+                    rules.allow(*tile, direction, *neighbor);
+                }
+            }
+        }
+        rules
+    }
+
+    /// Adds a tile with a weight and returns its index in the rules.
+    ///
+    /// If the tile was already added the weight is added to its weight.
+    pub fn add_tile(&mut self, tile: RawTile, weight: u32) -> usize {
+        if let Some(index) = self.tiles.iter().position(|known| *known == tile) {
+            if let Some(known) = self.weights.get_mut(index) {
+                *known = known.saturating_add(weight);
+            }
+            return index;
+        }
+        self.tiles.push(tile);
+        self.weights.push(weight);
+        self.allowed.push(vec![HashSet::default(); self.directions]);
+        self.tiles.len() - 1
+    }
+
+    /// Allows a tile to be next to another in a direction, and the other way
+    /// around.
+    pub fn allow(&mut self, tile: usize, direction: usize, neighbor: usize) {
+        let direction = direction % self.directions;
+        let opposite = (direction + self.directions / 2) % self.directions;
+        if let Some(allowed) = self
+            .allowed
+            .get_mut(tile)
+            .and_then(|tile| tile.get_mut(direction))
+        {
+            allowed.insert(neighbor);
+        }
+        if let Some(allowed) = self
+            .allowed
+            .get_mut(neighbor)
+            .and_then(|neighbor| neighbor.get_mut(opposite))
+        {
+            allowed.insert(tile);
+        }
+    }
+
+    /// Returns the tiles of the rules by their index.
+    pub fn tiles(&self) -> &[RawTile] {
+        &self.tiles
+    }
+
+    /// Returns the amount of directions tiles can be next to each other in.
+    pub fn directions(&self) -> usize {
+        self.directions
+    }
+
+    /// Returns the points next to a point in each direction.
+    fn neighbors(&self, tilemap: &Tilemap, point: Point3) -> Vec<Point3> {
+        let neighbors = tilemap.neighbor_points(point);
+        // Only the edges of a square, which are every other neighbor.
+        let step = neighbors.len() / self.directions.max(1);
+        neighbors.into_iter().step_by(step.max(1)).collect()
+    }
+
+    /// Returns `true` if a tile can be next to another in a direction.
+    fn allows(&self, tile: usize, direction: usize, neighbor: usize) -> bool {
+        self.allowed
+            .get(tile)
+            .and_then(|tile| tile.get(direction))
+            .map_or(false, |allowed| allowed.contains(&neighbor))
+    }
+}
+
+/// What it took to generate the tiles.
+#[derive(Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct WfcReport {
+    /// How many times generating was started, 1 if it never started over.
+    pub attempts: u32,
+    /// How many choices were undone over all attempts.
+    pub backtracks: u32,
+}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+/// The kinds of errors that can occur when generating tiles.
+pub enum ErrorKind {
+    /// No tile fits at a point after every attempt, with what it took.
+    Contradiction(Point3, WfcReport),
+    /// The tiles could not be inserted.
+    Tilemap(TilemapError),
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        use ErrorKind::*;
+        match self {
+            Contradiction(p, report) => write!(
+                f,
+                "no tile fits at {} after {} attempts and {} backtracks, try more attempts or other rules",
+                p, report.attempts, report.backtracks
+            ),
+            Tilemap(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl Error for ErrorKind {}
+
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+/// The error type for generating tiles with wave function collapse.
+pub struct WfcError(pub Box<ErrorKind>);
+
+impl Display for WfcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for WfcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl From<ErrorKind> for WfcError {
+    fn from(kind: ErrorKind) -> WfcError {
+        WfcError(Box::new(kind))
+    }
+}
+
+impl From<TilemapError> for WfcError {
+    fn from(err: TilemapError) -> WfcError {
+        WfcError(Box::new(ErrorKind::Tilemap(err)))
+    }
+}
+
+/// A wave function collapse result.
+pub type WfcResult<T> = Result<T, WfcError>;
+
+/// A generator that fills regions of tilemaps following rules.
+#[derive(Clone, PartialEq, Debug)]
+pub struct WaveFunctionCollapse {
+    /// The rules the tiles follow.
+    rules: WfcRules,
+    /// The seed of the random choices.
+    seed: u64,
+    /// The amount of times generating starts over before it fails.
+    attempts: u32,
+    /// The amount of choices that are undone before starting over.
+    max_backtracks: u32,
+}
+
+impl WaveFunctionCollapse {
+    /// Constructs a new generator with rules, seeded with 0.
+    pub fn new(rules: WfcRules) -> WaveFunctionCollapse {
+        WaveFunctionCollapse {
+            rules,
+            seed: 0,
+            attempts: DEFAULT_ATTEMPTS,
+            max_backtracks: DEFAULT_MAX_BACKTRACKS,
+        }
+    }
+
+    /// Sets the seed of the random choices, the same seed always generates
+    /// the same tiles.
+    pub fn seed(mut self, seed: u64) -> WaveFunctionCollapse {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets how many times generating starts over before it fails, 10 by
+    /// default.
+    pub fn attempts(mut self, attempts: u32) -> WaveFunctionCollapse {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets how many choices are undone before starting over, 1000 by
+    /// default.
+    pub fn max_backtracks(mut self, max_backtracks: u32) -> WaveFunctionCollapse {
+        self.max_backtracks = max_backtracks;
+        self
+    }
+
+    /// Returns the rules of the generator.
+    pub fn rules(&self) -> &WfcRules {
+        &self.rules
+    }
+
+    /// Fills a region of a tilemap at a sprite order with tiles that follow
+    /// the rules.
+    ///
+    /// Tiles of the sprite order next to the region are kept and the tiles
+    /// next to them follow the rules as well, unless they are not in the
+    /// rules. The tiles are inserted all at once.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the point where no tile was left and the report if every
+    /// attempt failed, else the same as [`Tilemap::insert_tiles`].
+    pub fn fill<P, I>(
+        &self,
+        tilemap: &mut Tilemap,
+        region: I,
+        sprite_order: usize,
+    ) -> WfcResult<WfcReport>
+    where
+        P: Into<Point3>,
+        I: IntoIterator<Item = P>,
+    {
+        let mut points = Vec::new();
+        let mut cells: HashMap<Point3, usize> = HashMap::default();
+        for point in region {
+            let point = point.into();
+            cells.entry(point).or_insert_with(|| {
+                points.push(point);
+                points.len() - 1
+            });
+        }
+
+        // The cells next to each cell, and the tiles around the region.
+        let tile_count = self.rules.tiles.len();
+        let mut neighbors = Vec::with_capacity(points.len());
+        let mut domains = vec![vec![true; tile_count]; points.len()];
+        for (domain, point) in domains.iter_mut().zip(points.iter()) {
+            let mut cell_neighbors = Vec::with_capacity(self.rules.directions);
+            for (direction, neighbor) in self.rules.neighbors(tilemap, *point).iter().enumerate() {
+                cell_neighbors.push(cells.get(neighbor).copied());
+                if cells.contains_key(neighbor) {
+                    continue;
+                }
+                let outside = tilemap
+                    .raw_tile(*neighbor, sprite_order)
+                    .and_then(|tile| self.rules.tiles.iter().position(|known| known == tile));
+                if let Some(outside) = outside {
+                    for (tile, possible) in domain.iter_mut().enumerate() {
+                        *possible = *possible && self.rules.allows(tile, direction, outside);
+                    }
+                }
+            }
+            neighbors.push(cell_neighbors);
+        }
+
+        let mut solver = Solver {
+            rules: &self.rules,
+            neighbors,
+            random: Random::new(self.seed),
+        };
+        let mut report = WfcReport::default();
+        let mut failed = 0;
+        let mut solved = None;
+        for _ in 0..self.attempts {
+            report.attempts += 1;
+            match solver.solve(domains.clone(), self.max_backtracks, &mut report) {
+                Ok(tiles) => {
+                    solved = Some(tiles);
+                    break;
+                }
+                Err(cell) => failed = cell,
+            }
+        }
+        let solved = match solved {
+            Some(solved) => solved,
+            None => {
+                let point = points.get(failed).copied().unwrap_or_default();
+                return Err(ErrorKind::Contradiction(point, report).into());
+            }
+        };
+
+        let tiles = points
+            .iter()
+            .zip(solved)
+            .filter_map(|(point, tile)| {
+                let tile = self.rules.tiles.get(tile)?;
+                Some(Tile {
+                    point: *point,
+                    sprite_order,
+                    sprite_index: tile.index,
+                    tint: tile.color,
+                    flip: tile.flip,
+                    rotation: tile.rotation,
+                })
+            })
+            .collect::<Vec<Tile<Point3>>>();
+        tilemap.insert_tiles(tiles)?;
+        Ok(report)
+    }
+}
+
+/// A choice of a tile for a cell.
+struct Choice {
+    /// The length of the trail before the choice.
+    trail: usize,
+    /// The cell a tile was chosen for.
+    cell: usize,
+    /// The tile that was chosen.
+    tile: usize,
+}
+
+/// The possible tiles of every cell, with the tiles that were removed so
+/// choices can be undone without copying every cell.
+struct Domains {
+    /// The possible tiles of every cell.
+    cells: Vec<Vec<bool>>,
+    /// The cell and tile of every tile that was removed, in order.
+    trail: Vec<(usize, usize)>,
+}
+
+impl Domains {
+    /// Returns the possible tiles of a cell.
+    fn get(&self, cell: usize) -> Option<&[bool]> {
+        self.cells.get(cell).map(Vec::as_slice)
+    }
+
+    /// Returns `true` if any tile is possible for a cell.
+    fn any(&self, cell: usize) -> bool {
+        self.get(cell)
+            .map_or(false, |domain| domain.contains(&true))
+    }
+
+    /// Removes a tile from a cell and returns `true` if it was possible.
+    fn remove(&mut self, cell: usize, tile: usize) -> bool {
+        match self
+            .cells
+            .get_mut(cell)
+            .and_then(|domain| domain.get_mut(tile))
+        {
+            Some(possible) if *possible => {
+                *possible = false;
+                self.trail.push((cell, tile));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Leaves only one possible tile for a cell.
+    fn collapse(&mut self, cell: usize, tile: usize) {
+        let tile_count = self.get(cell).map_or(0, <[bool]>::len);
+        for other in (0..tile_count).filter(|other| *other != tile) {
+            self.remove(cell, other);
+        }
+    }
+
+    /// Puts back the tiles that were removed after the trail had a length.
+    fn undo(&mut self, length: usize) {
+        while self.trail.len() > length {
+            if let Some((cell, tile)) = self.trail.pop() {
+                if let Some(possible) = self.cells.get_mut(cell).and_then(|d| d.get_mut(tile)) {
+                    *possible = true;
+                }
+            }
+        }
+    }
+}
+
+/// Collapses the possible tiles of the cells of a region.
+struct Solver<'a> {
+    /// The rules the tiles follow.
+    rules: &'a WfcRules,
+    /// The cell next to each cell in each direction, if it is in the region.
+    neighbors: Vec<Vec<Option<usize>>>,
+    /// The random choices.
+    random: Random,
+}
+
+impl<'a> Solver<'a> {
+    /// Chooses a tile for every cell, returning the cell where no tile was
+    /// left if it failed.
+    fn solve(
+        &mut self,
+        cells: Vec<Vec<bool>>,
+        max_backtracks: u32,
+        report: &mut WfcReport,
+    ) -> Result<Vec<usize>, usize> {
+        if let Some(cell) = cells.iter().position(|domain| !domain.contains(&true)) {
+            return Err(cell);
+        }
+        let mut domains = Domains {
+            cells,
+            trail: Vec::new(),
+        };
+        let all = (0..domains.cells.len()).collect();
+        self.propagate(&mut domains, all)?;
+
+        let mut choices: Vec<Choice> = Vec::new();
+        let mut backtracks = 0;
+        while let Some(cell) = self.lowest_entropy(&domains.cells) {
+            let tile = self.choose(domains.get(cell).unwrap_or_default());
+            choices.push(Choice {
+                trail: domains.trail.len(),
+                cell,
+                tile,
+            });
+            domains.collapse(cell, tile);
+
+            let mut result = self.propagate(&mut domains, vec![cell]);
+            // Undo the choices until another tile fits.
+            while let Err(failed) = result {
+                backtracks += 1;
+                report.backtracks += 1;
+                let choice = match choices.pop() {
+                    Some(choice) if backtracks <= max_backtracks => choice,
+                    _ => return Err(failed),
+                };
+                domains.undo(choice.trail);
+                domains.remove(choice.cell, choice.tile);
+                result = if domains.any(choice.cell) {
+                    self.propagate(&mut domains, vec![choice.cell])
+                } else {
+                    Err(choice.cell)
+                };
+            }
+        }
+
+        Ok(domains
+            .cells
+            .iter()
+            .map(|domain| domain.iter().position(|possible| *possible).unwrap_or(0))
+            .collect())
+    }
+
+    /// Removes the tiles that can not be next to the possible tiles of their
+    /// neighbors, starting at some cells.
+    fn propagate(&self, domains: &mut Domains, mut open: Vec<usize>) -> Result<(), usize> {
+        let tile_count = self.rules.tiles.len();
+        while let Some(cell) = open.pop() {
+            let cell_neighbors = match self.neighbors.get(cell) {
+                Some(cell_neighbors) => cell_neighbors,
+                None => continue,
+            };
+            for (direction, neighbor) in cell_neighbors.iter().enumerate() {
+                let neighbor = match neighbor {
+                    Some(neighbor) => *neighbor,
+                    None => continue,
+                };
+                let mut allowed = vec![false; tile_count];
+                if let Some(domain) = domains.get(cell) {
+                    for tile in (0..tile_count).filter(|tile| domain.get(*tile) == Some(&true)) {
+                        let tile_allowed = self
+                            .rules
+                            .allowed
+                            .get(tile)
+                            .and_then(|tile| tile.get(direction));
+                        for next in tile_allowed.into_iter().flatten() {
+                            if let Some(allowed) = allowed.get_mut(*next) {
+                                *allowed = true;
+                            }
+                        }
+                    }
+                }
+
+                let mut changed = false;
+                for (tile, allowed) in allowed.into_iter().enumerate() {
+                    if !allowed {
+                        changed |= domains.remove(neighbor, tile);
+                    }
+                }
+                if !domains.any(neighbor) {
+                    return Err(neighbor);
+                }
+                if changed {
+                    open.push(neighbor);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a random cell out of those with the fewest possible tiles
+    /// that are not decided yet.
+    fn lowest_entropy(&mut self, domains: &[Vec<bool>]) -> Option<usize> {
+        let mut lowest = Vec::new();
+        let mut lowest_count = usize::MAX;
+        for (cell, domain) in domains.iter().enumerate() {
+            let count = domain.iter().filter(|possible| **possible).count();
+            if count <= 1 || count > lowest_count {
+                continue;
+            }
+            if count < lowest_count {
+                lowest_count = count;
+                lowest.clear();
+            }
+            lowest.push(cell);
+        }
+        let index = self.random.below(lowest.len() as u64) as usize;
+        lowest.get(index).copied()
+    }
+
+    /// Returns a random possible tile by the weights of the tiles.
+    fn choose(&mut self, domain: &[bool]) -> usize {
+        let weights = &self.rules.weights;
+        let weight = |tile: usize| u64::from(weights.get(tile).copied().unwrap_or(1).max(1));
+        let possible = domain
+            .iter()
+            .enumerate()
+            .filter(|(_, possible)| **possible)
+            .map(|(tile, _)| tile)
+            .collect::<Vec<usize>>();
+        let total = possible.iter().map(|tile| weight(*tile)).sum();
+        let mut roll = self.random.below(total);
+        for tile in possible.iter() {
+            let tile_weight = weight(*tile);
+            if roll < tile_weight {
+                return *tile;
+            }
+            roll -= tile_weight;
+        }
+        possible.first().copied().unwrap_or(0)
+    }
+}
+
+/// A small seeded random number generator, SplitMix64.
+struct Random {
+    /// The state that is advanced for every number.
+    state: u64,
+}
+
+impl Random {
+    /// Constructs a new generator from a seed.
+    fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    /// Returns the next random number.
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number below a bound, or 0 if the bound is 0.
+    fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            0
+        } else {
+            self.next() % bound
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{shape::Shape, tilemap::TilemapBuilder};
+
+    fn new_tilemap(topology: GridTopology) -> Tilemap {
+        let mut tilemap = TilemapBuilder::new()
+            .texture_atlas(Handle::weak(HandleId::random::<TextureAtlas>()))
+            .topology(topology)
+            .texture_dimensions(32, 32)
+            .finish()
+            .unwrap();
+        tilemap.insert_chunk((0, 0)).unwrap();
+        tilemap
+    }
+
+    fn raw_tile(index: usize) -> RawTile {
+        RawTile {
+            index,
+            ..Default::default()
+        }
+    }
+
+    /// Rules where tiles can be next to each other in every direction as
+    /// given by pairs.
+    fn rules(topology: GridTopology, tiles: &[u32], pairs: &[(usize, usize)]) -> WfcRules {
+        let mut rules = WfcRules::new(topology);
+        for (index, weight) in tiles.iter().enumerate() {
+            rules.add_tile(raw_tile(index), *weight);
+        }
+        for (tile, neighbor) in pairs.iter() {
+            for direction in 0..rules.directions() {
+                rules.allow(*tile, direction, *neighbor);
+            }
+        }
+        rules
+    }
+
+    #[test]
+    fn same_seed_same_tiles() {
+        let rules = rules(
+            GridTopology::Square,
+            &[1, 1, 1],
+            &[(0, 0), (0, 1), (1, 1), (1, 2), (2, 2)],
+        );
+        let region = |tilemap: &Tilemap| {
+            tilemap.shape_points(&Shape::Rectangle {
+                corner: (-4, -4).into(),
+                opposite: (4, 4).into(),
+                filled: true,
+            })
+        };
+
+        let mut tiles = Vec::new();
+        for _ in 0..2 {
+            let mut tilemap = new_tilemap(GridTopology::Square);
+            let points = region(&tilemap);
+            WaveFunctionCollapse::new(rules.clone())
+                .seed(7)
+                .fill(&mut tilemap, points.clone(), 0)
+                .unwrap();
+            let indices = points
+                .iter()
+                .map(|point| tilemap.get_tile(*point, 0).map(|tile| tile.index))
+                .collect::<Vec<Option<usize>>>();
+            assert!(indices.iter().all(Option::is_some));
+            tiles.push(indices);
+        }
+        assert_eq!(tiles.first(), tiles.last());
+    }
+
+    #[test]
+    fn impossible_rules_report_contradiction() {
+        // A single tile that can not be next to anything.
+        let rules = rules(GridTopology::Square, &[1], &[]);
+        let mut tilemap = new_tilemap(GridTopology::Square);
+
+        let err = WaveFunctionCollapse::new(rules)
+            .attempts(3)
+            .fill(&mut tilemap, vec![(0, 0), (1, 0)], 0)
+            .unwrap_err();
+        match *err.0 {
+            ErrorKind::Contradiction(_, report) => assert_eq!(report.attempts, 3),
+            kind => panic!("expected a contradiction, got {:?}", kind),
+        }
+        assert!(tilemap.get_tile((0, 0), 0).is_none());
+    }
+
+    #[test]
+    fn backtracks_out_of_dead_end() {
+        // Tile 0 is chosen first by its weight, but its neighbors can only be
+        // tile 1 which can not be next to itself. Three hexes around a corner
+        // are all next to each other, so tile 0 never fits.
+        let rules = rules(GridTopology::HexY, &[1000, 1, 1], &[(0, 1), (1, 2), (2, 2)]);
+        let mut tilemap = new_tilemap(GridTopology::HexY);
+        let center = Point3::new(0, 0, 0);
+        let neighbors = tilemap.neighbor_points(center);
+        let first = neighbors.first().copied().unwrap();
+        let second = neighbors
+            .iter()
+            .copied()
+            .find(|point| tilemap.neighbor_points(first).contains(point))
+            .unwrap();
+
+        let report = WaveFunctionCollapse::new(rules)
+            .fill(&mut tilemap, vec![center, first, second], 0)
+            .unwrap();
+        assert!(report.backtracks > 0);
+        for point in [center, first, second] {
+            assert_ne!(tilemap.get_tile(point, 0).map(|tile| tile.index), Some(0));
+        }
+    }
+}